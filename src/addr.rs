@@ -1,6 +1,7 @@
+use std::cmp::Ordering;
 use std::convert::{TryFrom, TryInto};
 use std::error::Error;
-use std::fmt;
+use std::fmt::{self, Write};
 use std::hash::Hash;
 use std::num::ParseIntError;
 use std::ops::{BitAnd, BitOr, BitXor};
@@ -44,6 +45,70 @@ pub trait IpAddress: BitAnd<Output = Self> + BitOr<Output = Self> + BitXor<Outpu
     /// Returns the difference (with borrow) between this IP address and an offset. Returns `None`
     /// if the subtraction overflows beyond the range of the IP address.
     fn subtract_offset(&self, offset: i32) -> Option<Self>;
+
+    /// Returns the sum of this IP address and an offset, wrapping around at the boundaries of the
+    /// range of the IP address.
+    fn wrapping_add_offset(&self, offset: i32) -> Self;
+
+    /// Returns the difference between this IP address and an offset, wrapping around at the
+    /// boundaries of the range of the IP address.
+    fn wrapping_subtract_offset(&self, offset: i32) -> Self;
+
+    /// Returns the value of the bit at the given index, counting from the most significant bit
+    /// (index 0). Returns `None` if the index is beyond the length of the IP address.
+    fn bit(&self, index: usize) -> Option<bool> {
+        let byte = self.to_bytes().get(index / 8).copied()?;
+        Some(byte & (1 << (7 - (index % 8))) != 0)
+    }
+
+    /// Returns this IP address with the bit at the given index, counting from the most significant
+    /// bit (index 0), set to the given value.
+    ///
+    /// Panics if the index is beyond the length of the IP address.
+    fn with_bit(&self, index: usize, value: bool) -> Self {
+        let mut bytes = self.to_bytes();
+        let bit_count = bytes.len() * 8;
+        let byte = bytes.get_mut(index / 8)
+            .unwrap_or_else(|| panic!("bit index {} out of range for a {}-bit address", index, bit_count));
+        let bit_mask = 1 << (7 - (index % 8));
+        if value {
+            *byte |= bit_mask;
+        } else {
+            *byte &= !bit_mask;
+        }
+        Self::from_bytes(&bytes)
+            .expect("same number of bytes")
+    }
+
+    /// Compares this IP address to another IP address by their numeric values, i.e. interpreting
+    /// both of them as unsigned big-endian integers. For IPv6 addresses, this means that e.g.
+    /// `2001:db8::ffff` is less than `2001:db8::1:0`.
+    ///
+    /// The `Ord` implementations of the IP address types in this crate are consistent with this
+    /// ordering; this method exists to make the guarantee explicit.
+    fn numeric_cmp(&self, other: &Self) -> Ordering {
+        self.to_bytes().cmp(&other.to_bytes())
+    }
+
+    /// Returns the lowest IP address, i.e. the one with all bits set to 0.
+    fn min_addr() -> Self;
+
+    /// Returns the highest IP address, i.e. the one with all bits set to 1.
+    fn max_addr() -> Self;
+
+    /// Returns the sum of this IP address and an offset. Returns `min_addr()` or `max_addr()` if
+    /// the addition overflows beyond the range of the IP address.
+    fn saturating_add_offset(&self, offset: i32) -> Self {
+        self.add_offset(offset)
+            .unwrap_or_else(|| if offset < 0 { Self::min_addr() } else { Self::max_addr() })
+    }
+
+    /// Returns the difference between this IP address and an offset. Returns `min_addr()` or
+    /// `max_addr()` if the subtraction overflows beyond the range of the IP address.
+    fn saturating_subtract_offset(&self, offset: i32) -> Self {
+        self.subtract_offset(offset)
+            .unwrap_or_else(|| if offset < 0 { Self::max_addr() } else { Self::min_addr() })
+    }
 }
 
 /// An IPv4 address.
@@ -66,6 +131,41 @@ impl Ipv4Address {
         }
     }
 
+    /// Returns the four bytes (octets) of this address, the most significant byte first.
+    pub fn octets(&self) -> [u8; 4] {
+        self.addr_value.to_be_bytes()
+    }
+
+    /// Outputs the IPv4 address with each octet padded with leading zeroes to three digits (e.g.
+    /// `192.000.002.001`), so that all addresses have the same width.
+    pub fn to_zero_padded_string(self) -> String {
+        let [a, b, c, d] = self.octets();
+        format!("{:03}.{:03}.{:03}.{:03}", a, b, c, d)
+    }
+
+    /// Returns the prefix length of the network of this address according to the former address
+    /// classes (8 for class A, 16 for class B, 24 for class C), or `None` for class D (multicast)
+    /// and class E (reserved) addresses, which have no natural network size.
+    pub fn classful_prefix(&self) -> Option<usize> {
+        match self.addr_value.leading_ones() {
+            0 => Some(8),
+            1 => Some(16),
+            2 => Some(24),
+            _ => None,
+        }
+    }
+
+    /// Parses an IPv4 address like `from_str`, but rejects chunks with leading zeroes (e.g.
+    /// `010.0.0.1`), which other software might interpret as octal numbers.
+    pub fn parse_strict(s: &str) -> Result<Ipv4Address, IpAddressParseError> {
+        for (i, chunk) in s.split('.').enumerate() {
+            if chunk.len() > 1 && chunk.starts_with('0') {
+                return Err(IpAddressParseError::LeadingZero(i, String::from(chunk)));
+            }
+        }
+        s.parse()
+    }
+
     fn add_internal(addr64: i64, offset64: i64) -> Option<Ipv4Address> {
         let sum = addr64 + offset64;
         if sum < 0 {
@@ -85,12 +185,7 @@ impl IpAddress for Ipv4Address {
     fn count_zeros(&self) -> u32 { self.addr_value.count_zeros() }
 
     fn to_bytes(&self) -> Vec<u8> {
-        let mut ret: Vec<u8> = Vec::with_capacity(4);
-        ret.push(((self.addr_value >> 24) & 0xFF).try_into().unwrap());
-        ret.push(((self.addr_value >> 16) & 0xFF).try_into().unwrap());
-        ret.push(((self.addr_value >>  8) & 0xFF).try_into().unwrap());
-        ret.push(((self.addr_value >>  0) & 0xFF).try_into().unwrap());
-        ret
+        self.octets().to_vec()
     }
 
     fn from_bytes(bytes: &[u8]) -> Option<Ipv4Address> {
@@ -128,6 +223,18 @@ impl IpAddress for Ipv4Address {
         let offset64: i64 = offset.into();
         Ipv4Address::add_internal(self.addr_value.into(), -offset64)
     }
+
+    fn wrapping_add_offset(&self, offset: i32) -> Ipv4Address {
+        Ipv4Address::new(self.addr_value.wrapping_add_signed(offset))
+    }
+
+    fn wrapping_subtract_offset(&self, offset: i32) -> Ipv4Address {
+        // i32::MIN.wrapping_neg() == i32::MIN, which is congruent to -i32::MIN modulo 2**32
+        Ipv4Address::new(self.addr_value.wrapping_add_signed(offset.wrapping_neg()))
+    }
+
+    fn min_addr() -> Ipv4Address { IPV4_ZERO }
+    fn max_addr() -> Ipv4Address { Ipv4Address::new(0xFFFFFFFF) }
 }
 
 impl FromStr for Ipv4Address {
@@ -140,6 +247,7 @@ impl FromStr for Ipv4Address {
         }
 
         let mut addr_val: u32 = 0;
+        let mut chunk_offset = 0;
         for i in 0..4 {
             let shift_count = 24 - (i*8);
 
@@ -148,12 +256,17 @@ impl FromStr for Ipv4Address {
             }
 
             let chunk_val: u32 = chunks[i].parse()
-                .map_err(|e| IpAddressParseError::ChunkParseError(i, String::from(chunks[i]), e))?;
+                .map_err(|e| IpAddressParseError::ChunkParseError(
+                    i, String::from(chunks[i]), e, chunk_offset + invalid_digit_index(chunks[i], 10),
+                ))?;
             if chunk_val > 255 {
                 return Err(IpAddressParseError::ChunkOutOfRange(i, chunk_val, 0, 255));
             }
 
             addr_val |= chunk_val << shift_count;
+
+            // skip the chunk and the following dot
+            chunk_offset += chunks[i].len() + 1;
         }
 
         Ok(Ipv4Address::new(addr_val))
@@ -162,8 +275,8 @@ impl FromStr for Ipv4Address {
 
 impl fmt::Display for Ipv4Address {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let bytes = self.to_bytes();
-        write!(f, "{}.{}.{}.{}", bytes[0], bytes[1], bytes[2], bytes[3])
+        let octets = self.octets();
+        write!(f, "{}.{}.{}.{}", octets[0], octets[1], octets[2], octets[3])
     }
 }
 
@@ -191,6 +304,20 @@ impl BitXor for Ipv4Address {
     }
 }
 
+#[cfg(feature = "std-net")]
+impl From<std::net::Ipv4Addr> for Ipv4Address {
+    fn from(addr: std::net::Ipv4Addr) -> Self {
+        Ipv4Address::new(addr.into())
+    }
+}
+
+#[cfg(feature = "std-net")]
+impl From<Ipv4Address> for std::net::Ipv4Addr {
+    fn from(addr: Ipv4Address) -> Self {
+        addr.addr_value.into()
+    }
+}
+
 /// An IPv6 address.
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Ipv6Address {
@@ -220,24 +347,110 @@ impl Ipv6Address {
     pub fn to_full_string(&self) -> String {
         let chunks = self.to_chunks();
         let mut chunk_strings = Vec::with_capacity(chunks.len());
-        for i in 0..chunk_strings.len() {
-            chunk_strings.push(format!("{:04x}", chunks[i]));
+        for chunk in &chunks {
+            chunk_strings.push(format!("{:04x}", chunk));
         }
         chunk_strings.join(":")
     }
 
+    /// Outputs the IPv6 address with the longest run of consecutive zero fields shortened to `::`
+    /// (as in the usual representation), but with all leading zeroes of the remaining fields (e.g.
+    /// `2001:0db8::0001`).
+    pub fn to_padded_string(&self) -> String {
+        let mut ret = String::with_capacity(39);
+        self.write_compressed(&mut ret, true, 1)
+            .expect("writing to a String does not fail");
+        ret
+    }
+
+    /// Outputs the IPv6 address in the canonical text form of RFC 5952. Unlike the usual
+    /// representation, a single zero field is not shortened to `::` (e.g. `2001:db8:0:1:1:1:1:1`).
+    pub fn to_canonical_string(&self) -> String {
+        let mut ret = String::with_capacity(39);
+        self.write_compressed(&mut ret, false, 2)
+            .expect("writing to a String does not fail");
+        ret
+    }
+
+    /// Writes the IPv6 address with the longest run of consecutive zero fields shortened to `::`,
+    /// provided the run is at least `min_zero_run` fields long. If `pad_segments` is set, the
+    /// remaining fields are padded with leading zeroes to four digits.
+    fn write_compressed<W: fmt::Write>(&self, w: &mut W, pad_segments: bool, min_zero_run: usize) -> fmt::Result {
+        let segments = self.segments();
+
+        // find the longest run of zero segments; it is shortened to "::"
+        let mut i = 0;
+        let mut zero_index: Option<usize> = None;
+        let mut zero_length = 0;
+        while i < 8 {
+            if segments[i] != 0 {
+                i += 1;
+                continue;
+            }
+
+            // zero segment!
+            let mut j = i + 1;
+            while j < 8 {
+                if segments[j] != 0 {
+                    break;
+                }
+                j += 1;
+            }
+
+            if zero_length < j - i && j - i >= min_zero_run {
+                // new longest zero run found!
+                zero_index = Some(i);
+                zero_length = j - i;
+            }
+
+            // continue at j
+            i = j;
+        }
+
+        let mut i = 0;
+        let mut need_separator = false;
+        while i < 8 {
+            if zero_index == Some(i) {
+                // the double colon also separates the surrounding segments
+                write!(w, "::")?;
+                need_separator = false;
+
+                // jump past the length
+                i += zero_length;
+            } else {
+                if need_separator {
+                    write!(w, ":")?;
+                }
+                if pad_segments {
+                    write!(w, "{:04x}", segments[i])?;
+                } else {
+                    write!(w, "{:x}", segments[i])?;
+                }
+                need_separator = true;
+                i += 1;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Returns this address represented as 16-bit chunks.
     pub fn to_chunks(&self) -> Vec<u16> {
-        let mut ret: Vec<u16> = Vec::with_capacity(8);
-        ret.push(((self.top_half >> 48) & 0xFFFF).try_into().unwrap());
-        ret.push(((self.top_half >> 32) & 0xFFFF).try_into().unwrap());
-        ret.push(((self.top_half >> 16) & 0xFFFF).try_into().unwrap());
-        ret.push(((self.top_half >>  0) & 0xFFFF).try_into().unwrap());
-        ret.push(((self.bottom_half >> 48) & 0xFFFF).try_into().unwrap());
-        ret.push(((self.bottom_half >> 32) & 0xFFFF).try_into().unwrap());
-        ret.push(((self.bottom_half >> 16) & 0xFFFF).try_into().unwrap());
-        ret.push(((self.bottom_half >>  0) & 0xFFFF).try_into().unwrap());
-        ret
+        self.segments().to_vec()
+    }
+
+    /// Returns the eight 16-bit segments of this address, the most significant segment first.
+    pub fn segments(&self) -> [u16; 8] {
+        [
+            ((self.top_half >> 48) & 0xFFFF).try_into().unwrap(),
+            ((self.top_half >> 32) & 0xFFFF).try_into().unwrap(),
+            ((self.top_half >> 16) & 0xFFFF).try_into().unwrap(),
+            (self.top_half & 0xFFFF).try_into().unwrap(),
+            ((self.bottom_half >> 48) & 0xFFFF).try_into().unwrap(),
+            ((self.bottom_half >> 32) & 0xFFFF).try_into().unwrap(),
+            ((self.bottom_half >> 16) & 0xFFFF).try_into().unwrap(),
+            (self.bottom_half & 0xFFFF).try_into().unwrap(),
+        ]
     }
 
     /// Attempts to create an IPv6 address from its 16-bit chunk representation. Returns `None` if
@@ -275,13 +488,7 @@ impl Ipv6Address {
 
     fn sub_internal(addrtop64: u64, addrbot64: u64, offtop64: u64, offbot64: u64) -> Option<Ipv6Address> {
         let bot_diff = addrbot64.wrapping_sub(offbot64);
-        let is_borrow =
-            addrtop64 < offtop64
-            || (
-                addrtop64 == offtop64
-                && addrbot64 < offbot64
-            )
-        ;
+        let is_borrow = addrbot64 < offbot64;
 
         let mut top_diff = addrtop64.checked_sub(offtop64)?;
         if is_borrow {
@@ -289,6 +496,21 @@ impl Ipv6Address {
         }
         Some(Ipv6Address::new(top_diff, bot_diff))
     }
+
+    fn wrapping_add_internal(addrtop64: u64, addrbot64: u64, offset: i64) -> Ipv6Address {
+        // sign-extend the offset to 128 bits
+        let offtop64 = if offset < 0 { 0xFFFF_FFFF_FFFF_FFFFu64 } else { 0 };
+        let offbot64 = offset as u64;
+
+        let bot_sum = addrbot64.wrapping_add(offbot64);
+        let is_carry = bot_sum < addrbot64 || bot_sum < offbot64;
+
+        let mut top_sum = addrtop64.wrapping_add(offtop64);
+        if is_carry {
+            top_sum = top_sum.wrapping_add(1);
+        }
+        Ipv6Address::new(top_sum, bot_sum)
+    }
 }
 
 impl IpAddress for Ipv6Address {
@@ -388,15 +610,44 @@ impl IpAddress for Ipv6Address {
             )
         }
     }
+
+    fn wrapping_add_offset(&self, offset: i32) -> Ipv6Address {
+        Ipv6Address::wrapping_add_internal(self.top_half, self.bottom_half, offset.into())
+    }
+
+    fn wrapping_subtract_offset(&self, offset: i32) -> Ipv6Address {
+        let offset64: i64 = offset.into();
+        Ipv6Address::wrapping_add_internal(self.top_half, self.bottom_half, -offset64)
+    }
+
+    fn min_addr() -> Ipv6Address { IPV6_ZERO }
+    fn max_addr() -> Ipv6Address { Ipv6Address::new(0xFFFF_FFFF_FFFF_FFFF, 0xFFFF_FFFF_FFFF_FFFF) }
 }
 
 impl FromStr for Ipv6Address {
     type Err = IpAddressParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // literals in URLs and configuration files are often enclosed in brackets
+        let (s, bracket_offset) = match (s.starts_with('['), s.ends_with(']')) {
+            (true, true) => (&s[1..s.len()-1], 1),
+            (false, false) => (s, 0),
+            _ => return Err(IpAddressParseError::UnbalancedBrackets),
+        };
+
+        // the shortening logic below would turn these into "::"
+        if s.is_empty() {
+            return Err(IpAddressParseError::Empty);
+        }
+        if let Some(zone) = s.strip_prefix('%') {
+            return Err(IpAddressParseError::ZoneWithoutAddress(String::from(zone)));
+        }
+
         let mut addr_str = String::from(s);
+        let mut inserted_offset = 0;
         if addr_str.starts_with(':') {
             addr_str.insert(0, '0');
+            inserted_offset = 1;
         }
         if addr_str.ends_with(':') {
             addr_str.push('0');
@@ -407,6 +658,15 @@ impl FromStr for Ipv6Address {
             return Err(IpAddressParseError::IncorrectChunkCount(chunks.len(), 8));
         }
 
+        // offset of each chunk within the original string (for error reporting)
+        let mut chunk_offsets = Vec::with_capacity(chunks.len());
+        let mut chunk_offset: usize = bracket_offset;
+        for chunk in &chunks {
+            // the inserted zero chunk has no counterpart in the original string
+            chunk_offsets.push(chunk_offset.saturating_sub(inserted_offset));
+            chunk_offset += chunk.len() + 1;
+        }
+
         // how many shortening elements do we have?
         let shortening_count = chunks.iter()
             .filter(|x| x.len() == 0)
@@ -417,6 +677,7 @@ impl FromStr for Ipv6Address {
         }
 
         let mut actual_chunks = Vec::new();
+        let mut actual_offsets = Vec::new();
         if shortening_count == 0 {
             // full address "123:45:678:9:ab:cd:ef:21"
             if chunks.len() != 8 {
@@ -427,10 +688,12 @@ impl FromStr for Ipv6Address {
             for chunk in chunks.iter() {
                 actual_chunks.push(String::from(*chunk));
             }
+            actual_offsets.extend(chunk_offsets.iter());
         } else {
             // shortened address "123::456a"
             for _ in 0..8 {
                 actual_chunks.push(String::from("0"));
+                actual_offsets.push(0);
             }
 
             // copy from front
@@ -439,6 +702,7 @@ impl FromStr for Ipv6Address {
                     break;
                 }
                 actual_chunks[i] = String::from(chunks[i]);
+                actual_offsets[i] = chunk_offsets[i];
             }
 
             // copy from back
@@ -448,6 +712,7 @@ impl FromStr for Ipv6Address {
                 }
                 let j = actual_chunks.len() - i - 1;
                 actual_chunks[j] = String::from(chunks[chunks.len() - i - 1]);
+                actual_offsets[j] = chunk_offsets[chunks.len() - i - 1];
             }
 
             // leave remaining chunks as zero
@@ -463,7 +728,9 @@ impl FromStr for Ipv6Address {
             let chunk_value = match u16::from_str_radix(&actual_chunks[i], 16) {
                 Ok(v) => v,
                 Err(e)
-                    => return Err(IpAddressParseError::ChunkParseError(i, actual_chunks[i].clone(), e)),
+                    => return Err(IpAddressParseError::ChunkParseError(
+                        i, actual_chunks[i].clone(), e, actual_offsets[i] + invalid_digit_index(&actual_chunks[i], 16),
+                    )),
             };
 
             if into_top_half {
@@ -479,67 +746,23 @@ impl FromStr for Ipv6Address {
 
 impl fmt::Display for Ipv6Address {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.top_half == 0 && self.bottom_half == 0 {
-            return write!(f, "::");
-        }
-
-        let chunks = self.to_chunks();
-
-        // attempt to shorten
-        let mut i = 0;
-        let mut zero_index: Option<usize> = None;
-        let mut zero_length = 0;
-        while i < 8 {
-            if chunks[i] != 0 {
-                i += 1;
-                continue;
-            }
-
-            // zero chunk!
-            let mut j = i + 1;
-            while j < 8 {
-                if chunks[j] != 0 {
-                    break;
-                }
-                j += 1;
-            }
-
-            if zero_length < j - i {
-                // new longest zero chunk found!
-                zero_index = Some(i);
-                zero_length = j - i;
-            }
-
-            // continue at j
-            i = j;
-        }
-
-        let mut chunk_strings = Vec::new();
-        let mut i = 0;
-        while i < 8 {
-            if zero_index == Some(i) {
-                if i == 0 {
-                    // the initial part of the address is zero
-                    chunk_strings.push(String::from(""));
-                }
-
-                // an empty chunk causes two adjacent colons
-                chunk_strings.push(String::from(""));
-
-                // jump past the length
-                i += zero_length;
+        self.write_compressed(f, false, 1)
+    }
+}
 
-                if i == 8 {
-                    // the final part of the address is zero
-                    chunk_strings.push(String::from(""));
-                }
-            } else {
-                chunk_strings.push(format!("{:x}", chunks[i]));
-                i += 1;
-            }
-        }
+#[cfg(feature = "std-net")]
+impl From<std::net::Ipv6Addr> for Ipv6Address {
+    fn from(addr: std::net::Ipv6Addr) -> Self {
+        let value: u128 = addr.into();
+        Ipv6Address::new((value >> 64).try_into().unwrap(), (value & 0xFFFF_FFFF_FFFF_FFFF).try_into().unwrap())
+    }
+}
 
-        write!(f, "{}", chunk_strings.join(":"))
+#[cfg(feature = "std-net")]
+impl From<Ipv6Address> for std::net::Ipv6Addr {
+    fn from(addr: Ipv6Address) -> Self {
+        let value: u128 = (u128::from(addr.top_half) << 64) | u128::from(addr.bottom_half);
+        value.into()
     }
 }
 
@@ -576,14 +799,155 @@ impl BitXor for Ipv6Address {
     }
 }
 
+/// Information embedded in a Teredo IPv6 address (RFC 4380).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TeredoInfo {
+    /// The IPv4 address of the Teredo server.
+    pub server: Ipv4Address,
+
+    /// The external IPv4 address of the Teredo client.
+    pub client: Ipv4Address,
+
+    /// The external UDP port of the Teredo client.
+    pub port: u16,
+
+    /// The Teredo flags.
+    pub flags: u16,
+}
+
+/// Extracts the IPv4 address embedded in a 6to4 address (`2002::/16`, RFC 3056). Returns `None` if
+/// the address is not a 6to4 address.
+pub fn extract_6to4_v4(addr: Ipv6Address) -> Option<Ipv4Address> {
+    if (addr.top_half >> 48) != 0x2002 {
+        return None;
+    }
+    let v4_value: u32 = ((addr.top_half >> 16) & 0xFFFF_FFFF).try_into().unwrap();
+    Some(Ipv4Address::new(v4_value))
+}
+
+/// Extracts the information embedded in a Teredo address (`2001:0::/32`, RFC 4380). Returns `None`
+/// if the address is not a Teredo address.
+pub fn extract_teredo(addr: Ipv6Address) -> Option<TeredoInfo> {
+    if (addr.top_half >> 32) != 0x2001_0000 {
+        return None;
+    }
+
+    // the client's port and address are stored obfuscated (with all bits inverted)
+    let server_value: u32 = (addr.top_half & 0xFFFF_FFFF).try_into().unwrap();
+    let flags: u16 = (addr.bottom_half >> 48).try_into().unwrap();
+    let obfuscated_port: u16 = ((addr.bottom_half >> 32) & 0xFFFF).try_into().unwrap();
+    let obfuscated_client: u32 = (addr.bottom_half & 0xFFFF_FFFF).try_into().unwrap();
+    Some(TeredoInfo {
+        server: Ipv4Address::new(server_value),
+        client: Ipv4Address::new(!obfuscated_client),
+        port: !obfuscated_port,
+        flags,
+    })
+}
+
+/// Whether the given string is an IPv6 address in canonical text form (RFC 5952), i.e. it equals
+/// `Ipv6Address::to_canonical_string`: lowercase, without leading zeroes and with the first longest
+/// run of at least two zero fields shortened to `::`. Returns `false` if the string is not a valid
+/// IPv6 address.
+pub fn is_canonical_ipv6(s: &str) -> bool {
+    match s.parse::<Ipv6Address>() {
+        Ok(addr) => addr.to_canonical_string() == s,
+        Err(_) => false,
+    }
+}
+
+/// The scope of an IPv6 address, which takes the place of the address classes of IPv4.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Ipv6Scope {
+    /// The unspecified address (`::`, RFC 4291).
+    Unspecified,
+
+    /// The loopback address (`::1`, RFC 4291).
+    Loopback,
+
+    /// A link-local unicast address (`fe80::/10`, RFC 4291).
+    LinkLocal,
+
+    /// A unique local unicast address (`fc00::/7`, RFC 4193).
+    UniqueLocal,
+
+    /// A multicast address (`ff00::/8`, RFC 4291).
+    Multicast,
+
+    /// A global unicast address, i.e. any other address.
+    Global,
+}
+impl fmt::Display for Ipv6Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ipv6Scope::Unspecified => write!(f, "unspecified"),
+            Ipv6Scope::Loopback => write!(f, "loopback"),
+            Ipv6Scope::LinkLocal => write!(f, "link-local"),
+            Ipv6Scope::UniqueLocal => write!(f, "unique local"),
+            Ipv6Scope::Multicast => write!(f, "multicast"),
+            Ipv6Scope::Global => write!(f, "global"),
+        }
+    }
+}
+
+/// Returns the scope of the given IPv6 address.
+pub fn ipv6_scope(addr: Ipv6Address) -> Ipv6Scope {
+    if addr.top_half == 0 && addr.bottom_half == 0 {
+        Ipv6Scope::Unspecified
+    } else if addr.top_half == 0 && addr.bottom_half == 1 {
+        Ipv6Scope::Loopback
+    } else if (addr.top_half >> 48) & 0xFFC0 == 0xFE80 {
+        Ipv6Scope::LinkLocal
+    } else if (addr.top_half >> 48) & 0xFE00 == 0xFC00 {
+        Ipv6Scope::UniqueLocal
+    } else if (addr.top_half >> 56) == 0xFF {
+        Ipv6Scope::Multicast
+    } else {
+        Ipv6Scope::Global
+    }
+}
+
+/// Returns the index of the first character of the chunk that is not a digit in the given radix,
+/// or 0 if all characters are digits (e.g. if the value is out of range).
+fn invalid_digit_index(chunk: &str, radix: u32) -> usize {
+    chunk.char_indices()
+        .find(|(_i, c)| !c.is_digit(radix))
+        .map(|(i, _c)| i)
+        .unwrap_or(0)
+}
+
+/// Returns the fully qualified domain name used for reverse DNS (PTR) lookups of the given address,
+/// e.g. `1.2.0.192.in-addr.arpa.` for `192.0.2.1`. IPv4 addresses are reversed byte by byte, IPv6
+/// addresses nibble by nibble.
+pub fn reverse_dns_name<A: IpAddress>(addr: &A) -> String {
+    let bytes = addr.to_bytes();
+    let mut name = String::new();
+    if bytes.len() == 4 {
+        for b in bytes.iter().rev() {
+            write!(name, "{}.", b).unwrap();
+        }
+        name.push_str("in-addr.arpa.");
+    } else {
+        for b in bytes.iter().rev() {
+            write!(name, "{:x}.{:x}.", b & 0x0F, b >> 4).unwrap();
+        }
+        name.push_str("ip6.arpa.");
+    }
+    name
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum IpAddressParseError {
     UnknownAddressType,
     IncorrectChunkCount(usize, usize),
     EmptyChunk(usize),
-    ChunkParseError(usize, String, ParseIntError),
+    ChunkParseError(usize, String, ParseIntError, usize),
     ChunkOutOfRange(usize, u32, u32, u32),
     TooManyShorteningElements(usize, usize),
+    LeadingZero(usize, String),
+    UnbalancedBrackets,
+    Empty,
+    ZoneWithoutAddress(String),
 }
 impl fmt::Display for IpAddressParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -594,12 +958,20 @@ impl fmt::Display for IpAddressParseError {
                 => write!(f, "IP address has {} chunk(s); expected {}", got, expected),
             IpAddressParseError::EmptyChunk(chunk_idx)
                 => write!(f, "IP address chunk with index {} is empty", chunk_idx),
-            IpAddressParseError::ChunkParseError(chunk_idx, chunk, error)
-                => write!(f, "failed to parse IP address chunk with index {} ({:?}): {}", chunk_idx, chunk, error),
+            IpAddressParseError::ChunkParseError(chunk_idx, chunk, error, offset)
+                => write!(f, "failed to parse IP address chunk with index {} ({:?}) at offset {}: {}", chunk_idx, chunk, offset, error),
             IpAddressParseError::ChunkOutOfRange(chunk_idx, got, min, max)
                 => write!(f, "IP address chunk with index {} ({}) is out of range {} <= n <= {} chunk", chunk_idx, got, min, max),
             IpAddressParseError::TooManyShorteningElements(got, expected_max)
                 => write!(f, "IP address has {} shortening elements; expected maximum {}", got, expected_max),
+            IpAddressParseError::LeadingZero(chunk_idx, chunk)
+                => write!(f, "IP address chunk with index {} ({:?}) has a leading zero", chunk_idx, chunk),
+            IpAddressParseError::UnbalancedBrackets
+                => write!(f, "IP address has an opening bracket without a closing bracket or vice versa"),
+            IpAddressParseError::Empty
+                => write!(f, "IP address is empty"),
+            IpAddressParseError::ZoneWithoutAddress(zone)
+                => write!(f, "IP address consists only of the zone {:?}", zone),
         }
     }
 }
@@ -618,6 +990,13 @@ mod test {
         assert_eq!("127.0.0.1", Ipv4Address::new(0x7F000001).to_string());
     }
 
+    #[test]
+    fn test_ipv4_zero_padded_string() {
+        assert_eq!("000.000.000.000", Ipv4Address::new(0x00000000).to_zero_padded_string());
+        assert_eq!("192.000.002.001", Ipv4Address::new(0xC0000201).to_zero_padded_string());
+        assert_eq!("255.255.255.255", Ipv4Address::new(0xFFFFFFFF).to_zero_padded_string());
+    }
+
     fn parse_ipv4(s: &str) -> Result<Ipv4Address, IpAddressParseError> {
         s.parse()
     }
@@ -635,14 +1014,14 @@ mod test {
         assert_eq!(Err(IpAddressParseError::IncorrectChunkCount(2, 4)), parse_ipv4("."));
         assert_eq!(Err(IpAddressParseError::IncorrectChunkCount(3, 4)), parse_ipv4("1.2.3"));
         assert_eq!(Err(IpAddressParseError::IncorrectChunkCount(5, 4)), parse_ipv4("1.2.3.4.5"));
-        if let Err(IpAddressParseError::ChunkParseError(idx, s, _)) = parse_ipv4("1.2.-3.4") {
+        if let Err(IpAddressParseError::ChunkParseError(idx, s, _, _)) = parse_ipv4("1.2.-3.4") {
             assert_eq!(2, idx);
             assert_eq!("-3", s);
         } else {
             panic!();
         }
         assert_eq!(Err(IpAddressParseError::ChunkOutOfRange(1, 256, 0, 255)), parse_ipv4("255.256.255.255"));
-        if let Err(IpAddressParseError::ChunkParseError(idx, s, _)) = parse_ipv4("0xFF.256.255.255") {
+        if let Err(IpAddressParseError::ChunkParseError(idx, s, _, _)) = parse_ipv4("0xFF.256.255.255") {
             assert_eq!(0, idx);
             assert_eq!("0xFF", s);
         }
@@ -669,6 +1048,30 @@ mod test {
         assert_eq!(None, Ipv4Address::from_bytes(&vec![1, 2, 3, 4, 5]));
     }
 
+    #[test]
+    fn test_bit() {
+        let addr = parse_ipv4("192.0.2.1").unwrap();
+        assert_eq!(Some(true), addr.bit(0));
+        assert_eq!(Some(true), addr.bit(1));
+        assert_eq!(Some(false), addr.bit(2));
+        assert_eq!(Some(true), addr.bit(31));
+        assert_eq!(Some(false), addr.bit(30));
+        assert_eq!(None, addr.bit(32));
+
+        assert_eq!(parse_ipv4("64.0.2.1").unwrap(), addr.with_bit(0, false));
+        assert_eq!(parse_ipv4("192.0.2.0").unwrap(), addr.with_bit(31, false));
+        assert_eq!(parse_ipv4("192.0.2.1").unwrap(), addr.with_bit(31, true));
+        assert_eq!(parse_ipv4("192.0.2.3").unwrap(), addr.with_bit(30, true));
+        assert_eq!(Some(false), addr.with_bit(0, false).bit(0));
+
+        let addr: Ipv6Address = "2001:db8::1".parse().unwrap();
+        assert_eq!(Some(false), addr.bit(0));
+        assert_eq!(Some(true), addr.bit(2));
+        assert_eq!(Some(true), addr.bit(127));
+        assert_eq!(None, addr.bit(128));
+        assert_eq!("2001:db8::".parse::<Ipv6Address>().unwrap(), addr.with_bit(127, false));
+    }
+
     #[test]
     fn test_ipv4_eq() {
         fn teq(val: u32) {
@@ -708,6 +1111,14 @@ mod test {
         assert_eq!("abcd::123:256", Ipv6Address::new(0xABCD000000000000, 0x0000000001230256).to_string());
         assert_eq!("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff", Ipv6Address::new(0xFFFFFFFFFFFFFFFF, 0xFFFFFFFFFFFFFFFF).to_string());
         assert_eq!("fec0:abcd:1234:defa:1337:8008:1224:2323", Ipv6Address::new(0xFEC0ABCD1234DEFA, 0x1337800812242323).to_string());
+
+        // only the first of multiple equally long runs of zeroes is shortened
+        assert_eq!("1::2:0:0:3", Ipv6Address::new(0x0001000000000000, 0x0002000000000003).to_string());
+        assert_eq!("1:0:2:0:3::", Ipv6Address::new(0x0001000000020000, 0x0003000000000000).to_string());
+        assert_eq!("1:0:0:2::", Ipv6Address::new(0x0001000000000002, 0x0000000000000000).to_string());
+        assert_eq!("0:0:0:1::", Ipv6Address::new(0x0000000000000001, 0x0000000000000000).to_string());
+        assert_eq!("1::", Ipv6Address::new(0x0001000000000000, 0x0000000000000000).to_string());
+        assert_eq!("2001::4136:e378:8000:63bf:3fff:fdd2", Ipv6Address::new(0x200100004136E378, 0x800063BF3FFFFDD2).to_string());
     }
 
     #[test]
@@ -738,12 +1149,15 @@ mod test {
             input.parse()
         }
 
+        assert_eq!(Err(IpAddressParseError::Empty), p6(""));
+        assert_eq!(Err(IpAddressParseError::Empty), p6("[]"));
+        assert_eq!(Err(IpAddressParseError::ZoneWithoutAddress(String::from("eth0"))), p6("%eth0"));
         assert_eq!(Err(IpAddressParseError::IncorrectChunkCount(2, 8)), p6(":"));
         assert_eq!(Err(IpAddressParseError::IncorrectChunkCount(2, 8)), p6("a:"));
         assert_eq!(Err(IpAddressParseError::IncorrectChunkCount(2, 8)), p6(":a"));
         assert_eq!(Err(IpAddressParseError::TooManyShorteningElements(2, 1)), p6(":::"));
         assert_eq!(Err(IpAddressParseError::TooManyShorteningElements(2, 1)), p6("fe80::a55e:55ed::0b50:1e7e"));
-        if let Err(IpAddressParseError::ChunkParseError(idx, s, _)) = p6("fe80::a55e:55ed:0b50:1ete") {
+        if let Err(IpAddressParseError::ChunkParseError(idx, s, _, _)) = p6("fe80::a55e:55ed:0b50:1ete") {
             assert_eq!(7, idx);
             assert_eq!("1ete", s);
         } else {
@@ -794,6 +1208,36 @@ mod test {
         teq(0x123456789ABCDEF0, 0xFEDCBA9876543210);
     }
 
+    #[test]
+    fn test_ipv6_numeric_cmp() {
+        use crate::net::test::parse_ipv6;
+
+        let ascending = vec![
+            parse_ipv6("::"),
+            parse_ipv6("::1"),
+            parse_ipv6("::ffff:ffff:ffff:ffff"),
+            parse_ipv6("0:0:0:1::"),
+            parse_ipv6("2001:db8::ffff"),
+            parse_ipv6("2001:db8::1:0"),
+            parse_ipv6("2001:db8:0:1::"),
+            parse_ipv6("fe80::1"),
+            parse_ipv6("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"),
+        ];
+        for (i, one) in ascending.iter().enumerate() {
+            for (j, other) in ascending.iter().enumerate() {
+                assert_eq!(i.cmp(&j), one.numeric_cmp(other));
+                assert_eq!(one.cmp(other), one.numeric_cmp(other));
+            }
+        }
+
+        let mut scrambled = vec![
+            ascending[5], ascending[8], ascending[0], ascending[3], ascending[7],
+            ascending[1], ascending[6], ascending[4], ascending[2],
+        ];
+        scrambled.sort_by(|a, b| a.numeric_cmp(b));
+        assert_eq!(ascending, scrambled);
+    }
+
     #[test]
     fn test_ipv6_and() {
         fn tand(exp_top: u64, exp_bot: u64, left_top: u64, left_bot: u64, right_top: u64, right_bot: u64) {
@@ -806,4 +1250,300 @@ mod test {
 
         tand(0x1214121812141210, 0x1214121812141210, 0x123456789ABCDEF0, 0xFEDCBA9876543210, 0xFEDCBA9876543210, 0x123456789ABCDEF0);
     }
+
+    #[test]
+    fn test_ipv6_subtract_borrow() {
+        // the bottom half underflows and borrows from the top half
+        let addr = Ipv6Address::new(0x2001_0DB8_0000_0000, 0x0000_0000_0000_0001);
+        assert_eq!(
+            Some(Ipv6Address::new(0x2001_0DB7_FFFF_FFFF, 0xFFFF_FFFF_FFFF_FC19)),
+            addr.subtract_offset(1000),
+        );
+        assert_eq!(
+            Some(Ipv6Address::new(0x2001_0DB7_FFFF_FFFF, 0xFFFF_FFFF_FFFF_FFFF)),
+            addr.subtract_addr(&Ipv6Address::new(0x0, 0x2)),
+        );
+        assert_eq!(
+            Some(Ipv6Address::new(0x0000_0000_0000_0000, 0xFFFF_FFFF_FFFF_FFFF)),
+            Ipv6Address::new(0x0000_0000_0000_0001, 0x0000_0000_0000_0000).subtract_offset(1),
+        );
+
+        // there is nothing to borrow from
+        assert_eq!(None, Ipv6Address::new(0x0, 0x0).subtract_offset(1));
+        assert_eq!(
+            None,
+            Ipv6Address::new(0x0000_0000_0000_0001, 0x0).subtract_addr(&Ipv6Address::new(0x0000_0000_0000_0001, 0x1)),
+        );
+    }
+
+    #[test]
+    fn test_extract_6to4_v4() {
+        let addr: Ipv6Address = "2002:c000:204::".parse().unwrap();
+        assert_eq!(Some(Ipv4Address::new(0xC0000204)), extract_6to4_v4(addr));
+
+        let addr: Ipv6Address = "2002:cb00:71ff:1::1".parse().unwrap();
+        assert_eq!(Some(Ipv4Address::new(0xCB0071FF)), extract_6to4_v4(addr));
+
+        let addr: Ipv6Address = "2001:db8::".parse().unwrap();
+        assert_eq!(None, extract_6to4_v4(addr));
+    }
+
+    #[test]
+    fn test_extract_teredo() {
+        // example from RFC 4380
+        let addr: Ipv6Address = "2001:0:4136:e378:8000:63bf:3fff:fdd2".parse().unwrap();
+        assert_eq!(
+            Some(TeredoInfo {
+                server: Ipv4Address::new(0x4136E378),
+                client: Ipv4Address::new(0xC000022D),
+                port: 40000,
+                flags: 0x8000,
+            }),
+            extract_teredo(addr),
+        );
+
+        let addr: Ipv6Address = "2001:db8::".parse().unwrap();
+        assert_eq!(None, extract_teredo(addr));
+        let addr: Ipv6Address = "2002:c000:204::".parse().unwrap();
+        assert_eq!(None, extract_teredo(addr));
+    }
+
+    #[test]
+    fn test_is_canonical_ipv6() {
+        assert!(is_canonical_ipv6("2001:db8::1"));
+        assert!(is_canonical_ipv6("::"));
+        assert!(is_canonical_ipv6("fe80::38a2:b5f3:58dc:13d9"));
+        assert!(is_canonical_ipv6("1::2:0:0:3"));
+        assert!(is_canonical_ipv6("1:0:0:2::3"));
+        assert!(is_canonical_ipv6("2001:db8:0:1:1:1:1:1"));
+        assert!(is_canonical_ipv6("2001:0:0:1::1"));
+
+        assert!(!is_canonical_ipv6("2001:DB8::1"));
+        assert!(!is_canonical_ipv6("2001:0db8::1"));
+        assert!(!is_canonical_ipv6("2001:db8:0:0:0:0:0:1"));
+        assert!(!is_canonical_ipv6("1::2:0:0:0:3"));
+        assert!(!is_canonical_ipv6("2001:db8::1:1:1:1:1"));
+        assert!(!is_canonical_ipv6("[2001:db8::1]"));
+        assert!(!is_canonical_ipv6("192.0.2.1"));
+        assert!(!is_canonical_ipv6("not an address"));
+    }
+
+    #[test]
+    fn test_ipv6_scope() {
+        fn scope(s: &str) -> Ipv6Scope {
+            ipv6_scope(s.parse().unwrap())
+        }
+
+        assert_eq!(Ipv6Scope::Global, scope("2001:db8::1"));
+        assert_eq!(Ipv6Scope::Global, scope("2a00:1450:4001:82a::200e"));
+        assert_eq!(Ipv6Scope::UniqueLocal, scope("fd12:3456:789a::1"));
+        assert_eq!(Ipv6Scope::UniqueLocal, scope("fc00::"));
+        assert_eq!(Ipv6Scope::LinkLocal, scope("fe80::1"));
+        assert_eq!(Ipv6Scope::LinkLocal, scope("febf:ffff::1"));
+        assert_eq!(Ipv6Scope::Global, scope("fec0::1"));
+        assert_eq!(Ipv6Scope::Multicast, scope("ff02::1"));
+        assert_eq!(Ipv6Scope::Loopback, scope("::1"));
+        assert_eq!(Ipv6Scope::Unspecified, scope("::"));
+        assert_eq!("link-local", Ipv6Scope::LinkLocal.to_string());
+    }
+
+    #[test]
+    fn test_ipv6_full_string() {
+        assert_eq!(
+            "0000:0000:0000:0000:0000:0000:0000:0000",
+            Ipv6Address::new(0, 0).to_full_string(),
+        );
+        assert_eq!(
+            "2001:0db8:0000:0000:0000:0000:0000:0001",
+            Ipv6Address::new(0x20010DB800000000, 0x0000000000000001).to_full_string(),
+        );
+        assert_eq!(
+            "fe80:0000:0000:0000:38a2:b5f3:58dc:13d9",
+            Ipv6Address::new(0xFE80000000000000, 0x38A2B5F358DC13D9).to_full_string(),
+        );
+    }
+
+    #[test]
+    fn test_ipv6_padded_string() {
+        fn tpad(expected: &str, addr_str: &str) {
+            let addr: Ipv6Address = addr_str.parse().unwrap();
+            assert_eq!(expected, addr.to_padded_string());
+        }
+
+        tpad("2001:0db8::0001", "2001:db8::1");
+        tpad("::", "::");
+        tpad("::0001", "::1");
+        tpad("fe80::38a2:b5f3:58dc:13d9", "fe80::38a2:b5f3:58dc:13d9");
+        tpad("2001:0db8:0000:0001::0001", "2001:db8:0:1::1");
+        tpad("0001:0002:0003:0004:0005:0006:0007:0008", "1:2:3:4:5:6:7:8");
+        tpad("ffff::", "ffff::");
+    }
+
+    #[test]
+    fn test_parse_error_offset() {
+        fn offset<A: FromStr<Err = IpAddressParseError>>(s: &str) -> usize {
+            match s.parse::<A>() {
+                Err(IpAddressParseError::ChunkParseError(_, _, _, offset)) => offset,
+                _ => panic!("expected a chunk parse error for {:?}", s),
+            }
+        }
+
+        assert_eq!(7, offset::<Ipv4Address>("192.0.2x.1"));
+        assert_eq!(4, offset::<Ipv4Address>("1.2.-3.4"));
+        assert_eq!(1, offset::<Ipv4Address>("0xFF.256.255.255"));
+        // all digits, but too large for the chunk type
+        assert_eq!(4, offset::<Ipv4Address>("192.99999999999.2.1"));
+
+        assert_eq!(23, offset::<Ipv6Address>("fe80::a55e:55ed:0b50:1ete"));
+        assert_eq!(7, offset::<Ipv6Address>("2001:dbg8::1"));
+        assert_eq!(8, offset::<Ipv6Address>("[2001:dbg8::1]"));
+        assert_eq!(4, offset::<Ipv6Address>("::1:x"));
+    }
+
+    #[test]
+    fn test_ipv4_parse_strict() {
+        assert_eq!(Ok(Ipv4Address::new(0x0A000001)), Ipv4Address::parse_strict("10.0.0.1"));
+        assert_eq!(Ok(Ipv4Address::new(0x00000000)), Ipv4Address::parse_strict("0.0.0.0"));
+        assert_eq!(Ok(Ipv4Address::new(0xC0000264)), Ipv4Address::parse_strict("192.0.2.100"));
+
+        // leading zeroes are accepted by the lenient parser...
+        assert_eq!(Ok(Ipv4Address::new(0x0A000001)), parse_ipv4("010.0.0.1"));
+        assert_eq!(Ok(Ipv4Address::new(0x01020304)), parse_ipv4("01.002.00003.4"));
+
+        // ... but not by the strict one
+        assert_eq!(
+            Err(IpAddressParseError::LeadingZero(0, String::from("010"))),
+            Ipv4Address::parse_strict("010.0.0.1"),
+        );
+        assert_eq!(
+            Err(IpAddressParseError::LeadingZero(3, String::from("00"))),
+            Ipv4Address::parse_strict("10.0.0.00"),
+        );
+
+        // other errors are reported as usual
+        assert_eq!(
+            Err(IpAddressParseError::IncorrectChunkCount(3, 4)),
+            Ipv4Address::parse_strict("10.0.1"),
+        );
+    }
+
+    #[test]
+    fn test_classful_prefix() {
+        assert_eq!(Some(8), parse_ipv4("10.1.2.3").unwrap().classful_prefix());
+        assert_eq!(Some(8), parse_ipv4("127.255.255.255").unwrap().classful_prefix());
+        assert_eq!(Some(16), parse_ipv4("128.0.0.0").unwrap().classful_prefix());
+        assert_eq!(Some(24), parse_ipv4("192.0.2.1").unwrap().classful_prefix());
+        assert_eq!(None, parse_ipv4("224.0.0.1").unwrap().classful_prefix());
+        assert_eq!(None, parse_ipv4("255.255.255.255").unwrap().classful_prefix());
+    }
+
+    #[test]
+    fn test_octets_segments() {
+        for addr_str in &["0.0.0.0", "127.0.0.1", "192.0.2.255", "255.255.255.255"] {
+            let addr = parse_ipv4(addr_str).unwrap();
+            assert_eq!(addr.to_bytes(), addr.octets().to_vec());
+        }
+        assert_eq!([192, 0, 2, 1], parse_ipv4("192.0.2.1").unwrap().octets());
+
+        for addr_str in &["::", "::1", "2001:db8::1", "fe80::38a2:b5f3:58dc:13d9", "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"] {
+            let addr: Ipv6Address = addr_str.parse().unwrap();
+            assert_eq!(addr.to_chunks(), addr.segments().to_vec());
+
+            let segment_bytes: Vec<u8> = addr.segments().iter()
+                .flat_map(|s| s.to_be_bytes())
+                .collect();
+            assert_eq!(addr.to_bytes(), segment_bytes);
+        }
+        assert_eq!(
+            [0x2001, 0x0db8, 0, 0, 0, 0, 0, 0x0001],
+            "2001:db8::1".parse::<Ipv6Address>().unwrap().segments(),
+        );
+    }
+
+    #[test]
+    fn test_ipv4_wrapping_offset() {
+        let max = Ipv4Address::new(0xFFFFFFFF);
+        let zero = Ipv4Address::new(0x00000000);
+        assert_eq!(zero, max.wrapping_add_offset(1));
+        assert_eq!(max, zero.wrapping_subtract_offset(1));
+        assert_eq!(max, zero.wrapping_add_offset(-1));
+        assert_eq!(zero, max.wrapping_subtract_offset(-1));
+        assert_eq!(Ipv4Address::new(0x7F000002), Ipv4Address::new(0x7F000001).wrapping_add_offset(1));
+        assert_eq!(Ipv4Address::new(0x80000000), zero.wrapping_subtract_offset(i32::MIN));
+        assert_eq!(Ipv4Address::new(0x7FFFFFFF), max.wrapping_add_offset(i32::MIN));
+
+        // agrees with the checked variants if they do not overflow
+        let addr = Ipv4Address::new(0xC0000201);
+        assert_eq!(addr.add_offset(1000), Some(addr.wrapping_add_offset(1000)));
+        assert_eq!(addr.subtract_offset(1000), Some(addr.wrapping_subtract_offset(1000)));
+    }
+
+    #[test]
+    fn test_ipv6_wrapping_offset() {
+        let max = Ipv6Address::new(0xFFFF_FFFF_FFFF_FFFF, 0xFFFF_FFFF_FFFF_FFFF);
+        let zero = Ipv6Address::new(0x0, 0x0);
+        assert_eq!(zero, max.wrapping_add_offset(1));
+        assert_eq!(max, zero.wrapping_subtract_offset(1));
+        assert_eq!(max, zero.wrapping_add_offset(-1));
+        assert_eq!(zero, max.wrapping_subtract_offset(-1));
+        assert_eq!(
+            Ipv6Address::new(0x0000_0000_0000_0001, 0x0000_0000_0000_0000),
+            Ipv6Address::new(0x0000_0000_0000_0000, 0xFFFF_FFFF_FFFF_FFFF).wrapping_add_offset(1),
+        );
+        assert_eq!(
+            Ipv6Address::new(0x0000_0000_0000_0000, 0xFFFF_FFFF_FFFF_FFFF),
+            Ipv6Address::new(0x0000_0000_0000_0001, 0x0000_0000_0000_0000).wrapping_subtract_offset(1),
+        );
+        assert_eq!(
+            Ipv6Address::new(0x0000_0000_0000_0000, 0x0000_0000_8000_0000),
+            zero.wrapping_subtract_offset(i32::MIN),
+        );
+
+        // agrees with the checked variants if they do not overflow
+        let addr = Ipv6Address::new(0x2001_0db8_0000_0000, 0x0000_0000_0001_0000);
+        assert_eq!(addr.add_offset(1000), Some(addr.wrapping_add_offset(1000)));
+        assert_eq!(addr.subtract_offset(1000), Some(addr.wrapping_subtract_offset(1000)));
+        assert_eq!(addr.add_offset(-1000), Some(addr.wrapping_add_offset(-1000)));
+    }
+
+    #[test]
+    fn test_saturating_offset() {
+        fn tsat<A: IpAddress + fmt::Debug>(low: A, high: A) {
+            assert_eq!(A::min_addr(), A::min_addr().saturating_add_offset(-1));
+            assert_eq!(A::min_addr(), A::min_addr().saturating_subtract_offset(1));
+            assert_eq!(A::min_addr(), A::min_addr().saturating_subtract_offset(i32::MAX));
+            assert_eq!(A::max_addr(), A::max_addr().saturating_add_offset(1));
+            assert_eq!(A::max_addr(), A::max_addr().saturating_subtract_offset(-1));
+            assert_eq!(A::max_addr(), A::max_addr().saturating_add_offset(i32::MAX));
+
+            assert_eq!(A::max_addr(), A::max_addr().bitwise_negate().bitwise_negate());
+            assert_eq!(A::min_addr(), A::max_addr().bitwise_negate());
+
+            // agrees with the checked variants if they do not overflow
+            assert_eq!(low.add_offset(1), Some(low.saturating_add_offset(1)));
+            assert_eq!(high.subtract_offset(1), Some(high.saturating_subtract_offset(1)));
+            assert_eq!(A::min_addr(), low.saturating_subtract_offset(1000));
+            assert_eq!(A::max_addr(), high.saturating_add_offset(1000));
+        }
+
+        tsat(Ipv4Address::new(0x00000010), Ipv4Address::new(0xFFFFFFF0));
+        tsat(
+            Ipv6Address::new(0x0000_0000_0000_0000, 0x0000_0000_0000_0010),
+            Ipv6Address::new(0xFFFF_FFFF_FFFF_FFFF, 0xFFFF_FFFF_FFFF_FFF0),
+        );
+    }
+
+    #[cfg(feature = "std-net")]
+    #[test]
+    fn test_std_net_conversion() {
+        let std_v4: std::net::Ipv4Addr = "192.0.2.1".parse().unwrap();
+        let v4 = Ipv4Address::from(std_v4);
+        assert_eq!(Ipv4Address::new(0xC0000201), v4);
+        assert_eq!(std_v4, std::net::Ipv4Addr::from(v4));
+
+        let std_v6: std::net::Ipv6Addr = "2001:db8::8:1".parse().unwrap();
+        let v6 = Ipv6Address::from(std_v6);
+        assert_eq!(Ipv6Address::new(0x2001_0db8_0000_0000, 0x0000_0000_0008_0001), v6);
+        assert_eq!(std_v6, std::net::Ipv6Addr::from(v6));
+    }
 }
@@ -17,14 +17,25 @@ pub fn bytes_to_bits(bytes: &[u8]) -> Vec<bool> {
     ret
 }
 
+/// Converts a byte into the ASCII characters '1' and '0' representing its bit values (most
+/// significant bit first) without allocating.
+pub fn byte_to_binary(b: u8) -> [u8; 8] {
+    let mut ret = [b'0'; 8];
+    for (bit_idx, digit) in ret.iter_mut().enumerate() {
+        if b & (1 << (7 - bit_idx)) != 0 {
+            *digit = b'1';
+        }
+    }
+    ret
+}
+
 /// Converts a slice of bytes into a string of '1' and '0' characters representing the bit values
 /// (most significant bit first).
 pub fn bytes_to_binary(bytes: &[u8]) -> String {
-    let mut ret = String::with_capacity(bytes.len() * 8);
-    for bit in bytes_to_bits(bytes) {
-        ret.push(if bit { '1' } else { '0' });
-    }
-    ret
+    bytes.iter()
+        .flat_map(|b| byte_to_binary(*b))
+        .map(char::from)
+        .collect()
 }
 
 /// Converts a slice of bit values into bytes. Assumes that bits are ordered most significant bit
@@ -142,3 +153,133 @@ pub fn weave_address<A: IpAddress>(addr: A, subnet_mask: A) -> A {
     let ret_bytes = bits_to_bytes(&ret_bits);
     A::from_bytes(&ret_bytes).expect("address from bytes")
 }
+
+
+/// A precomputed plan for converting addresses between a (potentially mixed) subnet mask and the
+/// equally-sized CIDR subnet mask. Building the plan once and applying it to many addresses is
+/// cheaper than calling `unravel_address` or `weave_address` for each of them.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct MaskPlan {
+    /// For each bit index of an unraveled address, the index of the corresponding bit in the woven
+    /// address. Empty if the subnet mask is already a CIDR subnet mask.
+    permutation: Vec<usize>,
+}
+impl MaskPlan {
+    /// Creates a plan for the given subnet mask.
+    pub fn new<A: IpAddress>(subnet_mask: A) -> Self {
+        let mask_bytes = subnet_mask.to_bytes();
+        if prefix_from_subnet_mask_bytes(&mask_bytes).is_some() {
+            // nothing to permute :)
+            return Self { permutation: Vec::new() };
+        }
+
+        let mask_bits = bytes_to_bits(&mask_bytes);
+        let net_indexes = mask_bits.iter()
+            .enumerate()
+            .filter(|(_i, is_net)| **is_net)
+            .map(|(i, _is_net)| i);
+        let host_indexes = mask_bits.iter()
+            .enumerate()
+            .filter(|(_i, is_net)| !**is_net)
+            .map(|(i, _is_net)| i);
+        let permutation = net_indexes.chain(host_indexes).collect();
+        Self { permutation }
+    }
+
+    /// Converts the given address from the plan's subnet mask to the equally-sized CIDR subnet
+    /// mask. Equivalent to `unravel_address`.
+    pub fn unravel<A: IpAddress>(&self, addr: A) -> A {
+        if self.permutation.is_empty() {
+            return addr;
+        }
+
+        let addr_bytes = addr.to_bytes();
+        let mut ret_bytes = vec![0u8; addr_bytes.len()];
+        for (unraveled_index, woven_index) in self.permutation.iter().enumerate() {
+            if get_bit(&addr_bytes, *woven_index) {
+                set_bit(&mut ret_bytes, unraveled_index);
+            }
+        }
+        A::from_bytes(&ret_bytes).expect("address from bytes")
+    }
+
+    /// Converts the given address from the equally-sized CIDR subnet mask to the plan's subnet
+    /// mask. Equivalent to `weave_address`.
+    pub fn weave<A: IpAddress>(&self, addr: A) -> A {
+        if self.permutation.is_empty() {
+            return addr;
+        }
+
+        let addr_bytes = addr.to_bytes();
+        let mut ret_bytes = vec![0u8; addr_bytes.len()];
+        for (unraveled_index, woven_index) in self.permutation.iter().enumerate() {
+            if get_bit(&addr_bytes, unraveled_index) {
+                set_bit(&mut ret_bytes, *woven_index);
+            }
+        }
+        A::from_bytes(&ret_bytes).expect("address from bytes")
+    }
+}
+
+/// Returns the value of the bit at the given index (most significant bit first).
+fn get_bit(bytes: &[u8], index: usize) -> bool {
+    bytes[index / 8] & (1 << (7 - (index % 8))) != 0
+}
+
+/// Sets the bit at the given index (most significant bit first).
+fn set_bit(bytes: &mut [u8], index: usize) {
+    bytes[index / 8] |= 1 << (7 - (index % 8));
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::net::test::{parse_ipv4, parse_ipv6};
+
+    #[test]
+    fn test_byte_to_binary() {
+        for b in [0x00, 0x01, 0x0F, 0x80, 0xA5, 0xC0, 0xFE, 0xFF] {
+            let binary = byte_to_binary(b);
+            assert_eq!(bytes_to_binary(&[b]).as_bytes(), &binary);
+
+            // agrees with the bits from bytes_to_bits
+            let bits: Vec<u8> = bytes_to_bits(&[b]).into_iter()
+                .map(|bit| if bit { b'1' } else { b'0' })
+                .collect();
+            assert_eq!(bits, binary);
+        }
+        assert_eq!(b"10100101", &byte_to_binary(0xA5));
+        assert_eq!("110000000000001011111111", bytes_to_binary(&[0xC0, 0x02, 0xFF]));
+    }
+
+    fn assert_plan_matches<A: IpAddress + std::fmt::Debug>(subnet_mask: A, addrs: &[A]) {
+        let plan = MaskPlan::new(subnet_mask);
+        for addr in addrs {
+            let unraveled = unravel_address(*addr, subnet_mask);
+            assert_eq!(unraveled, plan.unravel(*addr));
+            assert_eq!(weave_address(unraveled, subnet_mask), plan.weave(unraveled));
+            assert_eq!(*addr, plan.weave(plan.unravel(*addr)));
+        }
+    }
+
+    #[test]
+    fn test_mask_plan() {
+        let v4_addrs = [
+            parse_ipv4("0.0.0.0"), parse_ipv4("192.64.2.0"), parse_ipv4("192.79.2.42"),
+            parse_ipv4("10.20.30.40"), parse_ipv4("255.255.255.255"),
+        ];
+        assert_plan_matches(parse_ipv4("255.255.255.0"), &v4_addrs);
+        assert_plan_matches(parse_ipv4("255.240.255.255"), &v4_addrs);
+        assert_plan_matches(parse_ipv4("255.255.240.255"), &v4_addrs);
+        assert_plan_matches(parse_ipv4("170.85.170.85"), &v4_addrs);
+
+        let v6_addrs = [
+            parse_ipv6("::"), parse_ipv6("2001:db8::1042"), parse_ipv6("2001:db8::1f42"),
+            parse_ipv6("fe80::38a2:b5f3:58dc:13d9"), parse_ipv6("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"),
+        ];
+        assert_plan_matches(parse_ipv6("ffff:ffff:ffff:ffff::"), &v6_addrs);
+        assert_plan_matches(parse_ipv6("ffff:ffff:ffff:ffff:ffff:ffff:ffff:f0ff"), &v6_addrs);
+        assert_plan_matches(parse_ipv6("ffff:0:ffff:0:ffff:0:ffff:0"), &v6_addrs);
+    }
+}
@@ -1,3 +1,6 @@
+use crate::addr::IpAddress;
+
+
 const XXX: u8 = 9;
 const SUBNET_MASK_BYTE_TO_PREFIX: [u8; 256] = [
       0, XXX, XXX, XXX, XXX, XXX, XXX, XXX, XXX, XXX, XXX, XXX, XXX, XXX, XXX, XXX,
@@ -58,6 +61,18 @@ pub fn prefix_from_subnet_mask_bytes(bs: &[u8]) -> Option<usize> {
     Some(cidr)
 }
 
+/// Returns whether the given subnet mask is equivalent to a CIDR prefix, i.e. whether all its 1-bits
+/// are contiguous at the high end.
+pub fn is_cidr_mask<A: IpAddress>(mask: A) -> bool {
+    prefix_from_subnet_mask_bytes(&mask.to_bytes()).is_some()
+}
+
+/// Returns whether the given wildcard (an inverted subnet mask, as used in Cisco ACLs) is equivalent
+/// to a CIDR prefix, i.e. whether all its 1-bits are contiguous at the low end.
+pub fn is_cidr_wildcard<A: IpAddress>(wildcard: A) -> bool {
+    prefix_from_subnet_mask_bytes(&wildcard.bitwise_negate().to_bytes()).is_some()
+}
+
 /// Converts a CIDR prefix into its equivalent subnet mask.
 pub fn subnet_mask_bytes_from_prefix(mut prefix: usize, byte_count: usize) -> Vec<u8> {
     let mut ret = Vec::with_capacity(byte_count);
@@ -81,6 +96,7 @@ pub fn subnet_mask_bytes_from_prefix(mut prefix: usize, byte_count: usize) -> Ve
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::addr::{Ipv4Address, Ipv6Address};
 
     #[test]
     fn test_mask_bytes_from_prefix() {
@@ -122,4 +138,27 @@ mod test {
         assert_eq!(vec![0b1111_1111, 0b1111_1111, 0b1111_1111, 0b1111_1110], subnet_mask_bytes_from_prefix(31, 4));
         assert_eq!(vec![0b1111_1111, 0b1111_1111, 0b1111_1111, 0b1111_1111], subnet_mask_bytes_from_prefix(32, 4));
     }
+
+    #[test]
+    fn test_is_cidr_mask() {
+        assert!(is_cidr_mask(Ipv4Address::new(0xFFFFFF00)));
+        assert!(is_cidr_mask(Ipv4Address::new(0x00000000)));
+        assert!(is_cidr_mask(Ipv4Address::new(0xFFFFFFFF)));
+        assert!(is_cidr_mask(Ipv6Address::new(0xFFFFFFFFFFFFFFFF, 0x0000000000000000)));
+
+        assert!(!is_cidr_mask(Ipv4Address::new(0xFF00FF00)));
+        assert!(!is_cidr_mask(Ipv4Address::new(0xC0000201)));
+        assert!(!is_cidr_mask(Ipv4Address::new(0x000000FF)));
+    }
+
+    #[test]
+    fn test_is_cidr_wildcard() {
+        assert!(is_cidr_wildcard(Ipv4Address::new(0x000000FF)));
+        assert!(is_cidr_wildcard(Ipv4Address::new(0x00000000)));
+        assert!(is_cidr_wildcard(Ipv4Address::new(0xFFFFFFFF)));
+        assert!(is_cidr_wildcard(Ipv6Address::new(0x0000000000000000, 0xFFFFFFFFFFFFFFFF)));
+
+        assert!(!is_cidr_wildcard(Ipv4Address::new(0x00FF00FF)));
+        assert!(!is_cidr_wildcard(Ipv4Address::new(0xFFFFFF00)));
+    }
 }
@@ -0,0 +1,98 @@
+use crate::run::usage;
+use crate::addr::IpAddress;
+use crate::cmds::{CommandContext, CommandResult, ErrorKind, ParsedIpAddress, parse_addr_as};
+use crate::output::Output;
+
+
+/// The direction in which to find the adjacent address.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction {
+    Next,
+    Previous,
+}
+
+
+pub fn next(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
+    // ripcalc --next IPADDRESS...
+    output_adjacent(args, ctx, stdout, stderr, Direction::Next)
+}
+
+pub fn prev(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
+    // ripcalc --prev IPADDRESS...
+    output_adjacent(args, ctx, stdout, stderr, Direction::Previous)
+}
+
+fn output_adjacent(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output, direction: Direction) -> CommandResult {
+    if args.len() < 3 {
+        return usage(ctx, stderr);
+    }
+
+    for addr_str in &args[2..] {
+        let addr = match parse_addr_as(addr_str, ctx.family) {
+            Ok(a) => a,
+            Err(e) => {
+                return ctx.fail(stderr, ErrorKind::from(&e), format_args!("failed to parse address {:?}: {}", addr_str, e), 1);
+            },
+        };
+        let write_res = match adjacent_addr(addr, direction) {
+            Some(ParsedIpAddress::Ipv4(a)) => writeln!(stdout, "{}", a),
+            Some(ParsedIpAddress::Ipv6(a)) => writeln!(stdout, "{}", a),
+            None => {
+                let (edge, neighbor) = match direction {
+                    Direction::Next => ("last", "next"),
+                    Direction::Previous => ("first", "previous"),
+                };
+                return ctx.fail(stderr, ErrorKind::Address, format_args!("{} is the {} address; there is no {} address", addr_str, edge, neighbor), 1);
+            },
+        };
+        if let Err(e) = write_res {
+            return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+        }
+    }
+    CommandResult::Ok
+}
+
+/// Returns the address immediately following or preceding the given address, regardless of any
+/// network boundaries, or `None` if the address is at the corresponding edge of the address space.
+pub fn adjacent_addr(addr: ParsedIpAddress, direction: Direction) -> Option<ParsedIpAddress> {
+    match addr {
+        ParsedIpAddress::Ipv4(a) => adjacent(a, direction).map(ParsedIpAddress::Ipv4),
+        ParsedIpAddress::Ipv6(a) => adjacent(a, direction).map(ParsedIpAddress::Ipv6),
+    }
+}
+
+fn adjacent<A: IpAddress>(addr: A, direction: Direction) -> Option<A> {
+    match direction {
+        Direction::Next => addr.add_offset(1),
+        Direction::Previous => addr.subtract_offset(1),
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::net::test::{parse_ipv4, parse_ipv6};
+
+    fn v4(s: &str) -> ParsedIpAddress {
+        ParsedIpAddress::Ipv4(parse_ipv4(s))
+    }
+
+    fn v6(s: &str) -> ParsedIpAddress {
+        ParsedIpAddress::Ipv6(parse_ipv6(s))
+    }
+
+    #[test]
+    fn test_adjacent_addr() {
+        assert_eq!(Some(v4("192.0.3.0")), adjacent_addr(v4("192.0.2.255"), Direction::Next));
+        assert_eq!(Some(v4("192.0.2.255")), adjacent_addr(v4("192.0.3.0"), Direction::Previous));
+        assert_eq!(Some(v4("0.0.0.1")), adjacent_addr(v4("0.0.0.0"), Direction::Next));
+        assert_eq!(None, adjacent_addr(v4("255.255.255.255"), Direction::Next));
+        assert_eq!(None, adjacent_addr(v4("0.0.0.0"), Direction::Previous));
+
+        assert_eq!(Some(v6("2001:db8::1:0")), adjacent_addr(v6("2001:db8::ffff"), Direction::Next));
+        assert_eq!(Some(v6("2001:db7:ffff:ffff:ffff:ffff:ffff:ffff")), adjacent_addr(v6("2001:db8::"), Direction::Previous));
+        assert_eq!(None, adjacent_addr(v6("::"), Direction::Previous));
+        assert_eq!(None, adjacent_addr(v6("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"), Direction::Next));
+    }
+}
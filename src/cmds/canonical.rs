@@ -0,0 +1,58 @@
+use crate::run::usage;
+use crate::addr::{IpAddressParseError, Ipv6Address, is_canonical_ipv6};
+use crate::cmds::{CommandContext, CommandResult, ErrorKind};
+use crate::output::Output;
+
+
+pub fn canonical(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
+    // ripcalc --canonical IPV6ADDRESS...
+    if args.len() < 3 {
+        return usage(ctx, stderr);
+    }
+
+    let mut all_canonical = true;
+    for addr_str in &args[2..] {
+        match canonical_line(addr_str) {
+            Ok((is_canonical, line)) => {
+                all_canonical &= is_canonical;
+                if let Err(e) = writeln!(stdout, "{}", line) {
+                    return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+                }
+            },
+            Err(e) => {
+                return ctx.fail(stderr, ErrorKind::from(&e), format_args!("failed to parse IPv6 address {:?}: {}", addr_str, e), 1);
+            },
+        }
+    }
+
+    if all_canonical { CommandResult::Ok } else { CommandResult::Error(2) }
+}
+
+/// Checks whether the given IPv6 address string is in canonical form and returns the result along
+/// with the line to output, which states the result followed by the canonical form (see
+/// `Ipv6Address::to_canonical_string`).
+pub fn canonical_line(addr_str: &str) -> Result<(bool, String), IpAddressParseError> {
+    let addr: Ipv6Address = addr_str.parse()?;
+    let is_canonical = is_canonical_ipv6(addr_str);
+    let status = if is_canonical { "canonical" } else { "non-canonical" };
+    Ok((is_canonical, format!("{} {}", status, addr.to_canonical_string())))
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_canonical_line() {
+        assert_eq!((true, String::from("canonical 2001:db8::1")), canonical_line("2001:db8::1").unwrap());
+        assert_eq!((false, String::from("non-canonical 2001:db8::1")), canonical_line("2001:0DB8:0:0:0:0:0:1").unwrap());
+        assert_eq!((false, String::from("non-canonical 1::2:0:0:3")), canonical_line("1:0:0:0:2::3").unwrap());
+
+        // RFC 5952 does not shorten a single zero field
+        assert_eq!((true, String::from("canonical 2001:db8:0:1:1:1:1:1")), canonical_line("2001:db8:0:1:1:1:1:1").unwrap());
+        assert_eq!((false, String::from("non-canonical 2001:db8:0:1:1:1:1:1")), canonical_line("2001:db8::1:1:1:1:1").unwrap());
+
+        assert!(canonical_line("192.0.2.1").is_err());
+    }
+}
@@ -0,0 +1,61 @@
+use crate::run::usage;
+use crate::addr::Ipv4Address;
+use crate::cmds::{CommandContext, CommandResult, ErrorKind, NetworkSpec, parse_netspec_as};
+use crate::net::IpNetwork;
+use crate::output::Output;
+
+
+pub fn cisco(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
+    // ripcalc --cisco IPADDRESS/SUBNET...
+    if args.len() < 3 {
+        return usage(ctx, stderr);
+    }
+
+    let mut nets = Vec::with_capacity(args.len() - 2);
+    for arg in &args[2..] {
+        match parse_netspec_as(arg, ctx.family) {
+            Err(e) => {
+                return ctx.fail(stderr, ErrorKind::from(&e), format_args!("failed to parse network specification {:?}: {}", arg, e), 1);
+            },
+            Ok(NetworkSpec::Ipv4(_addr, net)) => nets.push(net),
+            Ok(NetworkSpec::Ipv6(_addr, _net)) => {
+                return ctx.fail(stderr, ErrorKind::Input, format_args!("{:?} is not an IPv4 network; Cisco ACLs match IPv6 networks by prefix", arg), 1);
+            },
+        }
+    }
+
+    for net in &nets {
+        if let Err(e) = writeln!(stdout, "{}", acl_operand(net)) {
+            return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+        }
+    }
+    CommandResult::Ok
+}
+
+/// Formats the network as an address operand of a Cisco access control list entry, i.e. its base
+/// address followed by its wildcard, or `host` followed by the address for single-address
+/// networks.
+pub fn acl_operand(net: &IpNetwork<Ipv4Address>) -> String {
+    if net.cidr_prefix() == Some(32) {
+        format!("host {}", net.base_addr())
+    } else {
+        format!("{} {}", net.base_addr(), net.cisco_wildcard())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::net::test::{parse_ipv4net, parse_ipv4netm};
+
+    #[test]
+    fn test_acl_operand() {
+        assert_eq!("192.0.2.0 0.0.0.255", acl_operand(&parse_ipv4net("192.0.2.0", 24)));
+        assert_eq!("host 192.0.2.1", acl_operand(&parse_ipv4net("192.0.2.1", 32)));
+        assert_eq!("0.0.0.0 255.255.255.255", acl_operand(&parse_ipv4net("0.0.0.0", 0)));
+
+        // ACL wildcards need not be contiguous
+        assert_eq!("192.0.0.4 0.0.255.0", acl_operand(&parse_ipv4netm("192.0.0.4", "255.255.0.255")));
+    }
+}
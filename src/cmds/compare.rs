@@ -0,0 +1,137 @@
+use std::cmp::Ordering;
+
+use crate::run::usage;
+use crate::cmds::{CommandContext, CommandResult, ErrorKind, ParsedIpAddress, parse_addr_as};
+use crate::output::Output;
+
+
+pub fn compare(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
+    // ripcalc --compare IPADDRESS IPADDRESS
+    if args.len() != 4 {
+        return usage(ctx, stderr);
+    }
+
+    let mut addrs = Vec::with_capacity(2);
+    for addr_str in &args[2..] {
+        match parse_addr_as(addr_str, ctx.family) {
+            Ok(addr) => addrs.push(addr),
+            Err(e) => {
+                return ctx.fail(stderr, ErrorKind::from(&e), format_args!("failed to parse address {:?}: {}", addr_str, e), 1);
+            },
+        }
+    }
+
+    let ordering = match compare_addrs(addrs[0], addrs[1]) {
+        Some(o) => o,
+        None => {
+            return ctx.fail(stderr, ErrorKind::Input, format_args!("mixing IPv4 and IPv6 is not supported"), 1);
+        },
+    };
+    if let Err(e) = writeln!(stdout, "{}", ordering_name(ordering)) {
+        return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+    }
+    CommandResult::from_exit_code(ordering_exit_code(ordering))
+}
+
+/// Compares two IP addresses. Returns `None` if the addresses are not of the same IP version.
+pub fn compare_addrs(one: ParsedIpAddress, other: ParsedIpAddress) -> Option<Ordering> {
+    match (one, other) {
+        (ParsedIpAddress::Ipv4(o), ParsedIpAddress::Ipv4(t)) => Some(o.cmp(&t)),
+        (ParsedIpAddress::Ipv6(o), ParsedIpAddress::Ipv6(t)) => Some(o.cmp(&t)),
+        _ => None,
+    }
+}
+
+/// Returns the name under which the given ordering is output.
+pub fn ordering_name(ordering: Ordering) -> &'static str {
+    match ordering {
+        Ordering::Less => "less",
+        Ordering::Equal => "equal",
+        Ordering::Greater => "greater",
+    }
+}
+
+/// Returns the exit code encoding the given ordering. Exit code 1 is reserved for errors, so that
+/// scripts can distinguish all outcomes.
+pub fn ordering_exit_code(ordering: Ordering) -> i32 {
+    match ordering {
+        Ordering::Equal => 0,
+        Ordering::Less => 2,
+        Ordering::Greater => 3,
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cmds::parse_addr;
+    use crate::net::test::{parse_ipv4, parse_ipv6};
+    use crate::output::BufferOutput;
+
+    fn compare_strs(one: &str, other: &str) -> Option<Ordering> {
+        compare_addrs(parse_addr(one).unwrap(), parse_addr(other).unwrap())
+    }
+
+    #[test]
+    fn test_compare_addrs() {
+        assert_eq!(Some(Ordering::Less), compare_strs("192.0.2.1", "192.0.2.2"));
+        assert_eq!(Some(Ordering::Less), compare_strs("9.255.255.255", "10.0.0.0"));
+        assert_eq!(Some(Ordering::Equal), compare_strs("192.0.2.1", "192.0.2.1"));
+        assert_eq!(Some(Ordering::Greater), compare_strs("198.51.100.0", "192.0.2.255"));
+
+        assert_eq!(Some(Ordering::Less), compare_strs("2001:db8::ffff", "2001:db8::1:0"));
+        assert_eq!(Some(Ordering::Greater), compare_strs("2001:db8:1::", "2001:db8::1"));
+        assert_eq!(
+            Some(Ordering::Equal),
+            compare_addrs(ParsedIpAddress::Ipv6(parse_ipv6("2001:db8::1")), ParsedIpAddress::Ipv6(parse_ipv6("2001:0db8:0::1"))),
+        );
+
+        assert_eq!(None, compare_strs("192.0.2.1", "2001:db8::1"));
+        assert_eq!(None, compare_addrs(ParsedIpAddress::Ipv6(parse_ipv6("::")), ParsedIpAddress::Ipv4(parse_ipv4("0.0.0.0"))));
+    }
+
+    #[test]
+    fn test_compare_command() {
+        fn compare_args(args: &[&str], ctx: &CommandContext) -> (String, String, CommandResult) {
+            let args: Vec<String> = args.iter()
+                .map(|arg| String::from(*arg))
+                .collect();
+            let mut stdout = BufferOutput::new();
+            let mut stderr = BufferOutput::new();
+            let result = compare(&args, ctx, &mut stdout, &mut stderr);
+            (stdout.text(), stderr.text(), result)
+        }
+
+        let ctx = CommandContext::default();
+
+        assert_eq!(
+            (String::from("less\n"), String::new(), CommandResult::Error(2)),
+            compare_args(&["ripcalc", "--compare", "192.0.2.1", "192.0.2.2"], &ctx),
+        );
+        assert_eq!(
+            (String::from("equal\n"), String::new(), CommandResult::Ok),
+            compare_args(&["ripcalc", "--compare", "2001:db8::1", "2001:db8::1"], &ctx),
+        );
+        assert_eq!(
+            (String::from("greater\n"), String::new(), CommandResult::Error(3)),
+            compare_args(&["ripcalc", "--compare", "198.51.100.0", "192.0.2.255"], &ctx),
+        );
+
+        let (stdout, stderr, result) = compare_args(&["ripcalc", "--compare", "192.0.2.1", "2001:db8::1"], &ctx);
+        assert_eq!("", stdout);
+        assert_eq!("mixing IPv4 and IPv6 is not supported\n", stderr);
+        assert_eq!(CommandResult::Error(1), result);
+    }
+
+    #[test]
+    fn test_ordering_output() {
+        assert_eq!("less", ordering_name(Ordering::Less));
+        assert_eq!("equal", ordering_name(Ordering::Equal));
+        assert_eq!("greater", ordering_name(Ordering::Greater));
+
+        assert_eq!(0, ordering_exit_code(Ordering::Equal));
+        assert_eq!(2, ordering_exit_code(Ordering::Less));
+        assert_eq!(3, ordering_exit_code(Ordering::Greater));
+    }
+}
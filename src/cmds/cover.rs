@@ -0,0 +1,172 @@
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+use crate::run::usage;
+use crate::addr::IpAddress;
+use crate::cmds::{
+    AddressFamily, CommandContext, CommandResult, ErrorKind, NetworkSpecs, ParseNetspecError, mixed_specs_message,
+    parse_same_family_netspecs, read_netspec_lines, split_netspec_list,
+};
+use crate::net::{IpNetwork, covering_aggregate};
+use crate::output::Output;
+
+
+/// An error that occurred while computing the covering network of a list of networks.
+#[derive(Debug)]
+pub enum CoverError {
+    /// The networks could not be read.
+    Read(io::Error),
+
+    /// A network could not be parsed.
+    Parse(ParseNetspecError),
+
+    /// No networks were given.
+    NoNetworks,
+
+    /// The networks are not all of the same IP version. The values are the same as in
+    /// `NetworkSpecs::MixedSpecs`.
+    MixedSpecs(usize, String),
+}
+impl fmt::Display for CoverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoverError::Read(e)
+                => write!(f, "failed to read networks: {}", e),
+            CoverError::Parse(e)
+                => write!(f, "failed to parse network: {}", e),
+            CoverError::NoNetworks
+                => write!(f, "no networks given"),
+            CoverError::MixedSpecs(index, spec)
+                => write!(f, "mixing IPv4 and IPv6 is not supported: {}", mixed_specs_message(*index, spec)),
+        }
+    }
+}
+impl Error for CoverError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CoverError::Read(e) => Some(e),
+            CoverError::Parse(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+impl From<&CoverError> for ErrorKind {
+    fn from(e: &CoverError) -> Self {
+        match e {
+            CoverError::Read(_) => ErrorKind::Io,
+            CoverError::Parse(pe) => ErrorKind::from(pe),
+            CoverError::NoNetworks => ErrorKind::Usage,
+            CoverError::MixedSpecs(_, _) => ErrorKind::Input,
+        }
+    }
+}
+
+
+pub fn cover<R: BufRead>(
+    args: &[String], ctx: &CommandContext, mut input: R, stdout: &mut dyn Output, stderr: &mut dyn Output,
+) -> CommandResult {
+    // ripcalc --cover IPADDRESS/SUBNET...
+    if args.len() < 3 {
+        return usage(ctx, stderr);
+    }
+
+    let mut spec_strs = Vec::with_capacity(args.len() - 2);
+    for arg in &args[2..] {
+        if arg == "-" {
+            // read the specifications from stdin
+            match read_netspec_lines(&mut input) {
+                Ok(specs) => spec_strs.extend(specs),
+                Err(e) => {
+                    return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to read networks from stdin: {}", e), 1);
+                },
+            }
+        } else {
+            spec_strs.extend(split_netspec_list(arg).map(String::from));
+        }
+    }
+
+    output_cover(ctx, stdout, stderr, cover_specs(&spec_strs, ctx.family))
+}
+
+pub fn cover_file(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
+    // ripcalc --cover-file FILE
+    if args.len() != 3 {
+        return usage(ctx, stderr);
+    }
+
+    let file = match File::open(&args[2]) {
+        Ok(f) => f,
+        Err(e) => {
+            return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to read networks from {:?}: {}", args[2], e), 1);
+        },
+    };
+    output_cover(ctx, stdout, stderr, cover_reader(BufReader::new(file), ctx.family))
+}
+
+fn output_cover(ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output, cover_res: Result<String, CoverError>) -> CommandResult {
+    match cover_res {
+        Ok(aggregate) => {
+            match writeln!(stdout, "{}", aggregate) {
+                Ok(()) => CommandResult::Ok,
+                Err(e) => ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1),
+            }
+        },
+        Err(CoverError::NoNetworks) => {
+            usage(ctx, stderr)
+        },
+        Err(e) => {
+            ctx.fail(stderr, ErrorKind::from(&e), format_args!("{}", e), 1)
+        },
+    }
+}
+
+/// Returns the smallest network containing all of the networks in the given specifications,
+/// formatted as a string. If a family is given, specifications of the other family are rejected.
+pub fn cover_specs<S: AsRef<str>>(spec_strs: &[S], family: Option<AddressFamily>) -> Result<String, CoverError> {
+    match parse_same_family_netspecs(spec_strs, family).map_err(CoverError::Parse)? {
+        NetworkSpecs::Nothing
+            => Err(CoverError::NoNetworks),
+        NetworkSpecs::MixedSpecs(index, spec)
+            => Err(CoverError::MixedSpecs(index, spec)),
+        NetworkSpecs::Ipv4(specs)
+            => Ok(cover_networks(specs.iter().map(|(_addr, net)| *net).collect())),
+        NetworkSpecs::Ipv6(specs)
+            => Ok(cover_networks(specs.iter().map(|(_addr, net)| *net).collect())),
+    }
+}
+
+/// Reads network specifications from the given reader as described in `read_netspec_lines` and
+/// returns the smallest network containing all of them, formatted as a string.
+pub fn cover_reader<R: BufRead>(reader: R, family: Option<AddressFamily>) -> Result<String, CoverError> {
+    let spec_strs = read_netspec_lines(reader)
+        .map_err(CoverError::Read)?;
+    cover_specs(&spec_strs, family)
+}
+
+fn cover_networks<A: IpAddress>(nets: Vec<IpNetwork<A>>) -> String {
+    covering_aggregate(&nets)
+        .expect("at least one network")
+        .to_string()
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cover_reader() {
+        let input = "192.0.2.0/24\n# comment\n192.0.1.0/24 branch office\n\n192.0.3.0/24\n";
+        assert_eq!("192.0.0.0/22", cover_reader(input.as_bytes(), None).unwrap());
+
+        let mixed = "192.0.2.0/24\n2001:db8::/32\n";
+        match cover_reader(mixed.as_bytes(), None) {
+            Err(CoverError::MixedSpecs(1, spec)) => assert_eq!("2001:db8::/32", spec),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert!(matches!(cover_reader("# nothing here\n".as_bytes(), None), Err(CoverError::NoNetworks)));
+    }
+}
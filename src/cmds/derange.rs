@@ -1,53 +1,137 @@
 use std::cmp::{max, min};
+use std::io::{self, Write};
 
-use crate::usage;
+use crate::run::usage;
 use crate::addr::IpAddress;
-use crate::cmds::{parse_addr, ParsedIpAddress};
-use crate::net::IpNetwork;
+use crate::cmds::{
+    AddressFamily, CommandContext, CommandResult, ErrorKind, NetworkSpec, ParseNetspecError, ParsedIpAddress,
+    parse_addr_as, parse_netspec_as, write_json_string_array,
+};
+use crate::cmds::minimize::minimize_subnets;
+use crate::net::{IpNetwork, MaskFormat};
+use crate::output::Output;
+use crate::range::AddrRange;
 
 
-pub fn derange(args: &[String]) -> i32 {
-    // ripcalc --derange ONE OTHER
-    if args.len() != 4 {
-        usage();
-        return 1;
+pub fn derange(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
+    // ripcalc --derange [--json] [--mask-format=FORMAT] ONE OTHER
+    output_range_networks(args, ctx, stdout, stderr, false)
+}
+
+pub fn summarize(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
+    // ripcalc --summarize [--json] [--mask-format=FORMAT] ONE OTHER
+    output_range_networks(args, ctx, stdout, stderr, true)
+}
+
+fn output_range_networks(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output, summarize: bool) -> CommandResult {
+    let mut json = false;
+    let mut mask_format = MaskFormat::default();
+    let mut addr_strs: Vec<&String> = Vec::with_capacity(2);
+    for arg in &args[2..] {
+        if arg == "--json" {
+            json = true;
+        } else if let Some(format_name) = arg.strip_prefix("--mask-format=") {
+            mask_format = match MaskFormat::from_name(format_name) {
+                Some(mf) => mf,
+                None => {
+                    return ctx.fail(stderr, ErrorKind::Usage, format_args!("unknown mask format {:?}; expected cidr, slash or space", format_name), 1);
+                },
+            };
+        } else {
+            addr_strs.push(arg);
+        }
+    }
+    if addr_strs.len() != 2 {
+        return usage(ctx, stderr);
     }
 
-    let one = match parse_addr(&args[2]) {
+    let (one_first, one_last) = match parse_endpoint(addr_strs[0], ctx.family) {
         Ok(a) => a,
         Err(e) => {
-            eprintln!("failed to parse first address: {}", e);
-            return 1;
+            return ctx.fail(stderr, ErrorKind::from(&e), format_args!("failed to parse first endpoint: {}", e), 1);
         },
     };
-    let other = match parse_addr(&args[3]) {
+    let (other_first, other_last) = match parse_endpoint(addr_strs[1], ctx.family) {
         Ok(a) => a,
         Err(e) => {
-            eprintln!("failed to parse second address: {}", e);
-            return 1;
+            return ctx.fail(stderr, ErrorKind::from(&e), format_args!("failed to parse second endpoint: {}", e), 1);
         },
     };
 
-    if one.version() != other.version() {
-        eprintln!("both addresses must be the same version");
-        return 1;
-    } else if let ParsedIpAddress::Ipv4(one_addr) = one {
-        if let ParsedIpAddress::Ipv4(other_addr) = other {
-            let subnets = range_to_subnets(one_addr, other_addr);
-            for subnet in subnets {
-                println!("{}", subnet);
-            }
-        }
-    } else if let ParsedIpAddress::Ipv6(one_addr) = one {
-        if let ParsedIpAddress::Ipv6(other_addr) = other {
-            let subnets = range_to_subnets(one_addr, other_addr);
-            for subnet in subnets {
-                println!("{}", subnet);
-            }
-        }
+    if one_first.version() != other_first.version() {
+        return ctx.fail(stderr, ErrorKind::Input, format_args!("both endpoints must be the same version"), 1);
     }
 
-    0
+    let output_res = match (one_first, one_last, other_first, other_last) {
+        (
+            ParsedIpAddress::Ipv4(one_first_addr), ParsedIpAddress::Ipv4(one_last_addr),
+            ParsedIpAddress::Ipv4(other_first_addr), ParsedIpAddress::Ipv4(other_last_addr),
+        ) => {
+            let range = AddrRange::new(min(one_first_addr, other_first_addr), max(one_last_addr, other_last_addr));
+            output_subnets(stdout, &range_networks(range, summarize), json, mask_format)
+        },
+        (
+            ParsedIpAddress::Ipv6(one_first_addr), ParsedIpAddress::Ipv6(one_last_addr),
+            ParsedIpAddress::Ipv6(other_first_addr), ParsedIpAddress::Ipv6(other_last_addr),
+        ) => {
+            let range = AddrRange::new(min(one_first_addr, other_first_addr), max(one_last_addr, other_last_addr));
+            output_subnets(stdout, &range_networks(range, summarize), json, mask_format)
+        },
+        _ => unreachable!("endpoint versions have been compared"),
+    };
+    if let Err(e) = output_res {
+        return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+    }
+
+    CommandResult::Ok
+}
+
+/// Parses an endpoint of a range, which is either an IP address or an IP network, and returns the
+/// first and last address of the endpoint. For an IP address, both are the address itself; for an
+/// IP network, they are the network's base address and its last address. If a family is given,
+/// endpoints of the other family are rejected.
+fn parse_endpoint(spec: &str, family: Option<AddressFamily>) -> Result<(ParsedIpAddress, ParsedIpAddress), ParseNetspecError> {
+    let addr_err = match parse_addr_as(spec, family) {
+        Ok(addr) => return Ok((addr, addr)),
+        Err(e) => e,
+    };
+    if !spec.contains('/') {
+        // not a network either; the address error is more helpful
+        return Err(ParseNetspecError::Address(addr_err));
+    }
+
+    match parse_netspec_as(spec, family)? {
+        NetworkSpec::Ipv4(_addr, net) => Ok((
+            ParsedIpAddress::Ipv4(net.base_addr()),
+            ParsedIpAddress::Ipv4(net.last_addr_of_subnet()),
+        )),
+        NetworkSpec::Ipv6(_addr, net) => Ok((
+            ParsedIpAddress::Ipv6(net.base_addr()),
+            ParsedIpAddress::Ipv6(net.last_addr_of_subnet()),
+        )),
+    }
+}
+
+fn output_subnets<A: IpAddress, W: Write + ?Sized>(out: &mut W, subnets: &[IpNetwork<A>], json: bool, mask_format: MaskFormat) -> io::Result<()> {
+    let subnet_strs: Vec<String> = subnets.iter()
+        .map(|subnet| subnet.to_string_with_mask(mask_format))
+        .collect();
+    if json {
+        return write_json_string_array(out, &subnet_strs);
+    }
+    for subnet_str in &subnet_strs {
+        writeln!(out, "{}", subnet_str)?;
+    }
+    Ok(())
+}
+
+
+fn range_networks<A: IpAddress>(range: AddrRange<A>, summarize: bool) -> Vec<IpNetwork<A>> {
+    if summarize {
+        summarize_range(range.start(), range.end())
+    } else {
+        range.to_networks()
+    }
 }
 
 
@@ -65,22 +149,38 @@ pub fn range_to_subnets<A: IpAddress>(
     // start with the full mask
     let mut current_subnet = IpNetwork::new_with_prefix(first_addr, last_addr.byte_count() * 8);
     while first_addr <= last_addr {
-        // try enlarging the subnet
-        let larger_subnet = IpNetwork::new_with_prefix(first_addr, current_subnet.cidr_prefix().unwrap() - 1);
-        if larger_subnet.base_addr() != first_addr || larger_subnet.last_addr_of_subnet() > last_addr {
+        // try enlarging the subnet (unless it already spans the whole address space)
+        let larger_subnet = current_subnet.cidr_prefix().unwrap()
+            .checked_sub(1)
+            .map(|prefix| IpNetwork::new_with_prefix(first_addr, prefix))
+            .filter(|ls| ls.base_addr() == first_addr && ls.last_addr_of_subnet() <= last_addr);
+        if let Some(ls) = larger_subnet {
+            // anchor the growth and continue
+            current_subnet = ls;
+        } else {
             // we've gone beyond; store what we have and continue with the next chunk
             ret.push(current_subnet);
-            first_addr = current_subnet.next_subnet_base_addr().unwrap();
+            first_addr = match current_subnet.next_subnet_base_addr() {
+                Some(nsba) => nsba,
+                None => break, // we have reached the end of the address space
+            };
             current_subnet = IpNetwork::new_with_prefix(first_addr, last_addr.byte_count() * 8);
-        } else {
-            // anchor the growth and continue
-            current_subnet = larger_subnet;
         }
     }
 
     ret
 }
 
+/// Converts a range of IP addresses (whose inclusive ends are passed as `end_one` and `end_two`)
+/// into the equivalent set of IP networks like `range_to_subnets`, then minimizes the resulting
+/// list of networks using `minimize_subnets`.
+pub fn summarize_range<A: IpAddress>(
+    end_one: A,
+    end_two: A,
+) -> Vec<IpNetwork<A>> {
+    minimize_subnets(range_to_subnets(end_one, end_two))
+}
+
 
 #[cfg(test)]
 mod test {
@@ -159,4 +259,99 @@ mod test {
         assert_eq!(parse_ipv6net("2001:db8::fffc", 127), subnet[28]);
         assert_eq!(parse_ipv6net("2001:db8::fffe", 128), subnet[29]);
     }
+
+    #[test]
+    fn test_parse_endpoint() {
+        assert_eq!(
+            Ok((ParsedIpAddress::Ipv4(parse_ipv4("192.0.2.7")), ParsedIpAddress::Ipv4(parse_ipv4("192.0.2.7")))),
+            parse_endpoint("192.0.2.7", None),
+        );
+        assert_eq!(
+            Ok((ParsedIpAddress::Ipv4(parse_ipv4("192.0.2.0")), ParsedIpAddress::Ipv4(parse_ipv4("192.0.2.255")))),
+            parse_endpoint("192.0.2.7/24", None),
+        );
+        assert_eq!(
+            Ok((ParsedIpAddress::Ipv6(parse_ipv6("2001:db8::")), ParsedIpAddress::Ipv6(parse_ipv6("2001:db8::ff")))),
+            parse_endpoint("2001:db8::/120", None),
+        );
+        assert!(matches!(parse_endpoint("192.0.2", None), Err(ParseNetspecError::Address(_))));
+        assert_eq!(Err(ParseNetspecError::CidrRange(33, 32)), parse_endpoint("192.0.2.0/33", None));
+    }
+
+    #[test]
+    fn test_derange_between_networks() {
+        let (one_first, _one_last) = parse_endpoint("192.0.2.0/24", None).unwrap();
+        let (_other_first, other_last) = parse_endpoint("192.0.5.0/24", None).unwrap();
+        let (one_first, other_last) = match (one_first, other_last) {
+            (ParsedIpAddress::Ipv4(f), ParsedIpAddress::Ipv4(l)) => (f, l),
+            _ => panic!("expected IPv4 endpoints"),
+        };
+        let subnet = range_to_subnets(one_first, other_last);
+        assert_eq!(2, subnet.len());
+        assert_eq!(parse_ipv4net("192.0.2.0", 23), subnet[0]);
+        assert_eq!(parse_ipv4net("192.0.4.0", 23), subnet[1]);
+    }
+
+    #[test]
+    fn test_derange_address_space_edges() {
+        let subnet = range_to_subnets(parse_ipv4("0.0.0.0"), parse_ipv4("255.255.255.255"));
+        assert_eq!(1, subnet.len());
+        assert_eq!(parse_ipv4net("0.0.0.0", 0), subnet[0]);
+
+        let subnet = range_to_subnets(parse_ipv4("255.255.255.0"), parse_ipv4("255.255.255.255"));
+        assert_eq!(1, subnet.len());
+        assert_eq!(parse_ipv4net("255.255.255.0", 24), subnet[0]);
+
+        let subnet = range_to_subnets(parse_ipv4("255.255.255.255"), parse_ipv4("255.255.255.255"));
+        assert_eq!(1, subnet.len());
+        assert_eq!(parse_ipv4net("255.255.255.255", 32), subnet[0]);
+
+        let subnet = range_to_subnets(parse_ipv6("::"), parse_ipv6("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"));
+        assert_eq!(1, subnet.len());
+        assert_eq!(parse_ipv6net("::", 0), subnet[0]);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let ranges = [
+            ("192.0.2.1", "192.0.2.254"),
+            ("192.0.2.0", "192.0.5.255"),
+            ("10.0.0.7", "10.255.255.0"),
+            ("0.0.0.1", "255.255.255.255"),
+        ];
+        for (start_str, end_str) in &ranges {
+            let start = parse_ipv4(start_str);
+            let end = parse_ipv4(end_str);
+            let deranged = range_to_subnets(start, end);
+            let summarized = summarize_range(start, end);
+
+            // the cover calculated by range_to_subnets is already minimal
+            assert_eq!(deranged.len(), summarized.len());
+            assert_eq!(deranged, summarized);
+        }
+
+        let summarized = summarize_range(parse_ipv4("192.0.2.0"), parse_ipv4("192.0.5.255"));
+        assert_eq!(2, summarized.len());
+        assert_eq!(parse_ipv4net("192.0.2.0", 23), summarized[0]);
+        assert_eq!(parse_ipv4net("192.0.4.0", 23), summarized[1]);
+    }
+
+    #[test]
+    fn test_output_subnets_mask_format() {
+        let subnets = [parse_ipv4net("192.0.2.0", 24)];
+        let expected = [
+            (MaskFormat::Cidr, "192.0.2.0/24\n", "[\"192.0.2.0/24\"]\n"),
+            (MaskFormat::Slash, "192.0.2.0/255.255.255.0\n", "[\"192.0.2.0/255.255.255.0\"]\n"),
+            (MaskFormat::Space, "192.0.2.0 255.255.255.0\n", "[\"192.0.2.0 255.255.255.0\"]\n"),
+        ];
+        for (mask_format, plain_str, json_str) in expected {
+            let mut output = Vec::new();
+            output_subnets(&mut output, &subnets, false, mask_format).unwrap();
+            assert_eq!(plain_str, String::from_utf8(output).unwrap());
+
+            let mut output = Vec::new();
+            output_subnets(&mut output, &subnets, true, mask_format).unwrap();
+            assert_eq!(json_str, String::from_utf8(output).unwrap());
+        }
+    }
 }
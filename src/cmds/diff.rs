@@ -0,0 +1,178 @@
+use std::fs::File;
+use std::io::{self, BufReader, Write};
+
+use crate::run::usage;
+use crate::addr::{IpAddress, Ipv4Address, Ipv6Address};
+use crate::cmds::{
+    AddressFamily, CommandContext, CommandResult, ErrorKind, NetworkSpec, ParseNetspecError, parse_netspec_as,
+    read_netspec_lines,
+};
+use crate::cmds::minimize::minimize_subnets;
+use crate::net::IpNetwork;
+use crate::output::Output;
+
+
+/// A difference between two lists of networks.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NetworkChange<A: IpAddress> {
+    /// The network is only contained in the new list.
+    Added(IpNetwork<A>),
+
+    /// The network is only contained in the old list.
+    Removed(IpNetwork<A>),
+}
+impl<A: IpAddress> NetworkChange<A> {
+    /// The network that has been added or removed.
+    pub fn network(&self) -> IpNetwork<A> {
+        match self {
+            NetworkChange::Added(net) => *net,
+            NetworkChange::Removed(net) => *net,
+        }
+    }
+}
+
+
+/// A list of networks, split up by IP version.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+struct NetworkSet {
+    ipv4: Vec<IpNetwork<Ipv4Address>>,
+    ipv6: Vec<IpNetwork<Ipv6Address>>,
+}
+impl NetworkSet {
+    /// Parses the given network specifications into a set, sorting them by IP version. If a family
+    /// is given, specifications of the other family are rejected.
+    fn parse<S: AsRef<str>>(spec_strs: &[S], family: Option<AddressFamily>) -> Result<NetworkSet, ParseNetspecError> {
+        let mut set = NetworkSet::default();
+        for spec_str in spec_strs {
+            match parse_netspec_as(spec_str.as_ref(), family)? {
+                NetworkSpec::Ipv4(_addr, net) => set.ipv4.push(net),
+                NetworkSpec::Ipv6(_addr, net) => set.ipv6.push(net),
+            }
+        }
+        Ok(set)
+    }
+}
+
+
+pub fn diff(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
+    // ripcalc --diff FILE FILE
+    // ripcalc --diff IPADDRESS/SUBNET... -- IPADDRESS/SUBNET...
+    let (old_strs, new_strs) = if let Some(separator_index) = args.iter().skip(2).position(|a| a == "--") {
+        let separator_index = separator_index + 2;
+        (args[2..separator_index].to_vec(), args[separator_index+1..].to_vec())
+    } else if args.len() == 4 {
+        let old_strs = match read_netspec_file(&args[2]) {
+            Ok(ss) => ss,
+            Err(e) => {
+                return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to read networks from {:?}: {}", args[2], e), 1);
+            },
+        };
+        let new_strs = match read_netspec_file(&args[3]) {
+            Ok(ss) => ss,
+            Err(e) => {
+                return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to read networks from {:?}: {}", args[3], e), 1);
+            },
+        };
+        (old_strs, new_strs)
+    } else {
+        return usage(ctx, stderr);
+    };
+
+    let old_set = match NetworkSet::parse(&old_strs, ctx.family) {
+        Ok(s) => s,
+        Err(e) => {
+            return ctx.fail(stderr, ErrorKind::from(&e), format_args!("failed to parse old networks: {}", e), 1);
+        },
+    };
+    let new_set = match NetworkSet::parse(&new_strs, ctx.family) {
+        Ok(s) => s,
+        Err(e) => {
+            return ctx.fail(stderr, ErrorKind::from(&e), format_args!("failed to parse new networks: {}", e), 1);
+        },
+    };
+
+    let output_res = output_changes(stdout, &diff_networks(old_set.ipv4, new_set.ipv4))
+        .and_then(|()| output_changes(stdout, &diff_networks(old_set.ipv6, new_set.ipv6)));
+    if let Err(e) = output_res {
+        return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+    }
+    CommandResult::Ok
+}
+
+fn read_netspec_file(path: &str) -> io::Result<Vec<String>> {
+    let file = File::open(path)?;
+    read_netspec_lines(BufReader::new(file))
+}
+
+fn output_changes<A: IpAddress, W: Write + ?Sized>(out: &mut W, changes: &[NetworkChange<A>]) -> io::Result<()> {
+    for change in changes {
+        match change {
+            NetworkChange::Added(net) => writeln!(out, "+{}", net)?,
+            NetworkChange::Removed(net) => writeln!(out, "-{}", net)?,
+        }
+    }
+    Ok(())
+}
+
+/// Minimizes both lists of networks and returns the networks that are only contained in one of
+/// them, ordered by network.
+pub fn diff_networks<A: IpAddress>(old_nets: Vec<IpNetwork<A>>, new_nets: Vec<IpNetwork<A>>) -> Vec<NetworkChange<A>> {
+    let old_minimized = minimize_subnets(old_nets);
+    let new_minimized = minimize_subnets(new_nets);
+
+    let mut changes: Vec<NetworkChange<A>> = old_minimized.iter()
+        .filter(|net| !new_minimized.contains(net))
+        .map(|net| NetworkChange::Removed(*net))
+        .chain(
+            new_minimized.iter()
+                .filter(|net| !old_minimized.contains(net))
+                .map(|net| NetworkChange::Added(*net))
+        )
+        .collect();
+    changes.sort_by_key(|change| change.network());
+    changes
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::net::test::{parse_ipv4net, parse_ipv6net};
+
+    #[test]
+    fn test_diff_networks() {
+        let old_nets = vec![
+            parse_ipv4net("192.0.2.0", 24),
+            parse_ipv4net("198.51.100.0", 24),
+        ];
+        let new_nets = vec![
+            parse_ipv4net("198.51.100.0", 24),
+            parse_ipv4net("203.0.113.0", 24),
+        ];
+        assert_eq!(
+            vec![
+                NetworkChange::Removed(parse_ipv4net("192.0.2.0", 24)),
+                NetworkChange::Added(parse_ipv4net("203.0.113.0", 24)),
+            ],
+            diff_networks(old_nets, new_nets),
+        );
+
+        // lists are compared after minimization
+        let old_nets = vec![
+            parse_ipv6net("2001:db8::", 33),
+            parse_ipv6net("2001:db8:8000::", 33),
+        ];
+        let new_nets = vec![
+            parse_ipv6net("2001:db8::", 32),
+            parse_ipv6net("2001:db8:1::", 48),
+        ];
+        assert_eq!(Vec::<NetworkChange<Ipv6Address>>::new(), diff_networks(old_nets, new_nets));
+    }
+
+    #[test]
+    fn test_network_set() {
+        let set = NetworkSet::parse(&["192.0.2.0/24", "2001:db8::/32", "198.51.100.0/24"], None).unwrap();
+        assert_eq!(vec![parse_ipv4net("192.0.2.0", 24), parse_ipv4net("198.51.100.0", 24)], set.ipv4);
+        assert_eq!(vec![parse_ipv6net("2001:db8::", 32)], set.ipv6);
+    }
+}
@@ -1,27 +1,36 @@
+use std::fmt;
+use std::io::{self, BufRead, Write};
 use std::iter::Iterator;
 
-use crate::usage;
-use crate::addr::IpAddress;
-use crate::bit_manip::{unravel_address, weave_address};
-use crate::cmds::{NetworkSpec, parse_netspec};
+use crate::run::usage;
+use crate::addr::{IpAddress, Ipv4Address, reverse_dns_name};
+use crate::bit_manip::MaskPlan;
+use crate::cmds::{CommandContext, CommandResult, ErrorKind, NetworkSpec, parse_netspec_as, read_netspec_lines};
 use crate::net::IpNetwork;
+use crate::output::Output;
+
+
+/// The maximum number of addresses of a network that are enumerated is 2^MAX_ENUMERATE_BITS. This
+/// allows any IPv4 network but refuses IPv6 networks larger than a /96.
+const MAX_ENUMERATE_BITS: u32 = 32;
 
 
 struct NetworkIter<A: IpAddress> {
     is_empty: bool,
     unraveled_addr: A,
     last_unraveled_addr: A,
-    subnet_mask: A,
+    mask_plan: MaskPlan,
 }
 impl<A: IpAddress> NetworkIter<A> {
     pub fn new(network: IpNetwork<A>) -> Self {
-        let unraveled_addr = unravel_address(network.base_addr(), network.subnet_mask());
-        let last_unraveled_addr = unravel_address(network.last_addr_of_subnet(), network.subnet_mask());
+        let mask_plan = MaskPlan::new(network.subnet_mask());
+        let unraveled_addr = mask_plan.unravel(network.base_addr());
+        let last_unraveled_addr = mask_plan.unravel(network.last_addr_of_subnet());
         Self {
             is_empty: false,
             unraveled_addr,
             last_unraveled_addr,
-            subnet_mask: network.subnet_mask(),
+            mask_plan,
         }
     }
 }
@@ -37,7 +46,7 @@ impl<A: IpAddress> Iterator for NetworkIter<A> {
             return None;
         }
 
-        let woven_addr = weave_address(self.unraveled_addr, self.subnet_mask);
+        let woven_addr = self.mask_plan.weave(self.unraveled_addr);
         if let Some(next_addr) = self.unraveled_addr.add_offset(1) {
             self.unraveled_addr = next_addr;
         } else {
@@ -48,42 +57,185 @@ impl<A: IpAddress> Iterator for NetworkIter<A> {
     }
 }
 
-pub fn enumerate(args: &[String]) -> i32 {
-    // ripcalc --enumerate IPNETWORK...
+/// Writes addresses separated by a separator. Unless nothing has been written, the output is
+/// terminated by a newline once `finish` is called.
+struct SeparatedWriter<'a, W: Write + ?Sized> {
+    out: &'a mut W,
+    separator: &'a str,
+    is_first: bool,
+}
+impl<'a, W: Write + ?Sized> SeparatedWriter<'a, W> {
+    pub fn new(out: &'a mut W, separator: &'a str) -> Self {
+        Self {
+            out,
+            separator,
+            is_first: true,
+        }
+    }
+
+    /// Writes an address, preceded by the separator unless it is the first address.
+    pub fn write_address<D: fmt::Display>(&mut self, address: D) -> io::Result<()> {
+        if !self.is_first {
+            write!(self.out, "{}", self.separator)?;
+        }
+        self.is_first = false;
+        write!(self.out, "{}", address)
+    }
+
+    /// Terminates the output with a newline if any address has been written.
+    pub fn finish(self) -> io::Result<()> {
+        if self.is_first {
+            Ok(())
+        } else {
+            writeln!(self.out)
+        }
+    }
+}
+
+/// Options for the enumeration of addresses.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+struct EnumerateOptions<'a> {
+    /// Output the reverse DNS names of the addresses instead of the addresses themselves.
+    reverse: bool,
+
+    /// Only output the boundary addresses (base and broadcast address) of each network.
+    boundaries: bool,
+
+    /// Output the addresses padded with zeroes to a fixed width (see
+    /// `Ipv4Address::to_zero_padded_string` and `Ipv6Address::to_full_string`).
+    padded: bool,
+
+    /// The separator between consecutive addresses, or `None` to output each address on its own
+    /// line.
+    separator: Option<&'a str>,
+}
+
+pub fn enumerate<R: BufRead>(
+    args: &[String], ctx: &CommandContext, input: R, stdout: &mut dyn Output, stderr: &mut dyn Output,
+) -> CommandResult {
+    // ripcalc --enumerate [--reverse] [--boundaries] [--padded] [--separator SEPARATOR] IPNETWORK...
     if args.len() < 3 {
-        usage();
-        return 1;
+        return usage(ctx, stderr);
+    }
+
+    let mut options = EnumerateOptions::default();
+    let mut net_strs = Vec::with_capacity(args.len() - 2);
+    let mut arg_iter = args[2..].iter();
+    while let Some(arg) = arg_iter.next() {
+        if arg == "--reverse" {
+            options.reverse = true;
+        } else if arg == "--boundaries" {
+            options.boundaries = true;
+        } else if arg == "--padded" {
+            options.padded = true;
+        } else if arg == "--separator" {
+            match arg_iter.next() {
+                Some(separator) => options.separator = Some(separator),
+                None => {
+                    return usage(ctx, stderr);
+                },
+            }
+        } else {
+            net_strs.push(arg.clone());
+        }
+    }
+    if net_strs.is_empty() {
+        return usage(ctx, stderr);
     }
 
+    match enumerate_networks(&net_strs, options, input, ctx, stdout, stderr) {
+        Ok(0) => CommandResult::Ok,
+        Ok(ret) => CommandResult::Error(ret),
+        Err(e) => ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1),
+    }
+}
+
+/// Outputs all addresses of the given networks. A network specification of `-` is replaced by the
+/// network specifications read from `input` using `read_netspec_lines`.
+fn enumerate_networks<R: BufRead, W: Write + ?Sized>(
+    net_strs: &[String], options: EnumerateOptions, mut input: R, ctx: &CommandContext, out: &mut W,
+    stderr: &mut dyn Output,
+) -> io::Result<i32> {
+    let mut out = SeparatedWriter::new(out, options.separator.unwrap_or("\n"));
     let mut ret: i32 = 0;
-    for net_str in &args[2..] {
-        match parse_netspec(net_str) {
-            Err(e) => {
-                eprintln!("failed to parse network {:?}: {}", net_str, e);
-                ret = 1;
-            },
-            Ok(NetworkSpec::Ipv4(_addr, net)) => {
-                let iterator = NetworkIter::new(net);
-                for addr in iterator {
-                    println!("{}", addr);
-                }
-            },
-            Ok(NetworkSpec::Ipv6(_addr, net)) => {
-                let iterator = NetworkIter::new(net);
-                for addr in iterator {
-                    println!("{}", addr);
+    for net_str in net_strs {
+        if net_str == "-" {
+            let read_strs = match read_netspec_lines(&mut input) {
+                Ok(rs) => rs,
+                Err(e) => {
+                    ctx.fail(stderr, ErrorKind::Io, format_args!("failed to read networks from stdin: {}", e), 1);
+                    ret = 1;
+                    continue;
+                },
+            };
+            for read_str in &read_strs {
+                if enumerate_network(read_str, options, ctx, &mut out, stderr)? != 0 {
+                    ret = 1;
                 }
-            },
-        };
+            }
+        } else if enumerate_network(net_str, options, ctx, &mut out, stderr)? != 0 {
+            ret = 1;
+        }
     }
 
-    ret
+    out.finish()?;
+    Ok(ret)
+}
+
+fn enumerate_network<W: Write + ?Sized>(
+    net_str: &str, options: EnumerateOptions, ctx: &CommandContext, out: &mut SeparatedWriter<W>,
+    stderr: &mut dyn Output,
+) -> io::Result<i32> {
+    let spec = match parse_netspec_as(net_str, ctx.family) {
+        Ok(s) => s,
+        Err(e) => {
+            ctx.fail(stderr, ErrorKind::from(&e), format_args!("failed to parse network {:?}: {}", net_str, e), 1);
+            return Ok(1);
+        },
+    };
+
+    // only the boundary addresses are output regardless of the size of the network
+    let too_large = !options.boundaries && match &spec {
+        NetworkSpec::Ipv4(_addr, net) => net.address_count_exceeds(1 << MAX_ENUMERATE_BITS),
+        NetworkSpec::Ipv6(_addr, net) => net.address_count_exceeds(1 << MAX_ENUMERATE_BITS),
+    };
+    if too_large {
+        ctx.fail(stderr, ErrorKind::Input, format_args!("refusing to enumerate {}, which has more than 2^{} addresses", net_str, MAX_ENUMERATE_BITS), 1);
+        return Ok(1);
+    }
+
+    match spec {
+        NetworkSpec::Ipv4(_addr, net) => output_addresses(net, options, Ipv4Address::to_zero_padded_string, out)?,
+        NetworkSpec::Ipv6(_addr, net) => output_addresses(net, options, |addr| addr.to_full_string(), out)?,
+    };
+    Ok(0)
+}
+
+/// Outputs the addresses of the given network. `padded_string` returns the fixed-width
+/// representation of an address, which is output if `options.padded` is set.
+fn output_addresses<A: IpAddress, PS: Fn(A) -> String, W: Write + ?Sized>(net: IpNetwork<A>, options: EnumerateOptions, padded_string: PS, out: &mut SeparatedWriter<W>) -> io::Result<()> {
+    let iterator: Box<dyn Iterator<Item = A>> = if options.boundaries {
+        Box::new(net.boundary_addresses())
+    } else {
+        Box::new(NetworkIter::new(net))
+    };
+    for addr in iterator {
+        if options.reverse {
+            out.write_address(reverse_dns_name(&addr))?;
+        } else if options.padded {
+            out.write_address(padded_string(addr))?;
+        } else {
+            out.write_address(addr)?;
+        }
+    }
+    Ok(())
 }
 
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::output::BufferOutput;
     use crate::net::test::{
         parse_ipv4, parse_ipv4net, parse_ipv4netm, parse_ipv6, parse_ipv6net, parse_ipv6netm,
     };
@@ -173,4 +325,125 @@ mod test {
         assert_eq!(None, iter.next());
         assert_eq!(None, iter.next());
     }
+
+    #[test]
+    fn test_enumerate_from_input() {
+        let input = b"192.0.2.4/31 ; first
+# comment
+
+2001:db8::/127
+" as &[u8];
+        let net_strs = vec![String::from("198.51.100.7/32"), String::from("-")];
+        let mut output = Vec::new();
+        assert_eq!(0, enumerate_networks(&net_strs, EnumerateOptions::default(), input, &CommandContext::default(), &mut output, &mut BufferOutput::new()).unwrap());
+        assert_eq!(
+            "198.51.100.7\n192.0.2.4\n192.0.2.5\n2001:db8::\n2001:db8::1\n",
+            String::from_utf8(output).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_enumerate_reverse() {
+        let reverse = EnumerateOptions {
+            reverse: true,
+            ..Default::default()
+        };
+        let net_strs = vec![String::from("192.0.2.4/30")];
+        let mut output = Vec::new();
+        assert_eq!(0, enumerate_networks(&net_strs, reverse, b"" as &[u8], &CommandContext::default(), &mut output, &mut BufferOutput::new()).unwrap());
+        assert_eq!(
+            "4.2.0.192.in-addr.arpa.\n5.2.0.192.in-addr.arpa.\n6.2.0.192.in-addr.arpa.\n7.2.0.192.in-addr.arpa.\n",
+            String::from_utf8(output).unwrap(),
+        );
+
+        let net_strs = vec![String::from("2001:db8::1a/127")];
+        let mut output = Vec::new();
+        assert_eq!(0, enumerate_networks(&net_strs, reverse, b"" as &[u8], &CommandContext::default(), &mut output, &mut BufferOutput::new()).unwrap());
+        assert_eq!(
+            "a.1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa.\n\
+            b.1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa.\n",
+            String::from_utf8(output).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_enumerate_boundaries() {
+        let boundaries = EnumerateOptions {
+            boundaries: true,
+            ..Default::default()
+        };
+        let net_strs = vec![String::from("192.0.2.4/30"), String::from("198.51.100.7/32")];
+        let mut output = Vec::new();
+        assert_eq!(0, enumerate_networks(&net_strs, boundaries, b"" as &[u8], &CommandContext::default(), &mut output, &mut BufferOutput::new()).unwrap());
+        assert_eq!("192.0.2.4\n192.0.2.7\n198.51.100.7\n", String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn test_enumerate_padded() {
+        let padded = EnumerateOptions {
+            padded: true,
+            ..Default::default()
+        };
+        let net_strs = vec![String::from("192.0.2.8/30")];
+        let mut output = Vec::new();
+        assert_eq!(0, enumerate_networks(&net_strs, padded, b"" as &[u8], &CommandContext::default(), &mut output, &mut BufferOutput::new()).unwrap());
+        let output_str = String::from_utf8(output).unwrap();
+        assert_eq!("192.000.002.008\n192.000.002.009\n192.000.002.010\n192.000.002.011\n", output_str);
+        assert!(output_str.lines().all(|line| line.len() == 15));
+
+        let net_strs = vec![String::from("2001:db8::a/127")];
+        let mut output = Vec::new();
+        assert_eq!(0, enumerate_networks(&net_strs, padded, b"" as &[u8], &CommandContext::default(), &mut output, &mut BufferOutput::new()).unwrap());
+        assert_eq!(
+            "2001:0db8:0000:0000:0000:0000:0000:000a\n2001:0db8:0000:0000:0000:0000:0000:000b\n",
+            String::from_utf8(output).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_enumerate_separator() {
+        let comma = EnumerateOptions {
+            separator: Some(","),
+            ..Default::default()
+        };
+        let net_strs = vec![String::from("192.0.2.4/30")];
+        let mut output = Vec::new();
+        assert_eq!(0, enumerate_networks(&net_strs, comma, b"" as &[u8], &CommandContext::default(), &mut output, &mut BufferOutput::new()).unwrap());
+        assert_eq!("192.0.2.4,192.0.2.5,192.0.2.6,192.0.2.7\n", String::from_utf8(output).unwrap());
+
+        // the separator also goes between the addresses of consecutive networks
+        let space = EnumerateOptions {
+            separator: Some(" "),
+            boundaries: true,
+            ..Default::default()
+        };
+        let net_strs = vec![String::from("192.0.2.4/30"), String::from("198.51.100.7/32")];
+        let mut output = Vec::new();
+        assert_eq!(0, enumerate_networks(&net_strs, space, b"" as &[u8], &CommandContext::default(), &mut output, &mut BufferOutput::new()).unwrap());
+        assert_eq!("192.0.2.4 192.0.2.7 198.51.100.7\n", String::from_utf8(output).unwrap());
+
+        // nothing is output if there are no addresses
+        let mut output = Vec::new();
+        assert_eq!(1, enumerate_networks(&[String::from("nonsense")], comma, b"" as &[u8], &CommandContext::default(), &mut output, &mut BufferOutput::new()).unwrap());
+        assert_eq!("", String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn test_enumerate_too_large() {
+        let net_strs = vec![String::from("2001:db8::/32"), String::from("192.0.2.4/31")];
+        let mut output = Vec::new();
+        let mut stderr = BufferOutput::new();
+        assert_eq!(1, enumerate_networks(&net_strs, EnumerateOptions::default(), b"" as &[u8], &CommandContext::default(), &mut output, &mut stderr).unwrap());
+        assert_eq!("192.0.2.4\n192.0.2.5\n", String::from_utf8(output).unwrap());
+        assert_eq!("refusing to enumerate 2001:db8::/32, which has more than 2^32 addresses\n", stderr.text());
+
+        // the boundaries of a large network are output nonetheless
+        let boundaries = EnumerateOptions {
+            boundaries: true,
+            ..Default::default()
+        };
+        let mut output = Vec::new();
+        assert_eq!(0, enumerate_networks(&net_strs[..1], boundaries, b"" as &[u8], &CommandContext::default(), &mut output, &mut BufferOutput::new()).unwrap());
+        assert_eq!("2001:db8::\n2001:db8:ffff:ffff:ffff:ffff:ffff:ffff\n", String::from_utf8(output).unwrap());
+    }
 }
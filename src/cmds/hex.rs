@@ -0,0 +1,200 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[cfg(feature = "num-bigint")]
+use num_bigint::BigUint;
+
+use crate::run::usage;
+use crate::addr::{IpAddress, Ipv4Address, Ipv6Address};
+use crate::cmds::{CommandContext, CommandResult, ErrorKind, ParsedIpAddress, parse_addr_as};
+use crate::output::Output;
+
+
+/// An error that occurs when attempting to parse an IP address from a hexadecimal string.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseHexAddressError {
+    /// The string contains an odd number of hexadecimal digits. The contained value is the number
+    /// of digits.
+    OddLength(usize),
+
+    /// The string contains a character that is not a hexadecimal digit. The first value is the
+    /// index of the character and the second value is the character itself.
+    InvalidDigit(usize, char),
+
+    /// The number of bytes does not correspond to an IP address type. The contained value is the
+    /// number of bytes.
+    UnsupportedLength(usize),
+}
+impl fmt::Display for ParseHexAddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseHexAddressError::OddLength(digits)
+                => write!(f, "hexadecimal string has an odd number of digits ({})", digits),
+            ParseHexAddressError::InvalidDigit(index, c)
+                => write!(f, "character {:?} at index {} is not a hexadecimal digit", c, index),
+            ParseHexAddressError::UnsupportedLength(bytes)
+                => write!(f, "{} bytes do not form an IP address; expected 4 (IPv4) or 16 (IPv6)", bytes),
+        }
+    }
+}
+impl Error for ParseHexAddressError {
+}
+
+
+pub fn hex(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
+    // ripcalc --hex HEXADDRESS...
+    if args.len() < 3 {
+        return usage(ctx, stderr);
+    }
+
+    for hex_str in &args[2..] {
+        let write_res = match parse_hex_address(hex_str) {
+            Ok(ParsedIpAddress::Ipv4(addr)) => writeln!(stdout, "{}", addr),
+            Ok(ParsedIpAddress::Ipv6(addr)) => writeln!(stdout, "{}", addr),
+            Err(e) => {
+                return ctx.fail(stderr, ErrorKind::Address, format_args!("failed to parse hexadecimal address {:?}: {}", hex_str, e), 1);
+            },
+        };
+        if let Err(e) = write_res {
+            return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+        }
+    }
+
+    CommandResult::Ok
+}
+
+pub fn hex_out(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
+    // ripcalc --hex-out IPADDRESS...
+    if args.len() < 3 {
+        return usage(ctx, stderr);
+    }
+
+    for addr_str in &args[2..] {
+        let bytes = match parse_addr_as(addr_str, ctx.family) {
+            Ok(ParsedIpAddress::Ipv4(addr)) => addr.to_bytes(),
+            Ok(ParsedIpAddress::Ipv6(addr)) => addr.to_bytes(),
+            Err(e) => {
+                return ctx.fail(stderr, ErrorKind::from(&e), format_args!("failed to parse address {:?}: {}", addr_str, e), 1);
+            },
+        };
+        if let Err(e) = output_hex_and_integer(stdout, &bytes) {
+            return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+        }
+    }
+
+    CommandResult::Ok
+}
+
+#[cfg(feature = "num-bigint")]
+fn output_hex_and_integer(out: &mut dyn Output, bytes: &[u8]) -> io::Result<()> {
+    writeln!(out, "{} {}", format_hex_bytes(bytes), bytes_to_integer(bytes))
+}
+
+#[cfg(not(feature = "num-bigint"))]
+fn output_hex_and_integer(out: &mut dyn Output, bytes: &[u8]) -> io::Result<()> {
+    writeln!(out, "{}", format_hex_bytes(bytes))
+}
+
+/// Formats the given bytes as a string of lowercase hexadecimal digits, two digits per byte.
+pub fn format_hex_bytes(bytes: &[u8]) -> String {
+    bytes.iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Interprets the given bytes as an unsigned big-endian integer.
+#[cfg(feature = "num-bigint")]
+pub fn bytes_to_integer(bytes: &[u8]) -> BigUint {
+    bytes.iter()
+        .fold(BigUint::from(0u32), |acc, b| (acc << 8) | BigUint::from(*b))
+}
+
+/// Converts a string of hexadecimal digits into bytes, two digits per byte.
+pub fn parse_hex_bytes(hex_str: &str) -> Result<Vec<u8>, ParseHexAddressError> {
+    let digits: Vec<u8> = hex_str.chars()
+        .enumerate()
+        .map(|(i, c)| c.to_digit(16)
+            .map(|d| u8::try_from(d).unwrap())
+            .ok_or(ParseHexAddressError::InvalidDigit(i, c))
+        )
+        .collect::<Result<_, _>>()?;
+    let pairs = digits.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return Err(ParseHexAddressError::OddLength(digits.len()));
+    }
+
+    Ok(pairs
+        .map(|pair| (pair[0] << 4) | pair[1])
+        .collect())
+}
+
+/// Parses an IP address from its bytes given as a string of hexadecimal digits. Four bytes are
+/// interpreted as an IPv4 address and sixteen bytes as an IPv6 address.
+pub fn parse_hex_address(hex_str: &str) -> Result<ParsedIpAddress, ParseHexAddressError> {
+    let bytes = parse_hex_bytes(hex_str)?;
+    if let Some(addr) = Ipv4Address::from_bytes(&bytes) {
+        Ok(ParsedIpAddress::Ipv4(addr))
+    } else if let Some(addr) = Ipv6Address::from_bytes(&bytes) {
+        Ok(ParsedIpAddress::Ipv6(addr))
+    } else {
+        Err(ParseHexAddressError::UnsupportedLength(bytes.len()))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(feature = "num-bigint")]
+    use crate::net::test::parse_biguint;
+    use crate::net::test::{parse_ipv4, parse_ipv6};
+
+    #[test]
+    fn test_parse_hex_address() {
+        assert_eq!(Ok(ParsedIpAddress::Ipv4(parse_ipv4("192.0.2.1"))), parse_hex_address("c0000201"));
+        assert_eq!(Ok(ParsedIpAddress::Ipv4(parse_ipv4("255.255.255.255"))), parse_hex_address("FFFFFFFF"));
+        assert_eq!(
+            Ok(ParsedIpAddress::Ipv6(parse_ipv6("2001:db8::1"))),
+            parse_hex_address("20010db8000000000000000000000001"),
+        );
+    }
+
+    #[test]
+    fn test_parse_hex_address_errors() {
+        assert_eq!(Err(ParseHexAddressError::OddLength(7)), parse_hex_address("c000020"));
+        assert_eq!(Err(ParseHexAddressError::InvalidDigit(2, 'x')), parse_hex_address("c0x00201"));
+        assert_eq!(Err(ParseHexAddressError::UnsupportedLength(0)), parse_hex_address(""));
+        assert_eq!(Err(ParseHexAddressError::UnsupportedLength(3)), parse_hex_address("c00002"));
+        assert_eq!(Err(ParseHexAddressError::UnsupportedLength(5)), parse_hex_address("c000020100"));
+    }
+
+    #[test]
+    fn test_format_hex_bytes() {
+        assert_eq!("c0000201", format_hex_bytes(&parse_ipv4("192.0.2.1").to_bytes()));
+        assert_eq!("00000000", format_hex_bytes(&parse_ipv4("0.0.0.0").to_bytes()));
+        assert_eq!(
+            "20010db8000000000000000000000001",
+            format_hex_bytes(&parse_ipv6("2001:db8::1").to_bytes()),
+        );
+        assert_eq!(
+            "ffffffffffffffffffffffffffffffff",
+            format_hex_bytes(&parse_ipv6("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff").to_bytes()),
+        );
+    }
+
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn test_bytes_to_integer() {
+        assert_eq!(parse_biguint("3221225985"), bytes_to_integer(&parse_ipv4("192.0.2.1").to_bytes()));
+        assert_eq!(parse_biguint("4294967295"), bytes_to_integer(&parse_ipv4("255.255.255.255").to_bytes()));
+        assert_eq!(
+            parse_biguint("42540766411282592856903984951653826561"),
+            bytes_to_integer(&parse_ipv6("2001:db8::1").to_bytes()),
+        );
+        assert_eq!(
+            parse_biguint("340282366920938463463374607431768211455"),
+            bytes_to_integer(&parse_ipv6("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff").to_bytes()),
+        );
+    }
+}
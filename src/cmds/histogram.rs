@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+use std::io::BufRead;
+
+use crate::run::usage;
+use crate::addr::IpAddress;
+use crate::cmds::{
+    CommandContext, CommandResult, ErrorKind, NetworkSpecs, mixed_specs_message, parse_netspec_as,
+    parse_same_family_netspecs, read_numbered_netspec_lines, split_netspec_list,
+};
+use crate::net::IpNetwork;
+use crate::output::Output;
+
+
+/// The number of networks per CIDR prefix length in a list of networks.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PrefixHistogram {
+    /// The number of networks with each CIDR prefix length.
+    pub prefix_counts: BTreeMap<usize, usize>,
+
+    /// The number of networks with mixed subnet masks, which have no CIDR prefix length.
+    pub mixed_count: usize,
+}
+impl PrefixHistogram {
+    /// Tallies the CIDR prefix lengths of the given networks.
+    pub fn new<A: IpAddress>(nets: &[IpNetwork<A>]) -> PrefixHistogram {
+        let mut histogram = PrefixHistogram::default();
+        for net in nets {
+            match net.cidr_prefix() {
+                Some(prefix) => *histogram.prefix_counts.entry(prefix).or_insert(0) += 1,
+                None => histogram.mixed_count += 1,
+            }
+        }
+        histogram
+    }
+
+    /// Returns the lines to output: one line per CIDR prefix length in ascending order, followed
+    /// by one line for networks with mixed subnet masks, if there are any.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self.prefix_counts.iter()
+            .map(|(prefix, count)| format!("/{}: {}", prefix, count))
+            .collect();
+        if self.mixed_count > 0 {
+            lines.push(format!("mixed: {}", self.mixed_count));
+        }
+        lines
+    }
+}
+
+
+pub fn histogram<R: BufRead>(
+    args: &[String], ctx: &CommandContext, mut input: R, stdout: &mut dyn Output, stderr: &mut dyn Output,
+) -> CommandResult {
+    // ripcalc --histogram IPADDRESS/SUBNET...
+    if args.len() < 3 {
+        return usage(ctx, stderr);
+    }
+
+    let mut spec_strs = Vec::with_capacity(args.len() - 2);
+    for arg in &args[2..] {
+        if arg == "-" {
+            // read the specifications from stdin
+            let lines = match read_numbered_netspec_lines(&mut input) {
+                Ok(ls) => ls,
+                Err(e) => {
+                    return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to read networks from stdin: {}", e), 1);
+                },
+            };
+            for line in lines {
+                // check each line here so that a faulty one can be pointed out
+                if let Err(e) = line.parse_spec(|spec| parse_netspec_as(spec, ctx.family)) {
+                    return ctx.fail(stderr, ErrorKind::from(&e), format_args!("parsing error: {}", e), 1);
+                }
+                spec_strs.push(line.spec);
+            }
+        } else {
+            spec_strs.extend(split_netspec_list(arg).map(String::from));
+        }
+    }
+
+    let histogram = match parse_same_family_netspecs(&spec_strs, ctx.family) {
+        Ok(NetworkSpecs::Nothing) => PrefixHistogram::default(),
+        Ok(NetworkSpecs::MixedSpecs(index, spec)) => {
+            return ctx.fail(stderr, ErrorKind::Input, format_args!("mixing IPv4 and IPv6 is not supported: {}", mixed_specs_message(index, &spec)), 1);
+        },
+        Ok(NetworkSpecs::Ipv4(specs)) => {
+            let nets: Vec<_> = specs.iter().map(|(_addr, net)| *net).collect();
+            PrefixHistogram::new(&nets)
+        },
+        Ok(NetworkSpecs::Ipv6(specs)) => {
+            let nets: Vec<_> = specs.iter().map(|(_addr, net)| *net).collect();
+            PrefixHistogram::new(&nets)
+        },
+        Err(e) => {
+            return ctx.fail(stderr, ErrorKind::from(&e), format_args!("parsing error: {}", e), 1);
+        },
+    };
+
+    for line in histogram.lines() {
+        if let Err(e) = writeln!(stdout, "{}", line) {
+            return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+        }
+    }
+    CommandResult::Ok
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::addr::Ipv4Address;
+    use crate::net::test::{parse_ipv4net, parse_ipv4netm, parse_ipv6net};
+
+    #[test]
+    fn test_prefix_histogram() {
+        let nets = vec![
+            parse_ipv4net("192.0.2.0", 24),
+            parse_ipv4net("198.51.100.0", 22),
+            parse_ipv4net("203.0.113.0", 24),
+            parse_ipv4netm("10.0.0.0", "255.0.255.0"),
+            parse_ipv4net("192.0.2.0", 24),
+            parse_ipv4net("10.0.0.0", 8),
+        ];
+        let histogram = PrefixHistogram::new(&nets);
+        assert_eq!(Some(&3), histogram.prefix_counts.get(&24));
+        assert_eq!(Some(&1), histogram.prefix_counts.get(&22));
+        assert_eq!(Some(&1), histogram.prefix_counts.get(&8));
+        assert_eq!(None, histogram.prefix_counts.get(&16));
+        assert_eq!(1, histogram.mixed_count);
+        assert_eq!(vec!["/8: 1", "/22: 1", "/24: 3", "mixed: 1"], histogram.lines());
+
+        let histogram = PrefixHistogram::new(&[parse_ipv6net("2001:db8::", 32), parse_ipv6net("2001:db8::", 64)]);
+        assert_eq!(vec!["/32: 1", "/64: 1"], histogram.lines());
+
+        let histogram = PrefixHistogram::new::<Ipv4Address>(&[]);
+        assert!(histogram.lines().is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+use std::convert::TryFrom;
+use std::io;
+
+use crate::run::usage;
+use crate::addr::IpAddress;
+use crate::cmds::{
+    CommandContext, CommandResult, ErrorKind, NetworkSpecs, mixed_specs_message, parse_same_family_netspecs,
+};
+use crate::cmds::minimize::minimize_subnets;
+use crate::net::IpNetwork;
+use crate::output::Output;
+
+
+/// The maximum number of characters in a map is 2^MAX_MAP_BITS.
+const MAX_MAP_BITS: usize = 6;
+
+
+/// The allocation state of a block of addresses.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BlockState {
+    /// None of the addresses in the block are allocated.
+    Free,
+
+    /// Some, but not all, of the addresses in the block are allocated.
+    Partial,
+
+    /// All addresses in the block are allocated.
+    Allocated,
+}
+impl BlockState {
+    /// The character representing this state in a map.
+    pub fn map_char(&self) -> char {
+        match self {
+            BlockState::Free => '.',
+            BlockState::Partial => '+',
+            BlockState::Allocated => '#',
+        }
+    }
+}
+
+
+pub fn map(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
+    // ripcalc --map IPADDRESS/SUBNET IPADDRESS/SUBNET...
+    if args.len() < 3 {
+        return usage(ctx, stderr);
+    }
+
+    let output_res = match parse_same_family_netspecs(&args[2..], ctx.family) {
+        Err(e) => {
+            return ctx.fail(stderr, ErrorKind::from(&e), format_args!("failed to parse network: {}", e), 1);
+        },
+        Ok(NetworkSpecs::Nothing) => {
+            return usage(ctx, stderr);
+        },
+        Ok(NetworkSpecs::MixedSpecs(index, spec)) => {
+            return ctx.fail(stderr, ErrorKind::Input, format_args!("mixing IPv4 and IPv6 is not supported: {}", mixed_specs_message(index, &spec)), 1);
+        },
+        Ok(NetworkSpecs::Ipv4(specs)) => output_map(stdout, stderr, specs.iter().map(|(_addr, net)| *net).collect()),
+        Ok(NetworkSpecs::Ipv6(specs)) => output_map(stdout, stderr, specs.iter().map(|(_addr, net)| *net).collect()),
+    };
+    if let Err(e) = output_res {
+        return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+    }
+    CommandResult::Ok
+}
+
+fn output_map<A: IpAddress>(stdout: &mut dyn Output, stderr: &mut dyn Output, mut nets: Vec<IpNetwork<A>>) -> io::Result<()> {
+    let children = nets.split_off(1);
+    let parent = nets[0];
+
+    for child in &children {
+        if !parent.intersects(child) {
+            writeln!(stderr, "warning: {} does not overlap with {}", child, parent)?;
+        }
+    }
+
+    let host_bits = usize::try_from(parent.subnet_mask().count_zeros()).unwrap();
+    let block_host_bits = host_bits - host_bits.min(MAX_MAP_BITS);
+    writeln!(stdout, "{} (each character represents {} addresses)", parent, 1u128 << block_host_bits)?;
+    writeln!(stdout, "{}", render_map(parent, &children))?;
+    writeln!(stdout, "{} allocated, {} partially allocated, {} free",
+        BlockState::Allocated.map_char(), BlockState::Partial.map_char(), BlockState::Free.map_char())
+}
+
+/// Divides the parent network into equally-sized blocks and returns the allocation state of each
+/// block with regard to the given child networks. The parent network is divided into at most
+/// 2^MAX_MAP_BITS blocks.
+pub fn block_states<A: IpAddress>(parent: IpNetwork<A>, children: &[IpNetwork<A>]) -> Vec<BlockState> {
+    let net_bits = usize::try_from(parent.subnet_mask().count_ones()).unwrap();
+    let host_bits = usize::try_from(parent.subnet_mask().count_zeros()).unwrap();
+    let block_subnet_mask = parent.extended_subnet_mask(net_bits + host_bits.min(MAX_MAP_BITS))
+        .expect("blocks are not larger than the parent network");
+
+    // merge adjacent children so that blocks covered by multiple children count as allocated
+    let minimized_children = minimize_subnets(children.to_vec());
+
+    parent.subnets(block_subnet_mask)
+        .map(|block| {
+            if minimized_children.iter().any(|child| child.is_superset_of(&block)) {
+                BlockState::Allocated
+            } else if minimized_children.iter().any(|child| child.intersects(&block)) {
+                BlockState::Partial
+            } else {
+                BlockState::Free
+            }
+        })
+        .collect()
+}
+
+/// Renders a bar showing which parts of the parent network are allocated to the given child
+/// networks.
+pub fn render_map<A: IpAddress>(parent: IpNetwork<A>, children: &[IpNetwork<A>]) -> String {
+    let mut bar = String::from("[");
+    bar.extend(block_states(parent, children).iter().map(|s| s.map_char()));
+    bar.push(']');
+    bar
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::net::test::{parse_ipv4net, parse_ipv6net};
+
+    #[test]
+    fn test_render_map() {
+        let parent = parse_ipv4net("192.0.2.0", 24);
+        assert_eq!(
+            format!("[{}{}]", "#".repeat(32), ".".repeat(32)),
+            render_map(parent, &[parse_ipv4net("192.0.2.0", 25)]),
+        );
+
+        // adjacent children fill a block together; a lone /32 fills part of one
+        assert_eq!(
+            format!("[{}{}{}+]", ".".repeat(32), "#".repeat(16), ".".repeat(15)),
+            render_map(parent, &[
+                parse_ipv4net("192.0.2.128", 27),
+                parse_ipv4net("192.0.2.160", 27),
+                parse_ipv4net("192.0.2.255", 32),
+            ]),
+        );
+
+        // small networks have one character per address
+        assert_eq!(
+            "[.#..]",
+            render_map(parse_ipv4net("192.0.2.0", 30), &[parse_ipv4net("192.0.2.1", 32)]),
+        );
+
+        assert_eq!(
+            format!("[{}{}]", ".".repeat(32), "#".repeat(32)),
+            render_map(parse_ipv6net("2001:db8::", 32), &[parse_ipv6net("2001:db8:8000::", 33)]),
+        );
+    }
+}
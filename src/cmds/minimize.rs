@@ -1,60 +1,230 @@
 use std::collections::HashSet;
+use std::io::{self, BufRead, Write};
 
-use crate::usage;
+use crate::run::usage;
 use crate::addr::IpAddress;
-use crate::cmds::{NetworkSpecs, parse_same_family_netspecs};
-use crate::net::IpNetwork;
+use crate::cmds::{
+    CommandContext, CommandResult, ErrorKind, NetworkSpecs, mixed_specs_message, parse_netspec_as,
+    parse_same_family_netspecs, read_numbered_netspec_lines, split_netspec_list, write_json_string_array,
+};
+use crate::net::{IpNetwork, MaskFormat};
+use crate::output::Output;
+use crate::tagged::{TaggedNetwork, find_tag, split_tag};
 
 
-pub fn minimize(args: &[String]) -> i32 {
+pub fn minimize<R: BufRead>(
+    args: &[String], ctx: &CommandContext, mut input: R, stdout: &mut dyn Output, stderr: &mut dyn Output,
+) -> CommandResult {
     // ripcalc --minimize IPADDRESS/SUBNET...
     if args.len() < 3 {
-        usage();
-        return 1;
+        return usage(ctx, stderr);
     }
 
-    match parse_same_family_netspecs(&args[2..]) {
+    let mut merge_only = false;
+    let mut explain = false;
+    let mut json = false;
+    let mut sort_routes = false;
+    let mut mask_format = MaskFormat::default();
+    let mut spec_strs = Vec::with_capacity(args.len() - 2);
+    for arg in &args[2..] {
+        if arg == "--merge-only" {
+            merge_only = true;
+        } else if arg == "--explain" {
+            explain = true;
+        } else if arg == "--json" {
+            json = true;
+        } else if arg == "--sort-routes" {
+            sort_routes = true;
+        } else if let Some(format_name) = arg.strip_prefix("--mask-format=") {
+            mask_format = match MaskFormat::from_name(format_name) {
+                Some(mf) => mf,
+                None => {
+                    return ctx.fail(stderr, ErrorKind::Usage, format_args!("unknown mask format {:?}; expected cidr, slash or space", format_name), 1);
+                },
+            };
+        } else if arg == "-" {
+            // read the specifications from stdin
+            let lines = match read_numbered_netspec_lines(&mut input) {
+                Ok(ls) => ls,
+                Err(e) => {
+                    return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to read networks from stdin: {}", e), 1);
+                },
+            };
+            for line in lines {
+                // check each line here so that a faulty one can be pointed out
+                if let Err(e) = line.parse_spec(|spec| parse_netspec_as(split_tag(spec).0, ctx.family)) {
+                    return ctx.fail(stderr, ErrorKind::from(&e), format_args!("parsing error: {}", e), 1);
+                }
+                spec_strs.push(line.spec);
+            }
+        } else {
+            spec_strs.extend(split_netspec_list(arg).map(String::from));
+        }
+    }
+
+    if explain && json {
+        return ctx.fail(stderr, ErrorKind::Usage, format_args!("--explain cannot be combined with --json"), 1);
+    }
+
+    let options = MinimizeOptions {
+        merge_only,
+        explain,
+        json,
+        sort_routes,
+        mask_format,
+    };
+    let (net_strs, tags): (Vec<&str>, Vec<Option<String>>) = spec_strs.iter()
+        .map(|spec_str| {
+            let (net_str, tag) = split_tag(spec_str);
+            (net_str, tag.map(String::from))
+        })
+        .unzip();
+    let output_res = match parse_same_family_netspecs(&net_strs, ctx.family) {
         Ok(NetworkSpecs::Nothing) => {
-            0
+            if json {
+                write_json_string_array::<_, String>(stdout, &[])
+            } else {
+                Ok(())
+            }
         },
-        Ok(NetworkSpecs::MixedSpecs) => {
-            eprintln!("mixing IPv4 and IPv6 is not supported");
-            1
+        Ok(NetworkSpecs::MixedSpecs(index, spec)) => {
+            return ctx.fail(stderr, ErrorKind::Input, format_args!("mixing IPv4 and IPv6 is not supported: {}", mixed_specs_message(index, &spec)), 1);
         },
         Ok(NetworkSpecs::Ipv4(addrs_subnets)) => {
             let subnets = addrs_subnets.iter()
-                .map(|(_a, s)| *s)
+                .zip(tags)
+                .map(|((_a, s), tag)| TaggedNetwork::new(*s, tag))
                 .collect();
-            let minimized = minimize_subnets(subnets);
-            for min_net in minimized {
-                println!("{}", min_net);
-            }
-            0
+            output_minimized(stdout, subnets, options)
         },
         Ok(NetworkSpecs::Ipv6(addrs_subnets)) => {
             let subnets = addrs_subnets.iter()
-                .map(|(_a, s)| *s)
+                .zip(tags)
+                .map(|((_a, s), tag)| TaggedNetwork::new(*s, tag))
                 .collect();
-            let minimized = minimize_subnets(subnets);
-            for min_net in minimized {
-                println!("{}", min_net);
-            }
-            0
+            output_minimized(stdout, subnets, options)
         },
         Err(e) => {
-            eprintln!("parsing error: {}", e);
-            1
+            return ctx.fail(stderr, ErrorKind::from(&e), format_args!("parsing error: {}", e), 1);
         },
+    };
+    if let Err(e) = output_res {
+        return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+    }
+    CommandResult::Ok
+}
+
+/// Options for the output of minimized networks.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+struct MinimizeOptions {
+    /// Only merge adjacent networks; do not remove networks contained within other networks.
+    merge_only: bool,
+
+    /// Output the input networks subsumed by each output network.
+    explain: bool,
+
+    /// Output the networks as a JSON array of strings.
+    json: bool,
+
+    /// Sort the networks in routing table order (see `IpNetwork::route_order`) instead of by base
+    /// address.
+    sort_routes: bool,
+
+    /// The form in which the subnet masks of the networks are output.
+    mask_format: MaskFormat,
+}
+
+fn output_minimized<A: IpAddress, W: Write + ?Sized>(out: &mut W, tagged_subnets: Vec<TaggedNetwork<A>>, options: MinimizeOptions) -> io::Result<()> {
+    let subnets: Vec<IpNetwork<A>> = tagged_subnets.iter()
+        .map(|tagged| tagged.net)
+        .collect();
+    let with_tag = |net: &IpNetwork<A>| TaggedNetwork::new(*net, find_tag(&tagged_subnets, net).map(String::from));
+
+    let mut explained = if options.merge_only {
+        let merged = merge_adjacent_subnets(subnets.clone());
+        explain_minimization(&subnets, &merged)
+    } else {
+        minimize_subnets_explained(subnets)
+    };
+    if options.sort_routes {
+        explained.sort_by(|a, b| a.network.route_order(&b.network));
     }
+
+    if options.json {
+        let networks: Vec<String> = explained.iter()
+            .map(|min_net| with_tag(&min_net.network).to_string_with_mask(options.mask_format))
+            .collect();
+        return write_json_string_array(out, &networks);
+    }
+
+    for min_net in explained {
+        writeln!(out, "{}", with_tag(&min_net.network).to_string_with_mask(options.mask_format))?;
+        if options.explain {
+            for input in &min_net.subsumed {
+                if input != &min_net.network {
+                    writeln!(out, "  <- {}", with_tag(input).to_string_with_mask(options.mask_format))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A network in the output of a minimization along with the input networks it subsumes.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct MinimizedNetwork<A: IpAddress> {
+    /// The network in the minimized list.
+    pub network: IpNetwork<A>,
+
+    /// The input networks that are contained within `network`, sorted and without duplicates. If
+    /// the network was passed through unchanged, this list only contains the network itself.
+    pub subsumed: Vec<IpNetwork<A>>,
 }
 
 /// Minimizes the list of networks such that duplicate entries and networks that are subnets of
 /// other networks in the list are removed from the list, and adjacent networks are merged if
 /// possible.
 pub fn minimize_subnets<A: IpAddress>(
+    subnets: Vec<IpNetwork<A>>,
+) -> Vec<IpNetwork<A>> {
+    merge_adjacent_subnets(eliminate_subsets(subnets))
+}
+
+/// Minimizes the list of networks like [`minimize_subnets`] and additionally returns, for each
+/// output network, which of the input networks it subsumes.
+pub fn minimize_subnets_explained<A: IpAddress>(
+    subnets: Vec<IpNetwork<A>>,
+) -> Vec<MinimizedNetwork<A>> {
+    let minimized = minimize_subnets(subnets.clone());
+    explain_minimization(&subnets, &minimized)
+}
+
+/// Assigns each input network to the output networks of a minimization that contain it.
+pub fn explain_minimization<A: IpAddress>(
+    inputs: &[IpNetwork<A>],
+    minimized: &[IpNetwork<A>],
+) -> Vec<MinimizedNetwork<A>> {
+    let mut sorted_inputs = inputs.to_vec();
+    sorted_inputs.sort_unstable_by(|a, b| a.numeric_order(b));
+    sorted_inputs.dedup();
+
+    minimized.iter()
+        .map(|min_net| MinimizedNetwork {
+            network: *min_net,
+            subsumed: sorted_inputs.iter()
+                .filter(|input| min_net.is_superset_of(input))
+                .copied()
+                .collect(),
+        })
+        .collect()
+}
+
+/// Removes duplicate entries and networks that are subnets of other networks in the list from the
+/// list of networks.
+pub fn eliminate_subsets<A: IpAddress>(
     mut subnets: Vec<IpNetwork<A>>,
 ) -> Vec<IpNetwork<A>> {
-    subnets.sort_unstable_by_key(|net| (net.base_addr(), net.subnet_mask()));
+    subnets.sort_unstable_by(|a, b| a.numeric_order(b));
 
     let mut filtered_subnets: HashSet<IpNetwork<A>> = HashSet::new();
     filtered_subnets.extend(subnets.iter());
@@ -69,16 +239,29 @@ pub fn minimize_subnets<A: IpAddress>(
         }
     }
 
+    subnets = filtered_subnets.iter()
+        .copied()
+        .collect();
+    subnets.sort_unstable_by(|a, b| a.numeric_order(b));
+    subnets
+}
+
+/// Merges adjacent networks of the same size in the list of networks into larger networks, as long
+/// as possible. Duplicate entries are removed, but networks that are subnets of other networks in
+/// the list are retained.
+pub fn merge_adjacent_subnets<A: IpAddress>(
+    mut subnets: Vec<IpNetwork<A>>,
+) -> Vec<IpNetwork<A>> {
+    // always work on the sorted list so that the merges (and therefore the result) do not depend
+    // on the order of the input
+    subnets.sort_unstable_by(|a, b| a.numeric_order(b));
+    subnets.dedup();
+
     // try joining adjacent same-size subnets
     let mut subnets_merged = true;
     while subnets_merged {
         subnets_merged = false;
 
-        subnets = filtered_subnets.iter()
-            .map(|net| *net)
-            .collect();
-        subnets.sort_unstable_by_key(|net| (net.base_addr(), net.subnet_mask()));
-
         for i in 0..subnets.len() {
             for j in (i+1)..subnets.len() {
                 if subnets[i].subnet_mask() != subnets[j].subnet_mask() {
@@ -86,11 +269,9 @@ pub fn minimize_subnets<A: IpAddress>(
                     continue;
                 }
 
-                if let Some(last_ip_plus_one) = subnets[i].next_subnet_base_addr() {
-                    if last_ip_plus_one != subnets[j].base_addr() {
-                        // not adjacent
-                        continue;
-                    }
+                if !subnets[i].is_adjacent_to(&subnets[j]) {
+                    // not adjacent
+                    continue;
                 }
 
                 // adjacent!
@@ -114,9 +295,11 @@ pub fn minimize_subnets<A: IpAddress>(
                 assert!(new_subnet.is_superset_of(&subnets[j]));
 
                 // replace the lower subnets with the upper subnet
-                filtered_subnets.remove(&subnets[i]);
-                filtered_subnets.remove(&subnets[j]);
-                filtered_subnets.insert(new_subnet);
+                // (which might already be in the list if subsets have not been eliminated)
+                subnets.remove(j);
+                subnets[i] = new_subnet;
+                subnets.sort_unstable_by(|a, b| a.numeric_order(b));
+                subnets.dedup();
 
                 subnets_merged = true;
                 break;
@@ -128,16 +311,13 @@ pub fn minimize_subnets<A: IpAddress>(
         }
     }
 
-    subnets = filtered_subnets.iter()
-        .map(|net| *net)
-        .collect();
-    subnets.sort_unstable_by_key(|net| (net.base_addr(), net.subnet_mask()));
     subnets
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::addr::{Ipv4Address, Ipv6Address};
     use crate::net::test::{parse_ipv4net, parse_ipv4netm, parse_ipv6net, parse_ipv6netm};
 
     #[test]
@@ -176,6 +356,86 @@ mod test {
         assert_eq!(parse_ipv6net("2001:db8:4::", 48), minimized[2]);
     }
 
+    #[test]
+    fn test_minimize_order_independent() {
+        let minimize_us = vec![
+            parse_ipv4net("192.0.2.0", 26),
+            parse_ipv4net("192.0.2.64", 26),
+            parse_ipv4net("192.0.2.128", 25),
+            parse_ipv4net("192.0.2.0", 25),
+            parse_ipv4net("192.0.3.0", 24),
+            parse_ipv4net("198.51.100.0", 25),
+            parse_ipv4net("198.51.100.192", 26),
+            parse_ipv4net("198.51.100.128", 26),
+        ];
+        let mut shuffled = minimize_us.clone();
+        shuffled.reverse();
+        shuffled.swap(1, 5);
+        shuffled.swap(2, 6);
+
+        assert_eq!(minimize_subnets(minimize_us.clone()), minimize_subnets(shuffled.clone()));
+        assert_eq!(
+            vec![parse_ipv4net("192.0.2.0", 23), parse_ipv4net("198.51.100.0", 24)],
+            minimize_subnets(shuffled.clone()),
+        );
+
+        assert_eq!(merge_adjacent_subnets(minimize_us), merge_adjacent_subnets(shuffled));
+    }
+
+    #[test]
+    fn test_minimize_ipv6_numeric_order() {
+        let merge_us = vec![
+            parse_ipv6net("2001:db8::", 64),
+            parse_ipv6net("2001:db8::", 128),
+            parse_ipv6net("2001:db8::1", 128),
+            parse_ipv6net("2001:db8::ffff", 128),
+            parse_ipv6net("2001:db8::1:0", 128),
+            parse_ipv6net("2001:db8:0:1::", 64),
+            parse_ipv6net("2001:db8:1::", 64),
+        ];
+        let mut shuffled = merge_us.clone();
+        shuffled.reverse();
+        shuffled.swap(0, 3);
+        shuffled.swap(2, 5);
+
+        // host routes follow their covering aggregate in numeric order
+        let merged = merge_adjacent_subnets(shuffled.clone());
+        assert_eq!(
+            vec![
+                parse_ipv6net("2001:db8::", 63),
+                parse_ipv6net("2001:db8::", 127),
+                parse_ipv6net("2001:db8::ffff", 128),
+                parse_ipv6net("2001:db8::1:0", 128),
+                parse_ipv6net("2001:db8:1::", 64),
+            ],
+            merged,
+        );
+        assert_eq!(merge_adjacent_subnets(merge_us.clone()), merged);
+
+        assert_eq!(
+            vec![parse_ipv6net("2001:db8::", 63), parse_ipv6net("2001:db8:1::", 64)],
+            minimize_subnets(shuffled),
+        );
+    }
+
+    #[test]
+    fn test_minimize_comma_separated() {
+        fn minimize_args(args: &[&str]) -> Vec<IpNetwork<Ipv4Address>> {
+            let spec_strs: Vec<&str> = args.iter()
+                .flat_map(|arg| split_netspec_list(arg))
+                .collect();
+            match parse_same_family_netspecs(&spec_strs, None) {
+                Ok(NetworkSpecs::Ipv4(specs)) => minimize_subnets(specs.iter().map(|(_a, s)| *s).collect()),
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        let separate = minimize_args(&["192.0.2.0/25", "192.0.2.128/25", "198.51.100.0/24"]);
+        assert_eq!(separate, minimize_args(&["192.0.2.0/25,192.0.2.128/25,198.51.100.0/24"]));
+        assert_eq!(separate, minimize_args(&["192.0.2.0/25, 192.0.2.128/25,", "198.51.100.0/24"]));
+        assert_eq!(vec![parse_ipv4net("192.0.2.0", 24), parse_ipv4net("198.51.100.0", 24)], separate);
+    }
+
     #[test]
     fn test_minimize_mixed() {
         let minimize_us = vec![
@@ -201,4 +461,183 @@ mod test {
         assert_eq!(parse_ipv6netm("2001:db8::2", "ffff:ffff::fffe"), minimized[1]);
         assert_eq!(parse_ipv6netm("2001:db8::4", "ffff:ffff::ffff"), minimized[2]);
     }
+
+    #[test]
+    fn test_minimize_top_of_address_space() {
+        let minimize_us = vec![
+            parse_ipv4net("255.255.255.255", 32),
+            parse_ipv4net("0.0.0.0", 32),
+            parse_ipv4net("255.255.255.254", 32),
+            parse_ipv4net("255.255.255.252", 31),
+        ];
+        let minimized = minimize_subnets(minimize_us);
+        assert_eq!(2, minimized.len());
+        assert_eq!(parse_ipv4net("0.0.0.0", 32), minimized[0]);
+        assert_eq!(parse_ipv4net("255.255.255.252", 30), minimized[1]);
+
+        let minimize_us = vec![
+            parse_ipv6net("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff", 128),
+            parse_ipv6net("::", 128),
+            parse_ipv6net("ffff:ffff:ffff:ffff:ffff:ffff:ffff:fffe", 128),
+        ];
+        let minimized = minimize_subnets(minimize_us);
+        assert_eq!(2, minimized.len());
+        assert_eq!(parse_ipv6net("::", 128), minimized[0]);
+        assert_eq!(parse_ipv6net("ffff:ffff:ffff:ffff:ffff:ffff:ffff:fffe", 127), minimized[1]);
+    }
+
+    #[test]
+    fn test_minimize_merge_only() {
+        let minimize_us = vec![
+            parse_ipv4net("10.0.0.0", 8),
+            parse_ipv4net("10.1.0.0", 16),
+            parse_ipv4net("192.0.2.0", 25),
+            parse_ipv4net("192.0.2.128", 25),
+        ];
+
+        // the more-specific route is removed by a full minimization...
+        let minimized = minimize_subnets(minimize_us.clone());
+        assert_eq!(2, minimized.len());
+        assert_eq!(parse_ipv4net("10.0.0.0", 8), minimized[0]);
+        assert_eq!(parse_ipv4net("192.0.2.0", 24), minimized[1]);
+
+        // ... but retained when only merging
+        let merged = merge_adjacent_subnets(minimize_us);
+        assert_eq!(3, merged.len());
+        assert_eq!(parse_ipv4net("10.0.0.0", 8), merged[0]);
+        assert_eq!(parse_ipv4net("10.1.0.0", 16), merged[1]);
+        assert_eq!(parse_ipv4net("192.0.2.0", 24), merged[2]);
+
+        let merged = merge_adjacent_subnets(vec![
+            parse_ipv6net("2001:db8::", 32),
+            parse_ipv6net("2001:db8:1::", 48),
+            parse_ipv6net("2001:db8:1::", 48),
+            parse_ipv6net("2001:db9::", 32),
+        ]);
+        assert_eq!(2, merged.len());
+        assert_eq!(parse_ipv6net("2001:db8::", 31), merged[0]);
+        assert_eq!(parse_ipv6net("2001:db8:1::", 48), merged[1]);
+    }
+
+    #[test]
+    fn test_minimize_explained() {
+        let minimize_us = vec![
+            parse_ipv4net("128.131.0.0", 16),
+            parse_ipv4net("128.130.0.0", 16),
+            parse_ipv4net("128.130.0.0", 16),
+            parse_ipv4net("192.35.240.0", 22),
+            parse_ipv4net("192.35.241.0", 24),
+            parse_ipv4net("193.170.72.0", 21),
+        ];
+        let explained = minimize_subnets_explained(minimize_us);
+        assert_eq!(3, explained.len());
+
+        assert_eq!(parse_ipv4net("128.130.0.0", 15), explained[0].network);
+        assert_eq!(2, explained[0].subsumed.len());
+        assert_eq!(parse_ipv4net("128.130.0.0", 16), explained[0].subsumed[0]);
+        assert_eq!(parse_ipv4net("128.131.0.0", 16), explained[0].subsumed[1]);
+
+        assert_eq!(parse_ipv4net("192.35.240.0", 22), explained[1].network);
+        assert_eq!(2, explained[1].subsumed.len());
+        assert_eq!(parse_ipv4net("192.35.240.0", 22), explained[1].subsumed[0]);
+        assert_eq!(parse_ipv4net("192.35.241.0", 24), explained[1].subsumed[1]);
+
+        assert_eq!(parse_ipv4net("193.170.72.0", 21), explained[2].network);
+        assert_eq!(1, explained[2].subsumed.len());
+        assert_eq!(parse_ipv4net("193.170.72.0", 21), explained[2].subsumed[0]);
+    }
+
+    #[test]
+    fn test_minimize_json() {
+        let minimize_us = vec![
+            parse_ipv4net("192.0.2.0", 25),
+            parse_ipv4net("192.0.2.128", 25),
+            parse_ipv4netm("198.51.0.7", "255.255.0.255"),
+        ];
+        let options = MinimizeOptions {
+            json: true,
+            ..Default::default()
+        };
+        let mut output = Vec::new();
+        output_minimized(&mut output, minimize_us.into_iter().map(TaggedNetwork::from).collect(), options).unwrap();
+        assert_eq!(
+            "[\"192.0.2.0/24\", \"198.51.0.7/255.255.0.255\"]\n",
+            String::from_utf8(output).unwrap(),
+        );
+
+        let mut output = Vec::new();
+        output_minimized::<Ipv6Address, _>(&mut output, Vec::new(), options).unwrap();
+        assert_eq!("[]\n", String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn test_minimize_mask_format() {
+        let minimize_us = || vec![TaggedNetwork::from(parse_ipv4net("192.0.2.0", 24))];
+        let expected = [
+            (MaskFormat::Cidr, "192.0.2.0/24\n"),
+            (MaskFormat::Slash, "192.0.2.0/255.255.255.0\n"),
+            (MaskFormat::Space, "192.0.2.0 255.255.255.0\n"),
+        ];
+        for (mask_format, output_str) in expected {
+            let options = MinimizeOptions {
+                mask_format,
+                ..Default::default()
+            };
+            let mut output = Vec::new();
+            output_minimized(&mut output, minimize_us(), options).unwrap();
+            assert_eq!(output_str, String::from_utf8(output).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_minimize_sort_routes() {
+        let minimize_us = vec![
+            parse_ipv4net("10.0.0.0", 24),
+            parse_ipv4net("0.0.0.0", 0),
+            parse_ipv4net("192.0.2.0", 25),
+            parse_ipv4net("192.0.2.128", 25),
+            parse_ipv4net("192.0.0.0", 2),
+        ];
+        let options = MinimizeOptions {
+            merge_only: true,
+            sort_routes: true,
+            ..Default::default()
+        };
+        let mut output = Vec::new();
+        output_minimized(&mut output, minimize_us.into_iter().map(TaggedNetwork::from).collect(), options).unwrap();
+        assert_eq!(
+            "0.0.0.0/0\n192.0.0.0/2\n10.0.0.0/24\n192.0.2.0/24\n",
+            String::from_utf8(output).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_minimize_tagged() {
+        let minimize_us = vec![
+            TaggedNetwork::new(parse_ipv4net("192.0.2.0", 24), Some(String::from("lan"))),
+            TaggedNetwork::new(parse_ipv4net("192.0.2.64", 26), Some(String::from("servers"))),
+            TaggedNetwork::new(parse_ipv4net("198.51.100.0", 25), Some(String::from("dmz-a"))),
+            TaggedNetwork::new(parse_ipv4net("198.51.100.128", 25), Some(String::from("dmz-b"))),
+            TaggedNetwork::from(parse_ipv4net("203.0.113.0", 24)),
+        ];
+
+        let mut output = Vec::new();
+        output_minimized(&mut output, minimize_us.clone(), MinimizeOptions::default()).unwrap();
+        assert_eq!(
+            // merged networks lose their tags
+            "192.0.2.0/24=lan\n198.51.100.0/24\n203.0.113.0/24\n",
+            String::from_utf8(output).unwrap(),
+        );
+
+        let mut output = Vec::new();
+        let options = MinimizeOptions {
+            explain: true,
+            ..Default::default()
+        };
+        output_minimized(&mut output, minimize_us, options).unwrap();
+        assert_eq!(
+            "192.0.2.0/24=lan\n  <- 192.0.2.64/26=servers\n198.51.100.0/24\n  <- 198.51.100.0/25=dmz-a\n  <- 198.51.100.128/25=dmz-b\n203.0.113.0/24\n",
+            String::from_utf8(output).unwrap(),
+        );
+    }
 }
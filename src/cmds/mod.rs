@@ -1,21 +1,33 @@
+pub mod adjacent;
+pub mod canonical;
+pub mod cisco;
+pub mod compare;
+pub mod cover;
 pub mod derange;
+pub mod diff;
 pub mod enumerate;
+pub mod hex;
+pub mod histogram;
+pub mod map;
 pub mod minimize;
 pub mod resize;
 pub mod show_net;
-#[cfg(feature = "num-bigint")]
 pub mod split;
+pub mod subnets;
 
 
 use std::error::Error;
 use std::fmt;
+use std::io::{self, BufRead, Write};
 use std::num::ParseIntError;
+use std::str::FromStr;
 
 use once_cell::sync::Lazy;
 use regex::Regex;
 
 use crate::addr::{IpAddress, IpAddressParseError, Ipv4Address, Ipv6Address};
 use crate::net::IpNetwork;
+use crate::output::Output;
 
 
 static IPV4_WITH_SUBNET_REGEX: Lazy<Regex> = Lazy::new(||
@@ -25,11 +37,145 @@ static IPV4_WITH_CIDR_REGEX: Lazy<Regex> = Lazy::new(||
     Regex::new("^(?P<addr>[0-9]+(?:[.][0-9]+){3})/(?P<wildcard>-)?(?P<cidr>[0-9]+)$").unwrap()
 );
 static IPV6_WITH_SUBNET_REGEX: Lazy<Regex> = Lazy::new(||
-    Regex::new("^(?P<addr>[0-9a-f:]+)/(?P<wildcard>-)?(?P<mask>[0-9a-f:]*:[0-9a-f:]*)$").unwrap()
+    Regex::new("^(?P<addr>\\[?[0-9a-f:]+\\]?)/(?P<wildcard>-)?(?P<mask>[0-9a-f:]*:[0-9a-f:]*)$").unwrap()
 );
 static IPV6_WITH_CIDR_REGEX: Lazy<Regex> = Lazy::new(||
-    Regex::new("^(?P<addr>[0-9a-f:]+)/(?P<wildcard>-)?(?P<cidr>[0-9]+)$").unwrap()
+    Regex::new("^(?P<addr>\\[?[0-9a-f:]+\\]?)/(?P<wildcard>-)?(?P<cidr>[0-9]+)$").unwrap()
 );
+static SPACED_SLASH_REGEX: Lazy<Regex> = Lazy::new(||
+    Regex::new(" */ *").unwrap()
+);
+
+
+/// Options that apply to all modes of operation, passed to every command.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct CommandContext {
+    /// Whether to output without colors, regardless of the terminal's capabilities and of
+    /// environment variables.
+    pub no_color: bool,
+
+    /// Whether to report errors as JSON objects instead of free text.
+    pub errors_json: bool,
+
+    /// The IP address family as which all addresses and networks are parsed, if any. If both
+    /// `--ipv4` and `--ipv6` are given, the last one wins.
+    pub family: Option<AddressFamily>,
+
+    /// Whether to suppress the normal output of the command. The exit code and errors are not
+    /// affected.
+    pub quiet: bool,
+}
+impl CommandContext {
+    /// Writes an error message to the given error output and returns a failed command result with
+    /// the given exit code. The message is written as a JSON object if `errors_json` is set and as a
+    /// line of text otherwise.
+    ///
+    /// If the error message cannot be written, the write error is ignored, as there is no other
+    /// place to report it to; the exit code still signals the failure.
+    pub fn fail<E: Output + ?Sized>(&self, stderr: &mut E, kind: ErrorKind, msg: fmt::Arguments<'_>, code: i32) -> CommandResult {
+        let _ = if self.errors_json {
+            write_json_error(stderr, kind, msg)
+        } else {
+            writeln!(stderr, "{}", msg)
+        };
+        CommandResult::Error(code)
+    }
+}
+
+/// The result of running a command.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CommandResult {
+    /// The command completed successfully.
+    Ok,
+
+    /// The command failed. The contained value is the exit code of the process.
+    Error(i32),
+}
+impl CommandResult {
+    /// The result corresponding to the given exit code of the process.
+    pub fn from_exit_code(code: i32) -> CommandResult {
+        if code == 0 {
+            CommandResult::Ok
+        } else {
+            CommandResult::Error(code)
+        }
+    }
+
+    /// The exit code of the process corresponding to this result.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CommandResult::Ok => 0,
+            CommandResult::Error(code) => *code,
+        }
+    }
+}
+
+/// The kind of an error reported by a command, as output in JSON error reports.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ErrorKind {
+    /// The command was invoked with invalid arguments.
+    Usage,
+
+    /// The format of a network specification was not recognized.
+    Parse,
+
+    /// An IP address could not be parsed.
+    Address,
+
+    /// A subnet mask could not be parsed.
+    Mask,
+
+    /// A CIDR prefix could not be parsed or is out of range.
+    Prefix,
+
+    /// The input was parsed successfully but cannot be processed, e.g. because IPv4 and IPv6
+    /// networks are mixed.
+    Input,
+
+    /// Reading input or writing output failed.
+    Io,
+}
+impl ErrorKind {
+    /// The name of this kind of error in JSON error reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::Usage => "usage",
+            ErrorKind::Parse => "parse",
+            ErrorKind::Address => "address",
+            ErrorKind::Mask => "mask",
+            ErrorKind::Prefix => "prefix",
+            ErrorKind::Input => "input",
+            ErrorKind::Io => "io",
+        }
+    }
+}
+impl From<&ParseNetspecError> for ErrorKind {
+    fn from(e: &ParseNetspecError) -> Self {
+        match e {
+            ParseNetspecError::Unrecognized(_) => ErrorKind::Parse,
+            ParseNetspecError::Address(_) => ErrorKind::Address,
+            ParseNetspecError::Mask(_) => ErrorKind::Mask,
+            ParseNetspecError::CidrParse(_) => ErrorKind::Prefix,
+            ParseNetspecError::CidrRange(_, _) => ErrorKind::Prefix,
+            ParseNetspecError::WrongFamily(_, _) => ErrorKind::Input,
+        }
+    }
+}
+impl From<&IpAddressParseError> for ErrorKind {
+    fn from(_e: &IpAddressParseError) -> Self {
+        ErrorKind::Address
+    }
+}
+
+/// Writes an error message as a JSON object of the form `{"error":"...","kind":"..."}`, followed by
+/// a newline.
+pub fn write_json_error<W: Write + ?Sized>(out: &mut W, kind: ErrorKind, msg: fmt::Arguments<'_>) -> io::Result<()> {
+    write!(out, "{{\"error\":")?;
+    write_json_string(out, &msg.to_string())?;
+    write!(out, ",\"kind\":")?;
+    write_json_string(out, kind.name())?;
+    writeln!(out, "}}")
+}
 
 
 /// An IP address that has been parsed from a string.
@@ -46,6 +192,29 @@ impl ParsedIpAddress {
         }
     }
 }
+impl FromStr for ParsedIpAddress {
+    type Err = IpAddressParseError;
+
+    /// Parses an IP address of either version; see `parse_addr`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_addr(s)
+    }
+}
+
+/// An IP address family, i.e. IP version.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum AddressFamily {
+    Ipv4,
+    Ipv6,
+}
+impl AddressFamily {
+    pub fn version(&self) -> u32 {
+        match self {
+            AddressFamily::Ipv4 => 4,
+            AddressFamily::Ipv6 => 6,
+        }
+    }
+}
 
 /// An IP network specification parsed from a string, consisting of an IP address and a network
 /// within which this IP address is contained.
@@ -55,11 +224,44 @@ pub enum NetworkSpec {
     Ipv6(Ipv6Address, IpNetwork<Ipv6Address>),
 }
 
+/// The syntactic form in which an IP network specification was given.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum NetspecForm {
+    /// IPv4 address and CIDR prefix, e.g. `192.0.2.0/24`.
+    Ipv4Cidr,
+
+    /// IPv4 address and number of host bits, e.g. `192.0.2.0/-8`.
+    Ipv4WildcardCidr,
+
+    /// IPv4 address and subnet mask, e.g. `192.0.2.0/255.255.255.0`.
+    Ipv4Mask,
+
+    /// IPv4 address and Cisco wildcard, e.g. `192.0.2.0/-0.0.0.255`.
+    Ipv4Wildcard,
+
+    /// IPv6 address and CIDR prefix, e.g. `2001:db8::/32`.
+    Ipv6Cidr,
+
+    /// IPv6 address and number of host bits, e.g. `2001:db8::/-96`.
+    Ipv6WildcardCidr,
+
+    /// IPv6 address and subnet mask, e.g. `2001:db8::/ffff:ffff::`.
+    Ipv6Mask,
+
+    /// IPv6 address and Cisco wildcard, e.g. `2001:db8::/-::ffff:ffff:ffff:ffff:ffff:ffff`.
+    Ipv6Wildcard,
+}
+
 /// A list of IP network specifications parsed from strings.
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum NetworkSpecs {
     Nothing,
-    MixedSpecs,
+
+    /// The specifications are not all of the same IP version. The first value is the zero-based
+    /// index of the first specification whose IP version differs from that of the specifications
+    /// before it and the second value is that specification.
+    MixedSpecs(usize, String),
+
     Ipv4(Vec<(Ipv4Address, IpNetwork<Ipv4Address>)>),
     Ipv6(Vec<(Ipv6Address, IpNetwork<Ipv6Address>)>),
 }
@@ -95,6 +297,11 @@ pub enum ParseNetspecError {
     /// The parsed CIDR prefix is out of range. The first value is the CIDR prefix that was parsed
     /// and the second value is the maximum CIDR prefix for the given IP address type.
     CidrRange(usize, usize),
+
+    /// The IP network specification is valid but not of the IP address family that has been forced.
+    /// The first value is the original specification string and the second value is the forced IP
+    /// version.
+    WrongFamily(String, u32),
 }
 impl fmt::Display for ParseNetspecError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -109,6 +316,8 @@ impl fmt::Display for ParseNetspecError {
                 => write!(f, "failed to parse CIDR prefix: {:?}", e),
             ParseNetspecError::CidrRange(got, max)
                 => write!(f, "CIDR prefix {} is greater than the maximum ({})", got, max),
+            ParseNetspecError::WrongFamily(spec, version)
+                => write!(f, "network specification {:?} is not an IPv{} network", spec, version),
         }
     }
 }
@@ -116,9 +325,47 @@ impl Error for ParseNetspecError {
 }
 
 
-/// Attempts to parse a single IP address.
+/// Removes a trailing port from an address, as in `192.0.2.1:443` or `[2001:db8::1]:443`. The
+/// specification is returned unchanged if it does not have this form or if the remainder is not a
+/// valid address.
+pub fn strip_port(spec: &str) -> &str {
+    if let Some(bracketed) = spec.strip_prefix('[') {
+        // IPv6 addresses are bracketed to separate the port
+        if let Some((addr_str, port_str)) = bracketed.split_once("]:") {
+            if is_port(port_str) && addr_str.parse::<Ipv6Address>().is_ok() {
+                return addr_str;
+            }
+        }
+    } else if let Some((addr_str, port_str)) = spec.split_once(':') {
+        // IPv4 addresses contain no colons of their own
+        if is_port(port_str) && addr_str.parse::<Ipv4Address>().is_ok() {
+            return addr_str;
+        }
+    }
+    spec
+}
+
+fn is_port(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()) && s.parse::<u16>().is_ok()
+}
+
+/// Attempts to parse a single IP address. A trailing port is removed using `strip_port`. The family
+/// is detected from the address.
 pub fn parse_addr(spec: &str) -> Result<ParsedIpAddress, IpAddressParseError> {
-    if spec.contains('.') {
+    parse_addr_as(spec, None)
+}
+
+/// Attempts to parse a single IP address like `parse_addr`. If a family is given, the address is
+/// parsed as an address of this family; otherwise, the family is detected from the address.
+pub fn parse_addr_as(spec: &str, family: Option<AddressFamily>) -> Result<ParsedIpAddress, IpAddressParseError> {
+    let spec = strip_port(spec.trim());
+    if family == Some(AddressFamily::Ipv4) {
+        spec.parse()
+            .map(ParsedIpAddress::Ipv4)
+    } else if family == Some(AddressFamily::Ipv6) {
+        spec.parse()
+            .map(ParsedIpAddress::Ipv6)
+    } else if spec.contains('.') {
         if spec.contains(':') {
             // wtf
             return Err(IpAddressParseError::UnknownAddressType);
@@ -134,9 +381,44 @@ pub fn parse_addr(spec: &str) -> Result<ParsedIpAddress, IpAddressParseError> {
     }
 }
 
-/// Attempts to parse a single IP network specification (address + network).
+/// Attempts to parse a single IP network specification (address + network). The address and the
+/// subnet may also be separated by whitespace instead of a slash, as in `192.0.2.0 255.255.255.0`.
+/// The family is detected from the specification.
 pub fn parse_netspec(spec: &str) -> Result<NetworkSpec, ParseNetspecError> {
-    if let Some(caps) = IPV4_WITH_SUBNET_REGEX.captures(spec) {
+    parse_netspec_detailed(spec)
+        .map(|(netspec, _form)| netspec)
+}
+
+/// Attempts to parse a single IP network specification like `parse_netspec`. If a family is given,
+/// specifications of the other family are rejected; otherwise, the family is detected from the
+/// specification.
+pub fn parse_netspec_as(spec: &str, family: Option<AddressFamily>) -> Result<NetworkSpec, ParseNetspecError> {
+    parse_netspec_detailed_as(spec, family)
+        .map(|(netspec, _form)| netspec)
+}
+
+/// Attempts to parse a single IP network specification like `parse_netspec`, additionally returning
+/// the syntactic form in which it was given.
+pub fn parse_netspec_detailed(spec: &str) -> Result<(NetworkSpec, NetspecForm), ParseNetspecError> {
+    parse_netspec_detailed_as(spec, None)
+}
+
+/// Attempts to parse a single IP network specification like `parse_netspec_as`, additionally
+/// returning the syntactic form in which it was given.
+pub fn parse_netspec_detailed_as(spec: &str, family: Option<AddressFamily>) -> Result<(NetworkSpec, NetspecForm), ParseNetspecError> {
+    // "192.0.2.0 / 24" is sometimes found in documentation
+    let spec = SPACED_SLASH_REGEX.replace_all(spec.trim(), "/");
+    let spec = spec.as_ref();
+    let mut tokens = spec.split_whitespace();
+    if let (Some(addr_str), Some(subnet_str), None) = (tokens.next(), tokens.next(), tokens.next()) {
+        if !addr_str.contains('/') && !subnet_str.contains('/') {
+            return parse_netspec_detailed_as(&format!("{}/{}", addr_str, subnet_str), family);
+        }
+    }
+
+    let allow_ipv4 = family != Some(AddressFamily::Ipv6);
+    let allow_ipv6 = family != Some(AddressFamily::Ipv4);
+    if let Some(caps) = IPV4_WITH_SUBNET_REGEX.captures(spec).filter(|_| allow_ipv4) {
         let addr_str = caps.name("addr").expect("'addr' captured").as_str();
         let is_wildcard = caps.name("wildcard").is_some();
         let mask_str = caps.name("mask").expect("'mask' captured").as_str();
@@ -150,8 +432,9 @@ pub fn parse_netspec(spec: &str) -> Result<NetworkSpec, ParseNetspecError> {
         }
 
         let net = IpNetwork::new_with_mask(addr, mask);
-        Ok(NetworkSpec::Ipv4(addr, net))
-    } else if let Some(caps) = IPV4_WITH_CIDR_REGEX.captures(spec) {
+        let form = if is_wildcard { NetspecForm::Ipv4Wildcard } else { NetspecForm::Ipv4Mask };
+        Ok((NetworkSpec::Ipv4(addr, net), form))
+    } else if let Some(caps) = IPV4_WITH_CIDR_REGEX.captures(spec).filter(|_| allow_ipv4) {
         let addr_str = caps.name("addr").unwrap().as_str();
         let is_wildcard = caps.name("wildcard").is_some();
         let mask_str = caps.name("cidr").unwrap().as_str();
@@ -168,8 +451,9 @@ pub fn parse_netspec(spec: &str) -> Result<NetworkSpec, ParseNetspecError> {
         }
 
         let net = IpNetwork::new_with_prefix(addr, cidr);
-        Ok(NetworkSpec::Ipv4(addr, net))
-    } else if let Some(caps) = IPV6_WITH_SUBNET_REGEX.captures(spec) {
+        let form = if is_wildcard { NetspecForm::Ipv4WildcardCidr } else { NetspecForm::Ipv4Cidr };
+        Ok((NetworkSpec::Ipv4(addr, net), form))
+    } else if let Some(caps) = IPV6_WITH_SUBNET_REGEX.captures(spec).filter(|_| allow_ipv6) {
         let addr_str = caps.name("addr").unwrap().as_str();
         let is_wildcard = caps.name("wildcard").is_some();
         let mask_str = caps.name("mask").unwrap().as_str();
@@ -183,8 +467,9 @@ pub fn parse_netspec(spec: &str) -> Result<NetworkSpec, ParseNetspecError> {
         }
 
         let net = IpNetwork::new_with_mask(addr, mask);
-        Ok(NetworkSpec::Ipv6(addr, net))
-    } else if let Some(caps) = IPV6_WITH_CIDR_REGEX.captures(spec) {
+        let form = if is_wildcard { NetspecForm::Ipv6Wildcard } else { NetspecForm::Ipv6Mask };
+        Ok((NetworkSpec::Ipv6(addr, net), form))
+    } else if let Some(caps) = IPV6_WITH_CIDR_REGEX.captures(spec).filter(|_| allow_ipv6) {
         let addr_str = caps.name("addr").unwrap().as_str();
         let is_wildcard = caps.name("wildcard").is_some();
         let mask_str = caps.name("cidr").unwrap().as_str();
@@ -201,31 +486,50 @@ pub fn parse_netspec(spec: &str) -> Result<NetworkSpec, ParseNetspecError> {
         }
 
         let net = IpNetwork::new_with_prefix(addr, cidr);
-        Ok(NetworkSpec::Ipv6(addr, net))
+        let form = if is_wildcard { NetspecForm::Ipv6WildcardCidr } else { NetspecForm::Ipv6Cidr };
+        Ok((NetworkSpec::Ipv6(addr, net), form))
+    } else if let Some(f) = family.filter(|_| parse_netspec_as(spec, None).is_ok()) {
+        // valid, but not of the requested family
+        Err(ParseNetspecError::WrongFamily(String::from(spec), f.version()))
     } else {
         Err(ParseNetspecError::Unrecognized(String::from(spec)))
     }
 }
 
+/// Attempts to parse an IP network specification given as an address and a separate subnet
+/// specification (subnet mask, CIDR prefix or Cisco wildcard), e.g. `"192.0.2.0"` and
+/// `"255.255.255.0"`. If a family is given, specifications of the other family are rejected (see
+/// `parse_netspec_as`).
+pub fn parse_netspec_pair(addr: &str, subnet: &str, family: Option<AddressFamily>) -> Result<NetworkSpec, ParseNetspecError> {
+    parse_netspec_as(&format!("{}/{}", addr.trim(), subnet.trim()), family)
+}
+
 /// Attempts to parse multiple IP network specifications (address + network), ensuring that all are
-/// of the same IP version.
-pub fn parse_same_family_netspecs<S: AsRef<str>>(spec_strs: &[S]) -> Result<NetworkSpecs, ParseNetspecError> {
-    if spec_strs.len() == 0 {
-        return Ok(NetworkSpecs::Nothing);
-    }
+/// of the same IP version. Empty and whitespace-only specifications, e.g. from trailing newlines,
+/// are skipped; the index in `NetworkSpecs::MixedSpecs` still refers to the original list. If a
+/// family is given, specifications of the other family are rejected (see `parse_netspec_as`).
+pub fn parse_same_family_netspecs<S: AsRef<str>>(spec_strs: &[S], family: Option<AddressFamily>) -> Result<NetworkSpecs, ParseNetspecError> {
+    let mut non_blank_specs = spec_strs.iter()
+        .map(|spec_str| spec_str.as_ref())
+        .enumerate()
+        .filter(|(_i, spec_str)| !spec_str.trim().is_empty());
+    let first_spec_str = match non_blank_specs.next() {
+        Some((_i, spec_str)) => spec_str,
+        None => return Ok(NetworkSpecs::Nothing),
+    };
 
-    match parse_netspec(spec_strs[0].as_ref())? {
+    match parse_netspec_as(first_spec_str, family)? {
         NetworkSpec::Ipv4(addr, net) => {
             let mut specs = Vec::with_capacity(spec_strs.len());
             specs.push((addr, net));
 
-            for spec_str in &spec_strs[1..] {
-                match parse_netspec(spec_str.as_ref())? {
+            for (i, spec_str) in non_blank_specs {
+                match parse_netspec_as(spec_str, family)? {
                     NetworkSpec::Ipv4(addr, net) => {
                         specs.push((addr, net));
                     },
                     NetworkSpec::Ipv6(_, _) => {
-                        return Ok(NetworkSpecs::MixedSpecs);
+                        return Ok(NetworkSpecs::MixedSpecs(i, String::from(spec_str)));
                     },
                 };
             }
@@ -236,13 +540,13 @@ pub fn parse_same_family_netspecs<S: AsRef<str>>(spec_strs: &[S]) -> Result<Netw
             let mut specs = Vec::with_capacity(spec_strs.len());
             specs.push((addr, net));
 
-            for spec_str in &spec_strs[1..] {
-                match parse_netspec(spec_str.as_ref())? {
+            for (i, spec_str) in non_blank_specs {
+                match parse_netspec_as(spec_str, family)? {
                     NetworkSpec::Ipv6(addr, net) => {
                         specs.push((addr, net));
                     },
                     NetworkSpec::Ipv4(_, _) => {
-                        return Ok(NetworkSpecs::MixedSpecs);
+                        return Ok(NetworkSpecs::MixedSpecs(i, String::from(spec_str)));
                     },
                 };
             }
@@ -252,8 +556,19 @@ pub fn parse_same_family_netspecs<S: AsRef<str>>(spec_strs: &[S]) -> Result<Netw
     }
 }
 
+/// Describes the IP version mismatch reported as `NetworkSpecs::MixedSpecs` with the given index and
+/// specification, e.g. `entry 3 (2001:db8::/32) is IPv6 but earlier entries were IPv4`.
+pub fn mixed_specs_message(index: usize, spec: &str) -> String {
+    let (version, earlier_version) = match parse_netspec(spec) {
+        Ok(NetworkSpec::Ipv6(_, _)) => ("IPv6", "IPv4"),
+        _ => ("IPv4", "IPv6"),
+    };
+    format!("entry {} ({}) is {} but earlier entries were {}", index + 1, spec, version, earlier_version)
+}
+
 /// Attempts to parse a subnet specification (mask or CIDR prefix).
 pub fn parse_subnet(spec: &str) -> Result<ParsedSubnet, ParseNetspecError> {
+    let spec = spec.trim();
     if spec.contains(':') {
         let ipv6_addr: Ipv6Address = match spec.parse() {
             Ok(ia) => ia,
@@ -280,3 +595,481 @@ pub fn parse_subnet(spec: &str) -> Result<ParsedSubnet, ParseNetspecError> {
         Ok(ParsedSubnet::Cidr(cidr_prefix))
     }
 }
+
+/// Reads IP network specifications from a line-based source such as a prefix list exported from a
+/// router. Empty lines and lines starting with `!` or `#` are skipped. Of every other line, only the
+/// first whitespace-separated token is returned; the rest of the line (e.g. a comment) is ignored.
+pub fn read_netspec_lines<R: BufRead>(reader: R) -> io::Result<Vec<String>> {
+    let lines = read_numbered_netspec_lines(reader)?;
+    Ok(lines.into_iter().map(|line| line.spec).collect())
+}
+
+/// Reads IP network specifications from a line-based source as described in `read_netspec_lines`,
+/// remembering which line each of them was read from.
+pub fn read_numbered_netspec_lines<R: BufRead>(reader: R) -> io::Result<Vec<NetspecLine>> {
+    let mut specs = Vec::new();
+    for (index, line_res) in reader.lines().enumerate() {
+        let line = line_res?;
+        let trimmed = line.trim();
+        if trimmed.starts_with('!') || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(spec) = trimmed.split_whitespace().next() {
+            specs.push(NetspecLine {
+                line_number: index + 1,
+                spec: String::from(spec),
+                line,
+            });
+        }
+    }
+    Ok(specs)
+}
+
+/// An IP network specification read from a line-based source, along with the line it was read from.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct NetspecLine {
+    /// The 1-based number of the line.
+    pub line_number: usize,
+
+    /// The full text of the line, including anything following the specification.
+    pub line: String,
+
+    /// The IP network specification, i.e. the first whitespace-separated token of the line.
+    pub spec: String,
+}
+impl NetspecLine {
+    /// Parses the specification using the given function. If parsing fails, the error is extended
+    /// by the number and text of the line.
+    pub fn parse_spec<T, F: FnOnce(&str) -> Result<T, ParseNetspecError>>(&self, parse: F) -> Result<T, NetspecLineError> {
+        parse(&self.spec)
+            .map_err(|error| NetspecLineError {
+                line_number: self.line_number,
+                line: self.line.clone(),
+                error,
+            })
+    }
+}
+
+/// An error that occurs when attempting to parse an IP network specification read from a line-based
+/// source.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NetspecLineError {
+    /// The 1-based number of the line.
+    pub line_number: usize,
+
+    /// The full text of the line.
+    pub line: String,
+
+    /// The error that occurred while parsing the specification.
+    pub error: ParseNetspecError,
+}
+impl fmt::Display for NetspecLineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {} (line {:?})", self.line_number, self.error, self.line)
+    }
+}
+impl Error for NetspecLineError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+impl From<&NetspecLineError> for ErrorKind {
+    fn from(e: &NetspecLineError) -> Self {
+        ErrorKind::from(&e.error)
+    }
+}
+
+/// Splits a command-line argument containing a comma-separated list of IP network specifications,
+/// as exported from CSV files, into the individual specifications. Empty entries are skipped.
+pub fn split_netspec_list(arg: &str) -> impl Iterator<Item = &str> {
+    arg.split(',')
+        .map(|spec| spec.trim())
+        .filter(|spec| !spec.is_empty())
+}
+
+/// Writes the given values as a JSON array of strings, followed by a newline. Each value is
+/// converted to a string using its `Display` implementation.
+pub fn write_json_string_array<W: Write + ?Sized, T: fmt::Display>(out: &mut W, values: &[T]) -> io::Result<()> {
+    write!(out, "[")?;
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            write!(out, ", ")?;
+        }
+        write_json_string(out, &value.to_string())?;
+    }
+    writeln!(out, "]")
+}
+
+/// Writes the given string as a JSON string literal, escaping characters as required.
+fn write_json_string<W: Write + ?Sized>(out: &mut W, s: &str) -> io::Result<()> {
+    write!(out, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(out, "\\\"")?,
+            '\\' => write!(out, "\\\\")?,
+            '\n' => write!(out, "\\n")?,
+            '\r' => write!(out, "\\r")?,
+            '\t' => write!(out, "\\t")?,
+            c if c < ' ' => write!(out, "\\u{:04x}", u32::from(c))?,
+            c => write!(out, "{}", c)?,
+        }
+    }
+    write!(out, "\"")
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::output::BufferOutput;
+    use crate::net::test::{
+        parse_ipv4, parse_ipv4net, parse_ipv4netm, parse_ipv6, parse_ipv6net, parse_ipv6netm,
+    };
+
+    #[test]
+    fn test_read_netspec_lines() {
+        let input = b"! exported prefix list
+# another comment
+192.0.2.0/24 ; customer A
+
+  198.51.100.0/25\tcustomer B
+198.51.100.128/25
+\t
+2001:db8::/32 # documentation
+" as &[u8];
+        let specs = read_netspec_lines(input).unwrap();
+        assert_eq!(4, specs.len());
+        assert_eq!("192.0.2.0/24", specs[0]);
+        assert_eq!("198.51.100.0/25", specs[1]);
+        assert_eq!("198.51.100.128/25", specs[2]);
+        assert_eq!("2001:db8::/32", specs[3]);
+    }
+
+    #[test]
+    fn test_read_numbered_netspec_lines() {
+        let input = b"# networks
+192.0.2.0/24 ; customer A
+192.0.2.300/24 ; customer B
+198.51.100.0/25
+" as &[u8];
+        let lines = read_numbered_netspec_lines(input).unwrap();
+        assert_eq!(3, lines.len());
+        assert_eq!(2, lines[0].line_number);
+        assert_eq!("192.0.2.0/24", lines[0].spec);
+        assert_eq!(4, lines[2].line_number);
+        assert_eq!("198.51.100.0/25", lines[2].line);
+
+        assert!(lines[0].parse_spec(parse_netspec).is_ok());
+        let e = lines[1].parse_spec(parse_netspec).unwrap_err();
+        assert_eq!(3, e.line_number);
+        assert_eq!("192.0.2.300/24 ; customer B", e.line);
+        assert_eq!(ErrorKind::Address, ErrorKind::from(&e));
+        assert!(e.to_string().starts_with("line 3: failed to parse address: "));
+        assert!(e.to_string().ends_with(" (line \"192.0.2.300/24 ; customer B\")"));
+
+        let e = NetspecLine { line_number: 7, line: String::from("bogus"), spec: String::from("bogus") }
+            .parse_spec(parse_netspec)
+            .unwrap_err();
+        assert_eq!("line 7: unrecognized network specification: \"bogus\" (line \"bogus\")", e.to_string());
+    }
+
+    #[test]
+    fn test_fail() {
+        let mut stderr = BufferOutput::new();
+        let result = CommandContext::default().fail(&mut stderr, ErrorKind::Parse, format_args!("failed to parse {:?}", "x"), 2);
+        assert_eq!(CommandResult::Error(2), result);
+        assert_eq!(2, result.exit_code());
+        assert_eq!("failed to parse \"x\"\n", stderr.text());
+
+        let mut stderr = BufferOutput::new();
+        let ctx = CommandContext { errors_json: true, ..CommandContext::default() };
+        assert_eq!(CommandResult::Error(1), ctx.fail(&mut stderr, ErrorKind::Parse, format_args!("failed to parse {:?}", "x"), 1));
+        assert_eq!("{\"error\":\"failed to parse \\\"x\\\"\",\"kind\":\"parse\"}\n", stderr.text());
+        assert_eq!(0, CommandResult::Ok.exit_code());
+        assert_eq!(CommandResult::Ok, CommandResult::from_exit_code(0));
+        assert_eq!(CommandResult::Error(3), CommandResult::from_exit_code(3));
+    }
+
+    #[test]
+    fn test_write_json_error() {
+        let e = parse_netspec("192.0.2.0~24").unwrap_err();
+        let mut output = Vec::new();
+        write_json_error(&mut output, ErrorKind::from(&e), format_args!("failed to parse network: {}", e)).unwrap();
+        assert_eq!(
+            "{\"error\":\"failed to parse network: unrecognized network specification: \\\"192.0.2.0~24\\\"\",\"kind\":\"parse\"}\n",
+            String::from_utf8(output).unwrap(),
+        );
+
+        let e = parse_netspec("192.0.2.0/33").unwrap_err();
+        assert_eq!(ErrorKind::Prefix, ErrorKind::from(&e));
+        let e = parse_netspec("192.0.2.256/24").unwrap_err();
+        assert_eq!(ErrorKind::Address, ErrorKind::from(&e));
+    }
+
+    #[test]
+    fn test_write_json_string_array() {
+        let mut output = Vec::new();
+        write_json_string_array::<_, String>(&mut output, &[]).unwrap();
+        assert_eq!("[]\n", String::from_utf8(output).unwrap());
+
+        let mut output = Vec::new();
+        write_json_string_array(&mut output, &["192.0.2.0/24", "quote \" backslash \\ tab \t bell \x07"]).unwrap();
+        assert_eq!(
+            "[\"192.0.2.0/24\", \"quote \\\" backslash \\\\ tab \\t bell \\u0007\"]\n",
+            String::from_utf8(output).unwrap(),
+        );
+    }
+
+    fn assert_ipv4_round_trip(net: IpNetwork<Ipv4Address>) {
+        let string = net.to_string();
+        assert_eq!(Ok(NetworkSpec::Ipv4(net.base_addr(), net)), parse_netspec(&string), "{:?} does not round-trip", string);
+    }
+
+    fn assert_ipv6_round_trip(net: IpNetwork<Ipv6Address>) {
+        let string = net.to_string();
+        assert_eq!(Ok(NetworkSpec::Ipv6(net.base_addr(), net)), parse_netspec(&string), "{:?} does not round-trip", string);
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        // CIDR
+        assert_ipv4_round_trip(parse_ipv4net("192.0.2.0", 24));
+        assert_ipv4_round_trip(parse_ipv4net("0.0.0.0", 0));
+        assert_ipv4_round_trip(parse_ipv4net("192.0.2.1", 32));
+        assert_ipv6_round_trip(parse_ipv6net("2001:db8::", 32));
+        assert_ipv6_round_trip(parse_ipv6net("::", 0));
+        assert_ipv6_round_trip(parse_ipv6net("2001:db8::1", 128));
+
+        // mixed masks
+        assert_ipv4_round_trip(parse_ipv4netm("192.0.2.0", "255.0.255.0"));
+        assert_ipv4_round_trip(parse_ipv4netm("128.0.0.130", "255.0.0.254"));
+        assert_ipv4_round_trip(parse_ipv4netm("0.0.0.1", "0.0.0.255"));
+        assert_ipv6_round_trip(parse_ipv6netm("2001:db8::1", "ffff:ffff::ffff"));
+        assert_ipv6_round_trip(parse_ipv6netm("::1", "::ffff"));
+
+        // wildcard-derived
+        for spec_str in &["192.0.2.0/-0.0.0.255", "10.0.0.0/-0.255.0.255", "10.0.0.0/-8", "2001:db8::/-::ffff:0", "2001:db8::/-96"] {
+            match parse_netspec(spec_str).unwrap() {
+                NetworkSpec::Ipv4(_addr, net) => assert_ipv4_round_trip(net),
+                NetworkSpec::Ipv6(_addr, net) => assert_ipv6_round_trip(net),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_surrounding_whitespace() {
+        assert_eq!(
+            Ok(NetworkSpec::Ipv4(parse_ipv4("192.0.2.0"), parse_ipv4net("192.0.2.0", 24))),
+            parse_netspec(" 192.0.2.0/24 "),
+        );
+        assert_eq!(
+            Ok(NetworkSpec::Ipv4(parse_ipv4("192.0.2.0"), parse_ipv4net("192.0.2.0", 24))),
+            parse_netspec("\t192.0.2.0/255.255.255.0\n"),
+        );
+        assert_eq!(
+            Ok(NetworkSpec::Ipv6(parse_ipv6("2001:db8::"), parse_ipv6net("2001:db8::", 32))),
+            parse_netspec("  2001:db8::/32"),
+        );
+        assert_eq!(Ok(ParsedIpAddress::Ipv4(parse_ipv4("192.0.2.1"))), parse_addr(" 192.0.2.1 "));
+        assert_eq!(Ok(ParsedIpAddress::Ipv6(parse_ipv6("2001:db8::1"))), parse_addr("2001:db8::1\n"));
+        assert_eq!(Ok(ParsedSubnet::Cidr(24)), parse_subnet(" 24 "));
+        assert_eq!(Ok(ParsedSubnet::Ipv4Mask(parse_ipv4("255.255.255.0"))), parse_subnet(" 255.255.255.0"));
+
+        // whitespace within the specification is still an error (except around the slash)
+        assert_eq!(
+            Err(ParseNetspecError::Unrecognized(String::from("192.0.2. 0/24"))),
+            parse_netspec(" 192.0.2. 0/24 "),
+        );
+        assert!(parse_addr("192.0. 2.1").is_err());
+    }
+
+    #[test]
+    fn test_strip_port() {
+        assert_eq!("192.0.2.1", strip_port("192.0.2.1:443"));
+        assert_eq!("2001:db8::1", strip_port("[2001:db8::1]:443"));
+        assert_eq!(Ok(ParsedIpAddress::Ipv4(parse_ipv4("192.0.2.1"))), parse_addr("192.0.2.1:443"));
+        assert_eq!(Ok(ParsedIpAddress::Ipv6(parse_ipv6("2001:db8::1"))), parse_addr(" [2001:db8::1]:65535 "));
+
+        // an unbracketed IPv6 address cannot have a port
+        assert_eq!("2001:db8::1:443", strip_port("2001:db8::1:443"));
+        assert_eq!(Ok(ParsedIpAddress::Ipv6(parse_ipv6("2001:db8::1:443"))), parse_addr("2001:db8::1:443"));
+
+        // only strip if the remainder is valid
+        assert_eq!("192.0.2.1:65536", strip_port("192.0.2.1:65536"));
+        assert_eq!("192.0.2.1:", strip_port("192.0.2.1:"));
+        assert_eq!("192.0.2.1:+1", strip_port("192.0.2.1:+1"));
+        assert_eq!("192.0.2.256:443", strip_port("192.0.2.256:443"));
+        assert_eq!("[2001:db8::g]:443", strip_port("[2001:db8::g]:443"));
+        assert_eq!("[192.0.2.1]:443", strip_port("[192.0.2.1]:443"));
+        assert!(parse_addr("192.0.2.256:443").is_err());
+    }
+
+    #[test]
+    fn test_parse_bracketed() {
+        assert_eq!(Ok(ParsedIpAddress::Ipv6(parse_ipv6("2001:db8::1"))), parse_addr("[2001:db8::1]"));
+        assert_eq!(
+            Ok(NetworkSpec::Ipv6(parse_ipv6("2001:db8::1"), parse_ipv6net("2001:db8::", 64))),
+            parse_netspec("[2001:db8::1]/64"),
+        );
+        assert_eq!(
+            Ok(NetworkSpec::Ipv6(parse_ipv6("2001:db8::1"), parse_ipv6net("2001:db8::", 64))),
+            parse_netspec("[2001:db8::1]/ffff:ffff:ffff:ffff::"),
+        );
+
+        assert_eq!(Err(IpAddressParseError::UnbalancedBrackets), parse_addr("[2001:db8::1"));
+        assert_eq!(Err(IpAddressParseError::UnbalancedBrackets), parse_addr("2001:db8::1]"));
+        assert_eq!(
+            Err(ParseNetspecError::Address(IpAddressParseError::UnbalancedBrackets)),
+            parse_netspec("[2001:db8::1/64"),
+        );
+        assert!(parse_addr("[[2001:db8::1]]").is_err());
+    }
+
+    #[test]
+    fn test_parse_same_family_netspecs_mixed() {
+        assert_eq!(
+            Ok(NetworkSpecs::MixedSpecs(2, String::from("2001:db8::/32"))),
+            parse_same_family_netspecs(&["192.0.2.0/24", "198.51.100.0/24", "2001:db8::/32", "203.0.113.0/24"], None),
+        );
+        assert_eq!(
+            Ok(NetworkSpecs::MixedSpecs(1, String::from("192.0.2.0/24"))),
+            parse_same_family_netspecs(&["2001:db8::/32", "192.0.2.0/24"], None),
+        );
+        assert_eq!(
+            "entry 3 (2001:db8::/32) is IPv6 but earlier entries were IPv4",
+            mixed_specs_message(2, "2001:db8::/32"),
+        );
+        assert_eq!(
+            "entry 2 (192.0.2.0/24) is IPv4 but earlier entries were IPv6",
+            mixed_specs_message(1, "192.0.2.0/24"),
+        );
+    }
+
+    #[test]
+    fn test_parse_same_family_netspecs_blank() {
+        let joined = "192.0.2.0/24\n198.51.100.0/24\n\n  \n";
+        let spec_strs: Vec<&str> = joined.split('\n').collect();
+        assert_eq!(
+            Ok(NetworkSpecs::Ipv4(vec![
+                (parse_ipv4("192.0.2.0"), parse_ipv4net("192.0.2.0", 24)),
+                (parse_ipv4("198.51.100.0"), parse_ipv4net("198.51.100.0", 24)),
+            ])),
+            parse_same_family_netspecs(&spec_strs, None),
+        );
+
+        assert_eq!(Ok(NetworkSpecs::Nothing), parse_same_family_netspecs(&["", " \t"], None));
+        assert_eq!(
+            Ok(NetworkSpecs::MixedSpecs(3, String::from("192.0.2.0/24"))),
+            parse_same_family_netspecs(&["", "2001:db8::/32", "", "192.0.2.0/24"], None),
+        );
+    }
+
+    #[test]
+    fn test_parse_netspec_pair() {
+        let expected = NetworkSpec::Ipv4(parse_ipv4("192.0.2.0"), parse_ipv4net("192.0.2.0", 24));
+        assert_eq!(Ok(expected), parse_netspec_pair("192.0.2.0", "255.255.255.0", None));
+        assert_eq!(Ok(expected), parse_netspec_pair("192.0.2.0", "24", None));
+        assert_eq!(Ok(expected), parse_netspec_pair("192.0.2.0", "-0.0.0.255", None));
+        assert_eq!(Ok(expected), parse_netspec("192.0.2.0 255.255.255.0"));
+        assert_eq!(Ok(expected), parse_netspec(" 192.0.2.0\t24 "));
+        assert_eq!(
+            Ok(NetworkSpec::Ipv6(parse_ipv6("2001:db8::1"), parse_ipv6net("2001:db8::", 64))),
+            parse_netspec_pair("2001:db8::1", "64", None),
+        );
+
+        assert!(parse_netspec_pair("192.0.2.0", "255.255.255.256", None).is_err());
+        assert!(parse_netspec("192.0.2.0 255.255.255.0 24").is_err());
+    }
+
+    #[test]
+    fn test_parsed_ip_address_from_str() {
+        assert_eq!(Ok(ParsedIpAddress::Ipv4(parse_ipv4("192.0.2.1"))), "192.0.2.1".parse());
+        assert_eq!(Ok(ParsedIpAddress::Ipv6(parse_ipv6("2001:db8::1"))), "2001:db8::1".parse());
+        assert_eq!(Err(IpAddressParseError::UnknownAddressType), "localhost".parse::<ParsedIpAddress>());
+    }
+
+    #[test]
+    fn test_forced_family() {
+        // unambiguous
+        assert_eq!(Ok(ParsedIpAddress::Ipv4(parse_ipv4("192.0.2.1"))), parse_addr_as("192.0.2.1", Some(AddressFamily::Ipv4)));
+        assert!(parse_addr_as("192.0.2.1", Some(AddressFamily::Ipv6)).is_err());
+        assert_eq!(Ok(ParsedIpAddress::Ipv6(parse_ipv6("2001:db8::1"))), parse_addr_as("2001:db8::1", Some(AddressFamily::Ipv6)));
+        assert!(parse_addr_as("2001:db8::1", Some(AddressFamily::Ipv4)).is_err());
+
+        // ambiguous: neither family accepts IPv4-embedded IPv6 notation
+        assert_eq!(Err(IpAddressParseError::UnknownAddressType), parse_addr_as("::ffff:192.0.2.1", None));
+        assert!(parse_addr_as("::ffff:192.0.2.1", Some(AddressFamily::Ipv4)).is_err());
+        assert!(parse_addr_as("::ffff:192.0.2.1", Some(AddressFamily::Ipv6)).is_err());
+
+        assert_eq!(
+            Ok(NetworkSpec::Ipv4(parse_ipv4("192.0.2.0"), parse_ipv4net("192.0.2.0", 24))),
+            parse_netspec_as("192.0.2.0 255.255.255.0", Some(AddressFamily::Ipv4)),
+        );
+        assert_eq!(
+            Err(ParseNetspecError::WrongFamily(String::from("192.0.2.0/24"), 6)),
+            parse_netspec_as("192.0.2.0/24", Some(AddressFamily::Ipv6)),
+        );
+        assert_eq!(
+            Ok(NetworkSpec::Ipv6(parse_ipv6("2001:db8::1"), parse_ipv6net("2001:db8::", 32))),
+            parse_netspec_as("2001:db8::1/32", Some(AddressFamily::Ipv6)),
+        );
+        assert_eq!(
+            Err(ParseNetspecError::WrongFamily(String::from("2001:db8::/32"), 4)),
+            parse_netspec_as("2001:db8::/32", Some(AddressFamily::Ipv4)),
+        );
+        assert!(matches!(
+            parse_netspec_as("localhost/24", Some(AddressFamily::Ipv4)),
+            Err(ParseNetspecError::Unrecognized(_)),
+        ));
+
+        // lists are rejected as a whole instead of being reported as mixed
+        assert_eq!(
+            Err(ParseNetspecError::WrongFamily(String::from("2001:db8::/32"), 4)),
+            parse_same_family_netspecs(&["192.0.2.0/24", "2001:db8::/32"], Some(AddressFamily::Ipv4)),
+        );
+        assert_eq!(
+            Err(ParseNetspecError::WrongFamily(String::from("192.0.2.0/24"), 6)),
+            parse_netspec_pair("192.0.2.0", "24", Some(AddressFamily::Ipv6)),
+        );
+    }
+
+    #[test]
+    fn test_parse_netspec_detailed() {
+        fn form(spec: &str) -> NetspecForm {
+            parse_netspec_detailed(spec).unwrap().1
+        }
+
+        assert_eq!(NetspecForm::Ipv4Cidr, form("192.0.2.0/24"));
+        assert_eq!(NetspecForm::Ipv4Cidr, form("192.0.2.0 24"));
+        assert_eq!(NetspecForm::Ipv4Cidr, form("192.0.2.0 / 24"));
+        assert_eq!(NetspecForm::Ipv4WildcardCidr, form("192.0.2.0/-8"));
+        assert_eq!(NetspecForm::Ipv4Mask, form("192.0.2.0/255.255.255.0"));
+        assert_eq!(NetspecForm::Ipv4Mask, form("192.0.2.0 255.255.255.0"));
+        assert_eq!(NetspecForm::Ipv4Wildcard, form("192.0.2.0/-0.0.0.255"));
+        assert_eq!(NetspecForm::Ipv6Cidr, form("2001:db8::/32"));
+        assert_eq!(NetspecForm::Ipv6Cidr, form("[2001:db8::1]/64"));
+        assert_eq!(NetspecForm::Ipv6WildcardCidr, form("2001:db8::/-96"));
+        assert_eq!(NetspecForm::Ipv6Mask, form("2001:db8::/ffff:ffff::"));
+        assert_eq!(NetspecForm::Ipv6Wildcard, form("2001:db8::/-::ffff:ffff:ffff:ffff:ffff:ffff"));
+
+        let (netspec, _form) = parse_netspec_detailed("192.0.2.0/-8").unwrap();
+        assert_eq!(Ok(netspec), parse_netspec("192.0.2.0/24"));
+        assert!(parse_netspec_detailed("192.0.2.0~24").is_err());
+    }
+
+    #[test]
+    fn test_parse_netspec_spaced_slash() {
+        assert_eq!(
+            Ok(NetworkSpec::Ipv4(parse_ipv4("192.0.2.0"), parse_ipv4net("192.0.2.0", 24))),
+            parse_netspec("192.0.2.0 / 24"),
+        );
+        assert_eq!(
+            Ok(NetworkSpec::Ipv6(parse_ipv6("2001:db8::"), parse_ipv6net("2001:db8::", 32))),
+            parse_netspec("2001:db8:: / 32"),
+        );
+        assert_eq!(
+            Ok(NetworkSpec::Ipv4(parse_ipv4("192.0.2.1"), parse_ipv4net("192.0.2.0", 24))),
+            parse_netspec("192.0.2.1  /255.255.255.0"),
+        );
+    }
+}
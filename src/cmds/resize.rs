@@ -1,37 +1,35 @@
 use std::cmp::Ordering;
 use std::convert::TryInto;
+use std::io;
 
-use crate::usage;
+use crate::run::usage;
 use crate::addr::{IpAddress, Ipv4Address, Ipv6Address};
 use crate::bit_manip::{unravel_address, weave_address};
 use crate::cidr::subnet_mask_bytes_from_prefix;
-use crate::cmds::{NetworkSpec, ParsedSubnet, parse_netspec, parse_subnet};
-use crate::cmds::show_net::{output_ipv4_network, output_ipv6_network};
+use crate::cmds::{CommandContext, CommandResult, ErrorKind, NetworkSpec, ParsedSubnet, parse_netspec_as, parse_subnet};
+use crate::cmds::show_net::{ShowNetOptions, output_ipv4_network, output_ipv6_network};
 use crate::net::IpNetwork;
+use crate::output::Output;
 
 
-pub fn resize(args: &[String]) -> i32 {
+pub fn resize(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
     if args.len() != 4 {
         // ripcalc --resize IPADDRESS/SUBNET SUBNET
-        usage();
-        return 1;
+        return usage(ctx, stderr);
     }
 
-    match parse_netspec(&args[2]) {
+    let output_res = match parse_netspec_as(&args[2], ctx.family) {
         Err(e) => {
-            eprintln!("failed to parse network spec {:?}: {}", args[2], e);
-            1
+            return ctx.fail(stderr, ErrorKind::from(&e), format_args!("failed to parse network spec {:?}: {}", args[2], e), 1);
         },
         Ok(NetworkSpec::Ipv4(_addr, net)) => {
             let mask = match parse_subnet(&args[3]) {
                 Err(e) => {
-                    eprintln!("failed to parse subnet {:?}: {}", args[3], e);
-                    return 1;
+                    return ctx.fail(stderr, ErrorKind::from(&e), format_args!("failed to parse subnet {:?}: {}", args[3], e), 1);
                 },
                 Ok(ParsedSubnet::Cidr(cidr)) => {
                     if cidr > 32 {
-                        eprintln!("CIDR value {} is greater than maximum for IPv4 (32)", cidr);
-                        return 1;
+                        return ctx.fail(stderr, ErrorKind::Prefix, format_args!("CIDR value {} is greater than maximum for IPv4 (32)", cidr), 1);
                     }
                     let mask_bytes = subnet_mask_bytes_from_prefix(cidr, 4);
                     Ipv4Address::from_bytes(&mask_bytes).unwrap()
@@ -40,23 +38,19 @@ pub fn resize(args: &[String]) -> i32 {
                     m
                 },
                 Ok(ParsedSubnet::Ipv6Mask(_)) => {
-                    eprintln!("cannot resize an IPv4 subnet to an IPv6 mask");
-                    return 1;
+                    return ctx.fail(stderr, ErrorKind::Input, format_args!("cannot resize an IPv4 subnet to an IPv6 mask"), 1);
                 },
             };
-            resize_and_output(net, mask, output_ipv4_network);
-            0
+            resize_and_output(stdout, net, mask, output_ipv4_network)
         },
         Ok(NetworkSpec::Ipv6(_addr, net)) => {
             let mask = match parse_subnet(&args[3]) {
                 Err(e) => {
-                    eprintln!("failed to parse subnet {:?}: {}", args[3], e);
-                    return 1;
+                    return ctx.fail(stderr, ErrorKind::from(&e), format_args!("failed to parse subnet {:?}: {}", args[3], e), 1);
                 },
                 Ok(ParsedSubnet::Cidr(cidr)) => {
                     if cidr > 128 {
-                        eprintln!("CIDR value {} is greater than maximum for IPv6 (128)", cidr);
-                        return 1;
+                        return ctx.fail(stderr, ErrorKind::Prefix, format_args!("CIDR value {} is greater than maximum for IPv6 (128)", cidr), 1);
                     }
                     let mask_bytes = subnet_mask_bytes_from_prefix(cidr, 16);
                     Ipv6Address::from_bytes(&mask_bytes).unwrap()
@@ -65,42 +59,48 @@ pub fn resize(args: &[String]) -> i32 {
                     m
                 },
                 Ok(ParsedSubnet::Ipv4Mask(_)) => {
-                    eprintln!("cannot resize an IPv6 subnet to an IPv4 mask");
-                    return 1;
+                    return ctx.fail(stderr, ErrorKind::Input, format_args!("cannot resize an IPv6 subnet to an IPv4 mask"), 1);
                 },
             };
-            resize_and_output(net, mask, output_ipv6_network);
-            0
+            resize_and_output(stdout, net, mask, output_ipv6_network)
         },
+    };
+
+    match output_res {
+        Ok(()) => CommandResult::Ok,
+        Err(e) => ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1),
     }
 }
 
-fn resize_and_output<A: IpAddress, ON: Fn(IpNetwork<A>, Option<A>)>(initial_net: IpNetwork<A>, new_subnet_mask: A, output_network: ON) {
+fn resize_and_output<A: IpAddress, ON: Fn(&mut dyn Output, IpNetwork<A>, Option<A>, &ShowNetOptions) -> io::Result<()>>(stdout: &mut dyn Output, initial_net: IpNetwork<A>, new_subnet_mask: A, output_network: ON) -> io::Result<()> {
     let (resized, net_ordering) = resize_network(initial_net, new_subnet_mask);
+    let options = ShowNetOptions::default();
 
-    println!("Original network:");
-    output_network(initial_net, None);
-    println!();
+    writeln!(stdout, "Original network:")?;
+    output_network(stdout, initial_net, None, &options)?;
+    writeln!(stdout)?;
 
     match net_ordering {
         Ordering::Less => {
-            println!("Supernet:");
-            output_network(resized[0], None);
-            println!();
+            writeln!(stdout, "Supernet:")?;
+            output_network(stdout, resized[0], None, &options)?;
+            writeln!(stdout)?;
         },
         Ordering::Equal => {
-            println!("Same-sized net:");
-            output_network(resized[0], None);
-            println!();
+            writeln!(stdout, "Same-sized net:")?;
+            output_network(stdout, resized[0], None, &options)?;
+            writeln!(stdout)?;
         },
         Ordering::Greater => {
             for i in 0..resized.len() {
-                println!("Subnet {}:", i+1);
-                output_network(resized[i], None);
-                println!();
+                writeln!(stdout, "Subnet {}:", i+1)?;
+                output_network(stdout, resized[i], None, &options)?;
+                writeln!(stdout)?;
             }
         },
     }
+
+    Ok(())
 }
 
 /// Resizes the given network to the given subnet mask, returning the network or networks created by
@@ -113,7 +113,8 @@ pub fn resize_network<A: IpAddress>(initial_net: IpNetwork<A>, new_subnet_mask:
     if new_host_bits > initial_host_bits {
         // supernet
         let unraveled_initial_base_addr = unravel_address(initial_net.base_addr(), initial_net.subnet_mask());
-        let unraveled_shortened_net = IpNetwork::new_with_prefix(unraveled_initial_base_addr, new_net_bits);
+        let unraveled_shortened_net = IpNetwork::try_new_with_prefix(unraveled_initial_base_addr, new_net_bits)
+            .expect("the number of network bits of a subnet mask is a valid prefix");
         let woven_new_base_addr = weave_address(unraveled_shortened_net.base_addr(), new_subnet_mask);
         let new_net = IpNetwork::new_with_mask(woven_new_base_addr, new_subnet_mask);
 
@@ -158,6 +159,7 @@ pub fn resize_network<A: IpAddress>(initial_net: IpNetwork<A>, new_subnet_mask:
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::output::BufferOutput;
     use crate::net::test::{
         parse_ipv4, parse_ipv4net, parse_ipv4netm, parse_ipv6, parse_ipv6net, parse_ipv6netm,
     };
@@ -287,4 +289,52 @@ mod test {
         assert_eq!(1, resized.len());
         assert_eq!(parse_ipv6netm("2001:db8:0:1234::", "ffff:ffff:0:ffff::"), resized[0]);
     }
+
+    fn run_resize(net_str: &str, subnet_str: &str) -> (CommandResult, String, String) {
+        run_resize_in(net_str, subnet_str, &CommandContext::default())
+    }
+
+    fn run_resize_in(net_str: &str, subnet_str: &str, ctx: &CommandContext) -> (CommandResult, String, String) {
+        let args = vec![
+            String::from("ripcalc"), String::from("--resize"),
+            String::from(net_str), String::from(subnet_str),
+        ];
+        let mut stdout = BufferOutput::new();
+        let mut stderr = BufferOutput::new();
+        let result = resize(&args, ctx, &mut stdout, &mut stderr);
+        (result, stdout.text(), stderr.text())
+    }
+
+    #[test]
+    fn test_resize_command() {
+        let (result, stdout, stderr) = run_resize("192.0.2.0/24", "25");
+        assert_eq!(CommandResult::Ok, result);
+        assert!(stdout.starts_with("Original network:\n"));
+        assert!(stdout.contains("Subnet 1:\nNetwork:   192.0.2.0/25 "));
+        assert!(stdout.contains("Subnet 2:\nNetwork:   192.0.2.128/25 "));
+        assert_eq!("", stderr);
+
+        let (result, stdout, stderr) = run_resize("192.0.2.0/24", "33");
+        assert_eq!(CommandResult::Error(1), result);
+        assert_eq!("", stdout);
+        assert_eq!("CIDR value 33 is greater than maximum for IPv4 (32)\n", stderr);
+
+        let (result, stdout, stderr) = run_resize("192.0.2.0/24", "ffff::");
+        assert_eq!(CommandResult::Error(1), result);
+        assert_eq!("", stdout);
+        assert_eq!("cannot resize an IPv4 subnet to an IPv6 mask\n", stderr);
+
+        let (result, _stdout, stderr) = run_resize("192.0.2.0/-", "24");
+        assert_eq!(CommandResult::Error(1), result);
+        assert!(stderr.starts_with("failed to parse network spec \"192.0.2.0/-\": "));
+
+        let json = CommandContext {
+            errors_json: true,
+            ..Default::default()
+        };
+        let (result, stdout, stderr) = run_resize_in("192.0.2.0/24", "33", &json);
+        assert_eq!(CommandResult::Error(1), result);
+        assert_eq!("", stdout);
+        assert_eq!("{\"error\":\"CIDR value 33 is greater than maximum for IPv4 (32)\",\"kind\":\"prefix\"}\n", stderr);
+    }
 }
@@ -1,13 +1,21 @@
 use std::fmt::Debug;
+use std::io;
+use std::str;
 
 #[cfg(feature = "num-bigint")]
 use num_bigint::BigInt;
 
-use crate::bit_manip::bytes_to_binary;
-use crate::cmds::{NetworkSpec, parse_netspec};
+use crate::bit_manip::{byte_to_binary, bytes_to_bits};
+use crate::cmds::{
+    AddressFamily, CommandContext, CommandResult, ErrorKind, NetworkSpec, ParseNetspecError, ParsedSubnet,
+    parse_netspec_as, parse_netspec_pair, parse_subnet,
+};
+use crate::cidr::is_cidr_mask;
 use crate::console::{Color, write_in_color};
-use crate::addr::{IpAddress, Ipv4Address, Ipv6Address};
+use crate::addr::{IpAddress, Ipv4Address, Ipv6Address, extract_6to4_v4, extract_teredo, ipv6_scope};
 use crate::net::IpNetwork;
+use crate::output::Output;
+use crate::tagged::split_tag;
 
 
 const LABEL_COLOR: Color = Color::White;
@@ -18,134 +26,537 @@ const MASK_BITS_COLOR: Color = Color::Red;
 const CLASS_BITS_COLOR: Color = Color::Magenta;
 const ADDR_SEP_COLOR: Color = Color::White;
 
+const LABEL_WIDTH: isize = 11;
 
-pub fn show_net<S: AsRef<str> + Debug>(args: &Vec<S>) -> i32 {
-    let mut specs = Vec::new();
-    for arg in &args[1..] {
-        match parse_netspec(arg.as_ref()) {
-            Ok(spec) => specs.push(spec),
-            Err(e) => {
-                eprintln!("{}", e);
-                return 1;
-            },
+
+/// A network specification along with the tag attached to it, if any.
+type TaggedNetworkSpec = (NetworkSpec, Option<String>);
+
+
+/// Options influencing how information about a network is output.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ShowNetOptions {
+    /// Whether to separate groups of three digits in large numbers (e.g. `16,777,214`).
+    pub group_digits: bool,
+
+    /// Whether to omit the `=>` line separating the address from the network information.
+    pub hide_arrow: bool,
+
+    /// Whether to output IPv6 addresses in their full form, without omitting leading zeroes or
+    /// consecutive zero fields.
+    pub expand_ipv6: bool,
+
+    /// Whether to output the typical use of the network's subnet mask, if it is commonly used for a
+    /// specific purpose.
+    pub describe_mask: bool,
+
+    /// Whether to warn on standard error if an address given as part of a network specification
+    /// has host bits set, i.e. is not the network's base address.
+    pub warn_host_bits: bool,
+
+    /// Whether to output a legend below the binary representation of the network, marking each bit
+    /// as a class, network or host bit.
+    pub explain_bits: bool,
+
+    /// Whether to treat both addresses of a network consisting of two addresses (e.g. a /31 IPv4
+    /// network) as host addresses, as is common on point-to-point links.
+    pub point_to_point: bool,
+
+    /// Whether to output a table with one row per network instead of dissecting each network.
+    pub table: bool,
+
+    /// Whether to omit the lines containing the first and last host address and the broadcast
+    /// address, which are rarely meaningful for large (especially IPv6) networks.
+    pub hide_hosts: bool,
+
+    /// Whether to append the hexadecimal representation of the subnet mask (e.g. `0xffffff00`) to
+    /// the netmask line.
+    pub mask_hex: bool,
+}
+
+
+/// The key facts about a network, formatted for output as a row of a table.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct NetworkReport {
+    /// The network, in CIDR notation if possible.
+    pub network: String,
+
+    /// The subnet mask of the network.
+    pub netmask: String,
+
+    /// The first and last host address of the network, if it contains any hosts.
+    pub host_range: Option<(String, String)>,
+
+    /// The broadcast address of the network, if it has one.
+    pub broadcast: Option<String>,
+
+    /// The number of hosts in the network, if known and nonzero.
+    pub host_count: Option<String>,
+
+    /// The tag attached to the network, if any.
+    pub tag: Option<String>,
+}
+impl NetworkReport {
+    /// Collects the key facts about the given network, formatting addresses using the given
+    /// function.
+    pub fn new<A: IpAddress, FA: Fn(&A) -> String>(net: &IpNetwork<A>, format_address: FA, tag: Option<String>, options: &ShowNetOptions) -> NetworkReport {
+        let network = if let Some(pfx) = net.cidr_prefix() {
+            format!("{}/{}", format_address(&net.base_addr()), pfx)
+        } else {
+            format_address(&net.base_addr())
         };
+        let (host_addrs, broadcast_addr) = host_and_broadcast_addrs(net, options);
+        NetworkReport {
+            network,
+            netmask: format_address(&net.subnet_mask()),
+            host_range: host_addrs.map(|(fha, lha)| (format_address(&fha), format_address(&lha))),
+            broadcast: broadcast_addr.map(|bc| format_address(&bc)),
+            host_count: host_count_string_with_options(net, options),
+            tag,
+        }
+    }
+
+    /// Collects the key facts about the network in the given specification.
+    pub fn from_spec(spec: &NetworkSpec, tag: Option<String>, options: &ShowNetOptions) -> NetworkReport {
+        match spec {
+            NetworkSpec::Ipv4(_a, n)
+                => NetworkReport::new(n, |a| a.to_string(), tag, options),
+            NetworkSpec::Ipv6(_a, n)
+                => NetworkReport::new(n, |a| if options.expand_ipv6 { a.to_full_string() } else { a.to_string() }, tag, options),
+        }
+    }
+}
+
+
+pub fn show_net<S: AsRef<str> + Debug>(args: &[S], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
+    let (options, specs) = match parse_show_net_args(&args[1..], ctx.family) {
+        Ok(os) => os,
+        Err(e) => {
+            return ctx.fail(stderr, ErrorKind::from(&e), format_args!("{}", e), 1);
+        },
+    };
+
+    if options.table {
+        let mut reports = Vec::with_capacity(specs.len());
+        for (spec, tag) in &specs {
+            if options.warn_host_bits {
+                if let Some(w) = spec_host_bits_warning(spec) {
+                    let _ = writeln!(stderr, "{}", w);
+                }
+            }
+            reports.push(NetworkReport::from_spec(spec, tag.clone(), &options));
+        }
+        if let Err(e) = output_network_table(stdout, &reports) {
+            return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+        }
+        return CommandResult::Ok;
     }
 
     let mut is_first = true;
-    for spec in &specs {
+    for (spec, tag) in &specs {
         if !is_first {
-            println!();
+            if let Err(e) = writeln!(stdout) {
+                return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+            }
         }
         is_first = false;
 
-        match spec {
-            NetworkSpec::Ipv4(a, n) => output_ipv4_network(*n, Some(*a)),
-            NetworkSpec::Ipv6(a, n) => output_ipv6_network(*n, Some(*a)),
+        if options.warn_host_bits {
+            if let Some(w) = spec_host_bits_warning(spec) {
+                let _ = writeln!(stderr, "{}", w);
+            }
+        }
+
+        if let Some(t) = tag {
+            if let Err(e) = output_tag(stdout, t) {
+                return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+            }
+        }
+
+        let result = match spec {
+            NetworkSpec::Ipv4(a, n) => output_ipv4_network(stdout, *n, Some(*a), &options),
+            NetworkSpec::Ipv6(a, n) => output_ipv6_network(stdout, *n, Some(*a), &options),
         };
+        if let Err(e) = result {
+            return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+        }
     }
 
-    0
+    CommandResult::Ok
 }
 
-fn output_network<A: IpAddress, OBA: Fn(A, Option<A>, bool, Option<Color>), OC: Fn(&str, &str)>(
-    label_width: isize,
-    address_width: isize,
-    output_binary_address: OBA,
-    output_class: OC,
-    net: IpNetwork<A>,
-    addr: Option<A>,
-) {
-    let output_initial_columns = |label: &str, address: &str| {
-        write_in_color(label, Some(LABEL_COLOR), label_width);
-        write_in_color(address, Some(IP_ADDRESS_COLOR), address_width);
-    };
+/// Parses the arguments to the show-network mode into the output options and the network
+/// specifications, each with its optional tag (see `split_tag`). If a family is given, networks of
+/// the other family are rejected.
+fn parse_show_net_args<S: AsRef<str>>(args: &[S], family: Option<AddressFamily>) -> Result<(ShowNetOptions, Vec<TaggedNetworkSpec>), ParseNetspecError> {
+    let mut options = ShowNetOptions::default();
+    let mut specs = Vec::new();
+    let mut arg_iter = args.iter()
+        .map(|arg| arg.as_ref())
+        .peekable();
+    while let Some(arg) = arg_iter.next() {
+        if arg == "--group-digits" {
+            options.group_digits = true;
+        } else if arg == "--no-arrow" {
+            options.hide_arrow = true;
+        } else if arg == "--expand" {
+            options.expand_ipv6 = true;
+        } else if arg == "--describe-mask" {
+            options.describe_mask = true;
+        } else if arg == "--warn-host-bits" {
+            options.warn_host_bits = true;
+        } else if arg == "--explain-bits" {
+            options.explain_bits = true;
+        } else if arg == "--p2p" {
+            options.point_to_point = true;
+        } else if arg == "--table" {
+            options.table = true;
+        } else if arg == "--no-hosts" {
+            options.hide_hosts = true;
+        } else if arg == "--mask-hex" {
+            options.mask_hex = true;
+        } else if let Some(subnet_arg) = arg_iter.next_if(|next| !arg.contains('/') && is_separate_subnet_arg(split_tag(next).0)) {
+            // address and subnet given as separate arguments, e.g. "192.0.2.0 255.255.255.0"
+            let (subnet_str, tag) = split_tag(subnet_arg);
+            specs.push((parse_netspec_pair(arg, subnet_str, family)?, tag.map(String::from)));
+        } else {
+            let (net_str, tag) = split_tag(arg);
+            specs.push((parse_netspec_as(net_str, family)?, tag.map(String::from)));
+        }
+    }
+    Ok((options, specs))
+}
+
+/// Returns whether the given argument, following an address without a subnet, specifies the subnet
+/// of that address: a CIDR prefix, a Cisco wildcard prefixed with `-` or a subnet mask equivalent to a
+/// CIDR prefix. Any other address is taken to be another network specification.
+fn is_separate_subnet_arg(arg: &str) -> bool {
+    if let Some(wildcard) = arg.strip_prefix('-') {
+        return !wildcard.starts_with('-') && parse_subnet(wildcard).is_ok();
+    }
+    match parse_subnet(arg) {
+        Ok(ParsedSubnet::Cidr(_)) => true,
+        Ok(ParsedSubnet::Ipv4Mask(mask)) => is_cidr_mask(mask),
+        Ok(ParsedSubnet::Ipv6Mask(mask)) => is_cidr_mask(mask),
+        Err(_) => false,
+    }
+}
 
-    if let Some(a) = addr {
-        output_initial_columns("Address:", &a.to_string());
-        output_binary_address(a, Some(net.subnet_mask()), false, None);
-        println!();
+/// Returns a warning if the given address, from which the given network has been derived, has host
+/// bits set and is therefore not the base address of the network.
+pub fn host_bits_warning<A: IpAddress>(addr: A, net: &IpNetwork<A>) -> Option<String> {
+    if IpNetwork::new_with_mask_strict(addr, net.subnet_mask()).is_some() {
+        None
+    } else {
+        Some(format!("warning: {} has host bits set; the network is {}", addr, net))
+    }
+}
 
-        let netmask_addr_str = if let Some(pfx) = net.cidr_prefix() {
-            format!("{} = {}", net.subnet_mask(), pfx)
-        } else {
-            net.subnet_mask().to_string()
-        };
-        output_initial_columns("Netmask:", &netmask_addr_str);
-        output_binary_address(net.subnet_mask(), None, false, Some(MASK_BITS_COLOR));
-        println!();
+/// Returns a warning if the address in the given network specification has host bits set; see
+/// `host_bits_warning`.
+fn spec_host_bits_warning(spec: &NetworkSpec) -> Option<String> {
+    match spec {
+        NetworkSpec::Ipv4(a, n) => host_bits_warning(*a, n),
+        NetworkSpec::Ipv6(a, n) => host_bits_warning(*a, n),
+    }
+}
+
+/// Outputs the line containing the tag of a network.
+fn output_tag(out: &mut dyn Output, tag: &str) -> io::Result<()> {
+    write_in_color(out, "Tag:", Some(LABEL_COLOR), LABEL_WIDTH)?;
+    writeln!(out, "{}", tag)
+}
 
-        output_initial_columns("Wildcard:", &net.cisco_wildcard().to_string());
-        output_binary_address(net.cisco_wildcard(), None, false, None);
-        println!();
+/// Returns the number of hosts in the given network as a decimal number string, or `None` if the
+/// network contains no hosts.
+#[cfg(feature = "num-bigint")]
+fn host_count_string<A: IpAddress>(net: &IpNetwork<A>) -> Option<String> {
+    let host_count = net.host_count();
+    if host_count > BigInt::from(0) {
+        Some(host_count.to_string())
+    } else {
+        None
+    }
+}
 
-        write_in_color("=>", Some(LABEL_COLOR), 0);
-        println!();
+/// Host counts are only output when ripcalc is built with arbitrary-precision arithmetic.
+#[cfg(not(feature = "num-bigint"))]
+fn host_count_string<A: IpAddress>(_net: &IpNetwork<A>) -> Option<String> {
+    None
+}
+
+/// Returns the number of hosts in the given network as a decimal number string, taking into account
+/// point-to-point networks and digit grouping, or `None` if the network contains no hosts.
+fn host_count_string_with_options<A: IpAddress>(net: &IpNetwork<A>, options: &ShowNetOptions) -> Option<String> {
+    let point_to_point = options.point_to_point && net.is_point_to_point();
+    if point_to_point { Some(String::from("2")) } else { host_count_string(net) }
+        .map(|hc| if options.group_digits { group_digits(&hc) } else { hc })
+}
+
+/// Returns the first and last host address and the broadcast address of the given network, if
+/// any. If the network is a point-to-point network and the corresponding option is set, both of
+/// its addresses are host addresses and it has no broadcast address.
+fn host_and_broadcast_addrs<A: IpAddress>(net: &IpNetwork<A>, options: &ShowNetOptions) -> (Option<(A, A)>, Option<A>) {
+    if options.point_to_point && net.is_point_to_point() {
+        // both addresses are hosts; there is no broadcast address
+        (Some((net.base_addr(), net.last_addr_of_subnet())), None)
+    } else {
+        (net.first_host_addr().map(|fha| (fha, net.last_host_addr().unwrap())), net.broadcast_addr())
+    }
+}
+
+/// Formats the given bytes as a hexadecimal number with a `0x` prefix, e.g. `0xffffff00`.
+pub fn hex_string(bytes: &[u8]) -> String {
+    let mut ret = String::with_capacity(2 + bytes.len() * 2);
+    ret.push_str("0x");
+    for b in bytes {
+        ret.push_str(&format!("{:02x}", b));
+    }
+    ret
+}
+
+/// Separates groups of three digits in the given decimal number string using commas, e.g.
+/// `"16777214"` becomes `"16,777,214"`.
+pub fn group_digits(number: &str) -> String {
+    let (sign, digits) = match number.strip_prefix('-') {
+        Some(d) => ("-", d),
+        None => ("", number),
+    };
+
+    let mut ret = String::with_capacity(number.len() + digits.len() / 3);
+    ret.push_str(sign);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            ret.push(',');
+        }
+        ret.push(c);
     }
+    ret
+}
 
+fn output_network<A: IpAddress, FA: Fn(&A) -> String, OBA: Fn(&mut dyn Output, A, Option<A>, bool, Option<Color>) -> io::Result<()>, CB: Fn(&IpNetwork<A>) -> usize>(
+    out: &mut dyn Output,
+    format_address: FA,
+    output_binary_address: OBA,
+    class_bit_count: CB,
+    net: IpNetwork<A>,
+    addr: Option<A>,
+    options: &ShowNetOptions,
+) -> io::Result<()> {
+    // render the addresses first to find out how wide the address column must be
+    let addr_str = addr.map(|a| format_address(&a));
+    let mut netmask_addr_str = if let Some(pfx) = net.cidr_prefix() {
+        format!("{} = {}", format_address(&net.subnet_mask()), pfx)
+    } else {
+        format_address(&net.subnet_mask())
+    };
+    if options.mask_hex {
+        netmask_addr_str.push_str(&format!(" = {}", hex_string(&net.subnet_mask().to_bytes())));
+    }
+    let wildcard_str = if let Some(pfx) = net.cidr_prefix() {
+        format!("{} (inverse of /{})", format_address(&net.cisco_wildcard()), pfx)
+    } else {
+        format_address(&net.cisco_wildcard())
+    };
     let net_str = if let Some(pfx) = net.cidr_prefix() {
-        format!("{}/{}", net.base_addr(), pfx)
+        format!("{}/{}", format_address(&net.base_addr()), pfx)
     } else {
-        net.base_addr().to_string()
+        format_address(&net.base_addr())
     };
-    output_initial_columns("Network:", &net_str);
-    output_binary_address(net.base_addr(), Some(net.subnet_mask()), true, None);
-    println!();
-
-    if let Some(fha) = net.first_host_addr() {
-        output_initial_columns("HostMin:", &fha.to_string());
-        output_binary_address(fha, None, false, None);
-        println!();
-        let lha = net.last_host_addr().unwrap();
-        output_initial_columns("HostMax:", &lha.to_string());
-        output_binary_address(lha, None, false, None);
+    let (host_addrs, broadcast_addr) = if options.hide_hosts {
+        (None, None)
     } else {
-        write_in_color("no hosts", Some(LABEL_COLOR), 0);
+        host_and_broadcast_addrs(&net, options)
+    };
+    let host_addr_strs = host_addrs
+        .map(|(fha, lha)| (format_address(&fha), format_address(&lha)));
+    let broadcast_str = broadcast_addr
+        .map(|bc| format_address(&bc));
+    let host_count_str = host_count_string_with_options(&net, options);
+
+    // the column is at least as wide as the widest possible netmask (and wildcard, if it is output)
+    // so that the columns of consecutive networks line up
+    let all_ones = net.subnet_mask() | net.cisco_wildcard();
+    let mut widest_netmask_str = format!("{} = {}", format_address(&all_ones), all_ones.byte_count() * 8);
+    if options.mask_hex {
+        widest_netmask_str.push_str(&format!(" = {}", hex_string(&all_ones.to_bytes())));
+    }
+    let widest_wildcard_str = format!("{} (inverse of /{})", format_address(&all_ones), all_ones.byte_count() * 8);
+    let mut column_strs = vec![&widest_netmask_str, &netmask_addr_str, &net_str];
+    if addr_str.is_some() {
+        column_strs.push(&widest_wildcard_str);
+        column_strs.push(&wildcard_str);
     }
-    println!();
+    column_strs.extend(addr_str.iter());
+    if let Some((fha_str, lha_str)) = &host_addr_strs {
+        column_strs.push(fha_str);
+        column_strs.push(lha_str);
+    }
+    column_strs.extend(broadcast_str.iter());
+    column_strs.extend(host_count_str.iter());
+    let address_width: isize = column_strs.iter()
+        .map(|s| s.len() + 1)
+        .max()
+        .unwrap()
+        .try_into()
+        .unwrap();
 
-    if let Some(bc) = net.broadcast_addr() {
-        output_initial_columns("Broadcast:", &bc.to_string());
-        output_binary_address(bc, None, false, None);
-    } else {
-        write_in_color("no broadcast", Some(LABEL_COLOR), 0);
+    let output_initial_columns = |out: &mut dyn Output, label: &str, address: &str| -> io::Result<()> {
+        write_in_color(out, label, Some(LABEL_COLOR), LABEL_WIDTH)?;
+        write_in_color(out, address, Some(IP_ADDRESS_COLOR), address_width)
+    };
+
+    if let (Some(a), Some(a_str)) = (addr, &addr_str) {
+        output_initial_columns(out, "Address:", a_str)?;
+        output_binary_address(out, a, Some(net.subnet_mask()), false, None)?;
+        writeln!(out)?;
+
+        output_initial_columns(out, "Netmask:", &netmask_addr_str)?;
+        output_binary_address(out, net.subnet_mask(), None, false, Some(MASK_BITS_COLOR))?;
+        writeln!(out)?;
+
+        output_initial_columns(out, "Wildcard:", &wildcard_str)?;
+        output_binary_address(out, net.cisco_wildcard(), None, false, None)?;
+        writeln!(out)?;
+
+        if !options.hide_arrow {
+            write_in_color(out, "=>", Some(LABEL_COLOR), 0)?;
+            writeln!(out)?;
+        }
+    }
+
+    output_initial_columns(out, "Network:", &net_str)?;
+    output_binary_address(out, net.base_addr(), Some(net.subnet_mask()), true, None)?;
+    writeln!(out)?;
+
+    if options.explain_bits {
+        output_initial_columns(out, "", "")?;
+        write_in_color(out, bit_legend(&net.subnet_mask().to_bytes(), class_bit_count(&net)), Some(LABEL_COLOR), 0)?;
+        writeln!(out)?;
+    }
+
+    if !options.hide_hosts {
+        if let (Some((fha, lha)), Some((fha_str, lha_str))) = (host_addrs, &host_addr_strs) {
+            output_initial_columns(out, "HostMin:", fha_str)?;
+            output_binary_address(out, fha, None, false, None)?;
+            writeln!(out)?;
+            output_initial_columns(out, "HostMax:", lha_str)?;
+            output_binary_address(out, lha, None, false, None)?;
+        } else {
+            write_in_color(out, "no hosts", Some(LABEL_COLOR), 0)?;
+        }
+        writeln!(out)?;
+
+        if let (Some(bc), Some(bc_str)) = (broadcast_addr, &broadcast_str) {
+            output_initial_columns(out, "Broadcast:", bc_str)?;
+            output_binary_address(out, bc, None, false, None)?;
+        } else {
+            write_in_color(out, "no broadcast", Some(LABEL_COLOR), 0)?;
+        }
+        writeln!(out)?;
     }
-    println!();
 
     if cfg!(feature = "num-bigint") {
-        if net.host_count() > BigInt::from(0) {
-            output_initial_columns("Hosts/Net:", &net.host_count().to_string());
-            let top_bits = bytes_to_binary(&net.base_addr().to_bytes()[0..1]);
-            let top_mask_bits = bytes_to_binary(&net.subnet_mask().to_bytes()[0..1]);
-            output_class(&top_bits, &top_mask_bits);
-            println!();
+        if let Some(hc_str) = &host_count_str {
+            output_initial_columns(out, "Hosts/Net:", hc_str)?;
+            output_class(out, net.base_addr(), class_bit_count(&net))?;
+            writeln!(out)?;
         } else {
-            write_in_color("no hosts/net", Some(LABEL_COLOR), 0);
+            write_in_color(out, "no hosts/net", Some(LABEL_COLOR), 0)?;
+            writeln!(out)?;
         }
     }
+
+    let description = net.cidr_prefix()
+        .filter(|_pfx| options.describe_mask)
+        .and_then(|pfx| describe_prefix(pfx, net.subnet_mask().byte_count() * 8));
+    if let Some(description) = description {
+        write_in_color(out, "Mask use:", Some(LABEL_COLOR), LABEL_WIDTH)?;
+        writeln!(out, "/{}, {}", net.cidr_prefix().unwrap(), description)?;
+    }
+
+    Ok(())
+}
+
+/// Returns a description of the typical use of networks with the given CIDR prefix, or `None` if
+/// the prefix is not commonly used for a specific purpose. `address_bits` is the number of bits in
+/// an address of the relevant IP version.
+pub fn describe_prefix(prefix: usize, address_bits: usize) -> Option<&'static str> {
+    match (address_bits, prefix) {
+        (32, 8) => Some("16,777,216 addresses, former class A network"),
+        (32, 16) => Some("65,536 addresses, former class B network"),
+        (32, 24) => Some("256 addresses, typical LAN"),
+        (32, 30) => Some("4 addresses, point-to-point link"),
+        (32, 31) => Some("2 addresses, point-to-point link (RFC 3021)"),
+        (32, 32) => Some("single host"),
+        (128, 48) => Some("typical site allocation"),
+        (128, 56) => Some("typical end-user allocation"),
+        (128, 64) => Some("typical LAN"),
+        (128, 127) => Some("point-to-point link (RFC 6164)"),
+        (128, 128) => Some("single host"),
+        _ => None,
+    }
 }
 
-fn output_ipv4_class(top_bits: &str, top_mask_bits: &str) {
-    if top_bits.starts_with("0") && top_mask_bits.starts_with("1") {
-        write_in_color("Class A", Some(CLASS_BITS_COLOR), 0);
-    } else if top_bits.starts_with("10") && top_mask_bits.starts_with("11") {
-        write_in_color("Class B", Some(CLASS_BITS_COLOR), 0);
-    } else if top_bits.starts_with("110") && top_mask_bits.starts_with("111") {
-        write_in_color("Class C", Some(CLASS_BITS_COLOR), 0);
-    } else if top_mask_bits.starts_with("1111") {
-        if top_bits.starts_with("1110") {
-            write_in_color("Class D (multicast)", Some(CLASS_BITS_COLOR), 0);
-        } else if top_bits.starts_with("1111") {
-            write_in_color("Class E (reserved)", Some(CLASS_BITS_COLOR), 0);
+/// Returns a legend for the binary representation of an address, marking each bit as a class bit
+/// (`C`), a network bit (`N`) or a host bit (`H`) according to the subnet mask. The first
+/// `class_bits` bits are marked as class bits.
+pub fn bit_legend(subnet_mask: &[u8], class_bits: usize) -> String {
+    // like the binary representation, split IPv4 addresses into bytes and IPv6 addresses into
+    // 16-bit chunks
+    let (group_bytes, separator) = if subnet_mask.len() == 4 { (1, '.') } else { (2, ':') };
+
+    let mut legend = String::with_capacity(subnet_mask.len() * 9);
+    for (i, is_net_bit) in bytes_to_bits(subnet_mask).into_iter().enumerate() {
+        if i > 0 && i % (group_bytes * 8) == 0 {
+            legend.push(separator);
         }
+        legend.push(if i < class_bits { 'C' } else if is_net_bit { 'N' } else { 'H' });
     }
+    legend
+}
+
+/// Returns the number of leading bits of the network's base address that determine its (former)
+/// address class, or 0 if these bits are not all network bits.
+fn ipv4_class_bit_count(net: &IpNetwork<Ipv4Address>) -> usize {
+    let addr_bits = bytes_to_bits(&net.base_addr().to_bytes());
+    let mask_bits = bytes_to_bits(&net.subnet_mask().to_bytes());
+
+    // the class is given by up to three one bits followed by a zero bit (class A to D) or by four
+    // one bits (class E)
+    let class_bits = addr_bits.iter()
+        .take(4)
+        .position(|bit| !bit)
+        .map(|zero_index| zero_index + 1)
+        .unwrap_or(4);
+    if mask_bits[..class_bits].iter().all(|bit| *bit) {
+        class_bits
+    } else {
+        0
+    }
+}
+
+/// Outputs the (former) address class of a network given the number of leading bits of its base
+/// address that determine the class (see `ipv4_class_bit_count`).
+fn output_class<A: IpAddress>(out: &mut dyn Output, base_addr: A, class_bits: usize) -> io::Result<()> {
+    let class_name = match class_bits {
+        1 => "Class A",
+        2 => "Class B",
+        3 => "Class C",
+        4 if base_addr.to_bytes()[0] & 0b0001_0000 == 0 => "Class D (multicast)",
+        4 => "Class E (reserved)",
+        _ => return Ok(()),
+    };
+    write_in_color(out, class_name, Some(CLASS_BITS_COLOR), 0)
 }
 
 fn output_binary_ipv4_address(
+    out: &mut dyn Output,
     addr: Ipv4Address,
     subnet_mask: Option<Ipv4Address>,
     mut color_class: bool,
     override_color: Option<Color>
-) {
+) -> io::Result<()> {
     let addr_bytes = addr.to_bytes();
     let mask_bytes = subnet_mask.as_ref().map(|m| m.to_bytes());
 
@@ -153,33 +564,33 @@ fn output_binary_ipv4_address(
         let b = addr_bytes[i];
         let m = mask_bytes.as_ref().map(|m| m[i]);
 
-        let bits = bytes_to_binary(&[b]);
-        let mask_bits = m.map(|mb| bytes_to_binary(&[mb]));
+        let bits = byte_to_binary(b);
+        let bits_str = str::from_utf8(&bits).expect("binary digits are ASCII");
+        let mask_bits = m.map(byte_to_binary);
 
         if override_color.is_some() {
             // simply output the address
-            write_in_color(bits, override_color, 0);
+            write_in_color(out, bits_str, override_color, 0)?;
         } else if mask_bits.is_none() {
             // simple output here too
-            write_in_color(bits, Some(HOST_BITS_COLOR), 0);
+            write_in_color(out, bits_str, Some(HOST_BITS_COLOR), 0)?;
         } else {
             // we must differentiate
 
-            let bitvec: Vec<char> = bits.chars().collect();
             if i == 0 && color_class {
-                let mask_bitvec: Vec<char> = mask_bits.as_ref().unwrap().chars().collect();
+                let mask_bitvec = mask_bits.unwrap();
 
                 // check if this is a classful network
-                if mask_bitvec[0] == '0' {
+                if mask_bitvec[0] == b'0' {
                     // first bit isn't part of the network
                     color_class = false;
-                } else if bitvec[0] == '1' && mask_bitvec[1] == '0' {
+                } else if bits[0] == b'1' && mask_bitvec[1] == b'0' {
                     // first bit, 1, is part of the network, but second isn't
                     color_class = false;
-                } else if bitvec[1] == '1' && mask_bitvec[2] == '0' {
+                } else if bits[1] == b'1' && mask_bitvec[2] == b'0' {
                     // first two bits, both 1, are part of the network, but third isn't
                     color_class = false;
-                } else if bitvec[2] == '1' && mask_bitvec[3] == '0' {
+                } else if bits[2] == b'1' && mask_bitvec[3] == b'0' {
                     // first two bits, both 1, are part of the network, but third isn't
                     color_class = false;
                 }
@@ -188,7 +599,7 @@ fn output_binary_ipv4_address(
             for bit in 0..8 {
                 // assign color
                 let color = if let Some(mb) = &mask_bits {
-                    if mb.chars().nth(bit).unwrap() == '1' {
+                    if mb[bit] == b'1' {
                         NET_BITS_COLOR
                     } else {
                         HOST_BITS_COLOR
@@ -202,11 +613,11 @@ fn output_binary_ipv4_address(
 
                     if bit == 0 {
                         Some(CLASS_BITS_COLOR)
-                    } else if bit == 1 && bitvec[0] == '1' {
+                    } else if bit == 1 && bits[0] == b'1' {
                         Some(CLASS_BITS_COLOR)
-                    } else if bit == 2 && bits.starts_with("11") {
+                    } else if bit == 2 && bits_str.starts_with("11") {
                         Some(CLASS_BITS_COLOR)
-                    } else if bit == 3 && bits.starts_with("111") {
+                    } else if bit == 3 && bits_str.starts_with("111") {
                         Some(CLASS_BITS_COLOR)
                     } else {
                         None
@@ -215,23 +626,25 @@ fn output_binary_ipv4_address(
                     None
                 };
 
-                write_in_color(&String::from(bitvec[bit]), class_color.or(Some(color)), 0);
+                write_in_color(out, &bits_str[bit..bit+1], class_color.or(Some(color)), 0)?;
             }
         }
 
         if i < addr_bytes.len() - 1 {
             // add separator (dot)
-            write_in_color(".", Some(ADDR_SEP_COLOR), 0);
+            write_in_color(out, ".", Some(ADDR_SEP_COLOR), 0)?;
         }
     }
+    Ok(())
 }
 
 fn output_binary_ipv6_address(
+    out: &mut dyn Output,
     addr: Ipv6Address,
     subnet_mask: Option<Ipv6Address>,
     _color_class: bool,
     override_color: Option<Color>
-) {
+) -> io::Result<()> {
     let addr_bytes = addr.to_bytes();
     let mask_bytes = subnet_mask.as_ref().map(|m| m.to_bytes());
 
@@ -239,23 +652,22 @@ fn output_binary_ipv6_address(
         let b = addr_bytes[i];
         let m = mask_bytes.as_ref().map(|m| m[i]);
 
-        let bits = bytes_to_binary(&[b]);
-        let mask_bits = m.map(|mb| bytes_to_binary(&[mb]));
+        let bits = byte_to_binary(b);
+        let bits_str = str::from_utf8(&bits).expect("binary digits are ASCII");
+        let mask_bits = m.map(byte_to_binary);
 
         if override_color.is_some() {
             // simply output the address
-            write_in_color(bits, override_color, 0);
+            write_in_color(out, bits_str, override_color, 0)?;
         } else if mask_bits.is_none() {
             // simple output here too
-            write_in_color(bits, Some(HOST_BITS_COLOR), 0);
+            write_in_color(out, bits_str, Some(HOST_BITS_COLOR), 0)?;
         } else {
             // we must differentiate
-            let bitvec: Vec<char> = bits.chars().collect();
-            let mask_bitvec: Option<Vec<char>> = mask_bits.map(|mb| mb.chars().collect());
             for bit in 0..8 {
                 // assign color
-                let color = if let Some(mbv) = &mask_bitvec {
-                    if mbv[bit] == '1' {
+                let color = if let Some(mbv) = &mask_bits {
+                    if mbv[bit] == b'1' {
                         NET_BITS_COLOR
                     } else {
                         HOST_BITS_COLOR
@@ -264,37 +676,521 @@ fn output_binary_ipv6_address(
                     HOST_BITS_COLOR
                 };
 
-                write_in_color(&String::from(bitvec[bit]), Some(color), 0);
+                write_in_color(out, &bits_str[bit..bit+1], Some(color), 0)?;
             }
         }
 
         if i < addr_bytes.len() - 1 && i % 2 == 1 {
             // add separator (colon)
-            write_in_color(":", Some(ADDR_SEP_COLOR), 0);
+            write_in_color(out, ":", Some(ADDR_SEP_COLOR), 0)?;
+        }
+    }
+    Ok(())
+}
+
+/// Outputs a table with one row per network, containing the key facts about each network. Values
+/// that do not exist for a network (e.g. the broadcast address of a /32 network) are output as `-`.
+/// The host count column is only output if host counts are available, the tag column only if at
+/// least one of the networks has a tag.
+pub fn output_network_table(out: &mut dyn Output, reports: &[NetworkReport]) -> io::Result<()> {
+    let mut headers = vec!["Network", "Netmask", "HostMin", "HostMax", "Broadcast"];
+    if cfg!(feature = "num-bigint") {
+        headers.push("Hosts/Net");
+    }
+    let has_tags = reports.iter().any(|r| r.tag.is_some());
+    if has_tags {
+        headers.push("Tag");
+    }
+
+    let rows: Vec<Vec<&str>> = reports.iter()
+        .map(|report| {
+            let (host_min, host_max) = match &report.host_range {
+                Some((fha, lha)) => (fha.as_str(), lha.as_str()),
+                None => ("-", "-"),
+            };
+            let mut row = vec![
+                report.network.as_str(),
+                report.netmask.as_str(),
+                host_min,
+                host_max,
+                report.broadcast.as_deref().unwrap_or("-"),
+            ];
+            if cfg!(feature = "num-bigint") {
+                row.push(report.host_count.as_deref().unwrap_or("-"));
+            }
+            if has_tags {
+                row.push(report.tag.as_deref().unwrap_or("-"));
+            }
+            row
+        })
+        .collect();
+
+    let widths: Vec<isize> = (0..headers.len())
+        .map(|i| {
+            let width = rows.iter()
+                .map(|row| row[i].len())
+                .chain(std::iter::once(headers[i].len()))
+                .max()
+                .unwrap();
+            (width + 1).try_into().unwrap()
+        })
+        .collect();
+    let last_column = headers.len() - 1;
+
+    for (i, header) in headers.iter().enumerate() {
+        let pad_to = if i == last_column { 0 } else { widths[i] };
+        write_in_color(out, header, Some(LABEL_COLOR), pad_to)?;
+    }
+    writeln!(out)?;
+    for row in &rows {
+        for (i, value) in row.iter().enumerate() {
+            let pad_to = if i == last_column { 0 } else { widths[i] };
+            let color = if i == last_column && has_tags { None } else { Some(IP_ADDRESS_COLOR) };
+            write_in_color(out, value, color, pad_to)?;
         }
+        writeln!(out)?;
     }
+    Ok(())
 }
 
 /// Outputs and dissects information about an IPv4 network.
-pub fn output_ipv4_network(net: IpNetwork<Ipv4Address>, addr: Option<Ipv4Address>) {
+pub fn output_ipv4_network(out: &mut dyn Output, net: IpNetwork<Ipv4Address>, addr: Option<Ipv4Address>, options: &ShowNetOptions) -> io::Result<()> {
     output_network(
-        11,
-        21,
+        out,
+        |a| a.to_string(),
         output_binary_ipv4_address,
-        output_ipv4_class,
+        ipv4_class_bit_count,
         net,
         addr,
+        options,
     )
 }
 
 /// Outputs and dissects information about an IPv6 network.
-pub fn output_ipv6_network(net: IpNetwork<Ipv6Address>, addr: Option<Ipv6Address>) {
+pub fn output_ipv6_network(out: &mut dyn Output, net: IpNetwork<Ipv6Address>, addr: Option<Ipv6Address>, options: &ShowNetOptions) -> io::Result<()> {
     output_network(
-        11,
-        46,
+        out,
+        |a| if options.expand_ipv6 { a.to_full_string() } else { a.to_string() },
         output_binary_ipv6_address,
-        |_top_bits, _top_mask_bits| {},
+        // IPv6 has no address classes
+        |_net| 0,
         net,
         addr,
-    )
+        options,
+    )?;
+
+    // IPv6 has no address classes, but scopes
+    let scope_addr = addr.unwrap_or(net.base_addr());
+    write_in_color(out, "Scope:", Some(LABEL_COLOR), LABEL_WIDTH)?;
+    writeln!(out, "{}", ipv6_scope(scope_addr))?;
+
+    // decode IPv4 addresses embedded in transition mechanism addresses
+    let embedding_addr = scope_addr;
+    if let Some(v4) = extract_6to4_v4(embedding_addr) {
+        write_in_color(out, "6to4:", Some(LABEL_COLOR), LABEL_WIDTH)?;
+        write_in_color(out, v4.to_string(), Some(IP_ADDRESS_COLOR), 0)?;
+        writeln!(out)?;
+    }
+    if let Some(teredo) = extract_teredo(embedding_addr) {
+        write_in_color(out, "Teredo:", Some(LABEL_COLOR), LABEL_WIDTH)?;
+        write_in_color(
+            out,
+            format!("server {}, client {}, port {}", teredo.server, teredo.client, teredo.port),
+            Some(IP_ADDRESS_COLOR),
+            0,
+        )?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::net::test::{parse_ipv4, parse_ipv4net, parse_ipv4netm, parse_ipv6, parse_ipv6net};
+    use crate::output::BufferOutput;
+
+    #[test]
+    fn test_group_digits() {
+        assert_eq!("0", group_digits("0"));
+        assert_eq!("6", group_digits("6"));
+        assert_eq!("62", group_digits("62"));
+        assert_eq!("254", group_digits("254"));
+        assert_eq!("-1", group_digits("-1"));
+        assert_eq!("1,022", group_digits("1022"));
+        assert_eq!("65,534", group_digits("65534"));
+        assert_eq!("16,777,214", group_digits("16777214"));
+        assert_eq!("-16,777,214", group_digits("-16777214"));
+        assert_eq!("18,446,744,073,709,551,614", group_digits("18446744073709551614"));
+    }
+
+    #[test]
+    fn test_parse_group_digits_flag() {
+        assert!(!ShowNetOptions::default().group_digits);
+
+        let (options, specs) = parse_show_net_args(&["10.0.0.0/8"], None).unwrap();
+        assert!(!options.group_digits);
+        assert_eq!(1, specs.len());
+
+        let (options, specs) = parse_show_net_args(&["--group-digits", "10.0.0.0/8"], None).unwrap();
+        assert!(options.group_digits);
+        assert_eq!(1, specs.len());
+        assert_eq!((NetworkSpec::Ipv4("10.0.0.0".parse().unwrap(), parse_ipv4net("10.0.0.0", 8)), None), specs[0]);
+    }
+
+    #[test]
+    fn test_parse_tag() {
+        let (_options, specs) = parse_show_net_args(&["192.0.2.0/24=lan", "2001:db8::/32"], None).unwrap();
+        assert_eq!(
+            vec![
+                (NetworkSpec::Ipv4(parse_ipv4("192.0.2.0"), parse_ipv4net("192.0.2.0", 24)), Some(String::from("lan"))),
+                (NetworkSpec::Ipv6(parse_ipv6("2001:db8::"), parse_ipv6net("2001:db8::", 32)), None),
+            ],
+            specs,
+        );
+
+        let (_options, specs) = parse_show_net_args(&["192.0.2.1", "255.255.255.0=lan", "--expand", "2001:db8::1", "64"], None).unwrap();
+        assert_eq!(
+            vec![
+                (NetworkSpec::Ipv4(parse_ipv4("192.0.2.1"), parse_ipv4net("192.0.2.0", 24)), Some(String::from("lan"))),
+                (NetworkSpec::Ipv6(parse_ipv6("2001:db8::1"), parse_ipv6net("2001:db8::", 64)), None),
+            ],
+            specs,
+        );
+
+        let (_options, specs) = parse_show_net_args(&["192.0.2.1", "-0.0.0.255", "192.0.2.9", "-8"], None).unwrap();
+        assert_eq!(
+            vec![
+                (NetworkSpec::Ipv4(parse_ipv4("192.0.2.1"), parse_ipv4net("192.0.2.0", 24)), None),
+                (NetworkSpec::Ipv4(parse_ipv4("192.0.2.9"), parse_ipv4net("192.0.2.0", 24)), None),
+            ],
+            specs,
+        );
+
+        // a second address is not taken as the subnet mask of the first, even though it parses as one
+        assert_eq!(
+            Err(ParseNetspecError::Unrecognized(String::from("192.0.2.1"))),
+            parse_show_net_args(&["192.0.2.1", "198.51.100.7"], None),
+        );
+
+        let mut out = BufferOutput::new();
+        output_tag(&mut out, "lan").unwrap();
+        assert_eq!("Tag:       lan\n", out.text());
+    }
+
+    #[test]
+    fn test_point_to_point() {
+        let net = parse_ipv4net("192.0.2.0", 31);
+
+        let mut out = BufferOutput::new();
+        output_ipv4_network(&mut out, net, None, &ShowNetOptions::default()).unwrap();
+        let text = out.text();
+        assert!(text.contains("\nno hosts\n"));
+        assert!(text.contains("\nBroadcast: 192.0.2.1 "));
+
+        let (options, _specs) = parse_show_net_args(&["--p2p", "192.0.2.0/31"], None).unwrap();
+        assert!(options.point_to_point);
+        let mut out = BufferOutput::new();
+        output_ipv4_network(&mut out, net, None, &options).unwrap();
+        let text = out.text();
+        assert!(text.contains("\nHostMin:   192.0.2.0 "));
+        assert!(text.contains("\nHostMax:   192.0.2.1 "));
+        assert!(text.contains("\nno broadcast\n"));
+        assert!(!text.contains("no hosts"));
+
+        // other networks are not affected
+        let mut out = BufferOutput::new();
+        output_ipv4_network(&mut out, parse_ipv4net("192.0.2.0", 30), None, &options).unwrap();
+        let text = out.text();
+        assert!(text.contains("\nHostMin:   192.0.2.1 "));
+        assert!(text.contains("\nBroadcast: 192.0.2.3 "));
+    }
+
+    #[test]
+    fn test_network_table() {
+        let (options, specs) = parse_show_net_args(&["--table", "192.0.2.0/24", "198.51.100.7/32=gw", "10.0.0.0/8"], None).unwrap();
+        assert!(options.table);
+        let reports: Vec<NetworkReport> = specs.iter()
+            .map(|(spec, tag)| NetworkReport::from_spec(spec, tag.clone(), &options))
+            .collect();
+
+        let mut out = BufferOutput::new();
+        output_network_table(&mut out, &reports).unwrap();
+        let text = out.text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(4, lines.len());
+        assert!(lines[0].starts_with("Network         Netmask         HostMin   HostMax        Broadcast      "));
+        assert!(lines[0].ends_with(" Tag"));
+        assert!(lines[1].starts_with("192.0.2.0/24    255.255.255.0   192.0.2.1 192.0.2.254    192.0.2.255    "));
+        assert!(lines[1].ends_with(" -"));
+        assert!(lines[2].starts_with("198.51.100.7/32 255.255.255.255 -         -              -              "));
+        assert!(lines[2].ends_with(" gw"));
+        assert!(lines[3].starts_with("10.0.0.0/8      255.0.0.0       10.0.0.1  10.255.255.254 10.255.255.255 "));
+    }
+
+    #[test]
+    fn test_mask_hex() {
+        assert_eq!("0xffffff00", hex_string(&parse_ipv4("255.255.255.0").to_bytes()));
+        assert_eq!("0x00000000", hex_string(&parse_ipv4("0.0.0.0").to_bytes()));
+
+        let (options, _specs) = parse_show_net_args(&["--mask-hex", "192.0.2.0/24"], None).unwrap();
+        assert!(options.mask_hex);
+        let mut out = BufferOutput::new();
+        output_ipv4_network(&mut out, parse_ipv4net("192.0.2.0", 24), Some(parse_ipv4("192.0.2.1")), &options).unwrap();
+        let text = out.text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[1].starts_with("Netmask:   255.255.255.0 = 24 = 0xffffff00 "));
+
+        let offsets = binary_column_offsets(&text);
+        assert!(offsets.iter().all(|o| *o == offsets[0]));
+
+        // mixed masks have no prefix
+        let mut out = BufferOutput::new();
+        output_ipv4_network(&mut out, parse_ipv4netm("192.0.0.0", "255.255.0.255"), Some(parse_ipv4("192.0.0.0")), &options).unwrap();
+        assert!(out.text().contains("\nNetmask:   255.255.0.255 = 0xffff00ff "));
+    }
+
+    #[test]
+    fn test_hide_hosts() {
+        let (options, specs) = parse_show_net_args(&["--no-hosts", "2001:db8::1/64"], None).unwrap();
+        assert!(options.hide_hosts);
+        let (addr, net) = match specs[0].0 {
+            NetworkSpec::Ipv6(a, n) => (a, n),
+            _ => panic!("IPv6 network expected"),
+        };
+
+        let mut out = BufferOutput::new();
+        output_ipv6_network(&mut out, net, Some(addr), &options).unwrap();
+        let text = out.text();
+        assert!(text.starts_with("Address:   2001:db8::1 "));
+        assert!(text.contains("\nNetmask:   ffff:ffff:ffff:ffff:: = 64 "));
+        assert!(text.contains("\nNetwork:   2001:db8::/64 "));
+        assert!(!text.contains("HostMin:"));
+        assert!(!text.contains("HostMax:"));
+        assert!(!text.contains("Broadcast:"));
+        assert!(!text.contains("no hosts\n"));
+
+        let mut out = BufferOutput::new();
+        output_ipv6_network(&mut out, net, Some(addr), &ShowNetOptions::default()).unwrap();
+        let text = out.text();
+        assert!(text.contains("\nHostMin:   2001:db8::1 "));
+        assert!(text.contains("\nHostMax:   2001:db8::ffff:ffff:ffff:fffe "));
+    }
+
+    // the expected output includes the host count
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn test_hide_arrow() {
+        let net = parse_ipv4net("192.0.2.0", 24);
+        let addr = Some(parse_ipv4("192.0.2.4"));
+
+        let mut out = BufferOutput::new();
+        output_ipv4_network(&mut out, net, addr, &ShowNetOptions::default()).unwrap();
+        let text = out.text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(9, lines.len());
+        assert!(lines[2].starts_with("Wildcard:  0.0.0.255 "));
+        assert_eq!("=>", lines[3]);
+        assert!(lines[4].starts_with("Network:   192.0.2.0/24 "));
+
+        let (options, _specs) = parse_show_net_args(&["--no-arrow", "192.0.2.4/24"], None).unwrap();
+        assert!(options.hide_arrow);
+        let mut out = BufferOutput::new();
+        output_ipv4_network(&mut out, net, addr, &options).unwrap();
+        let text = out.text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(8, lines.len());
+        assert!(lines[2].starts_with("Wildcard:  0.0.0.255 "));
+        assert!(lines[3].starts_with("Network:   192.0.2.0/24 "));
+        assert!(lines.iter().all(|line| *line != "=>"));
+    }
+
+    #[test]
+    fn test_wildcard_inverse_prefix() {
+        let mut out = BufferOutput::new();
+        output_ipv4_network(&mut out, parse_ipv4net("192.0.2.0", 24), Some(parse_ipv4("192.0.2.4")), &ShowNetOptions::default()).unwrap();
+        assert!(out.text().contains("\nWildcard:  0.0.0.255 (inverse of /24) "));
+
+        // non-contiguous masks have no prefix to refer to
+        let mut out = BufferOutput::new();
+        output_ipv4_network(&mut out, parse_ipv4netm("192.0.0.0", "255.255.0.255"), Some(parse_ipv4("192.0.0.4")), &ShowNetOptions::default()).unwrap();
+        assert!(out.text().contains("\nWildcard:  0.0.255.0 "));
+        assert!(!out.text().contains("inverse of"));
+    }
+
+    #[test]
+    fn test_describe_mask() {
+        let (options, _specs) = parse_show_net_args(&["--describe-mask", "192.0.2.4/30"], None).unwrap();
+        assert!(options.describe_mask);
+
+        let mut out = BufferOutput::new();
+        output_ipv4_network(&mut out, parse_ipv4net("192.0.2.4", 30), None, &options).unwrap();
+        let text = out.text();
+        assert_eq!(Some("Mask use:  /30, 4 addresses, point-to-point link"), text.lines().last());
+
+        let mut out = BufferOutput::new();
+        output_ipv4_network(&mut out, parse_ipv4netm("192.0.0.0", "255.255.0.255"), None, &options).unwrap();
+        assert!(!out.text().contains("Mask use:"));
+
+        let mut out = BufferOutput::new();
+        output_ipv4_network(&mut out, parse_ipv4net("192.0.2.0", 27), None, &options).unwrap();
+        assert!(!out.text().contains("Mask use:"));
+
+        let mut out = BufferOutput::new();
+        output_ipv6_network(&mut out, parse_ipv6net("2001:db8::", 64), None, &options).unwrap();
+        assert!(out.text().contains("Mask use:  /64, typical LAN\n"));
+
+        // only on request
+        let mut out = BufferOutput::new();
+        output_ipv4_network(&mut out, parse_ipv4net("192.0.2.4", 30), None, &ShowNetOptions::default()).unwrap();
+        assert!(!out.text().contains("Mask use:"));
+    }
+
+    #[test]
+    fn test_host_bits_warning() {
+        let (options, specs) = parse_show_net_args(&["--warn-host-bits", "192.0.2.37/24", "192.0.2.0/24"], None).unwrap();
+        assert!(options.warn_host_bits);
+        let warnings: Vec<Option<String>> = specs.iter()
+            .map(|(spec, _tag)| match spec {
+                NetworkSpec::Ipv4(a, n) => host_bits_warning(*a, n),
+                NetworkSpec::Ipv6(a, n) => host_bits_warning(*a, n),
+            })
+            .collect();
+        assert_eq!(
+            vec![Some(String::from("warning: 192.0.2.37 has host bits set; the network is 192.0.2.0/24")), None],
+            warnings,
+        );
+
+        assert_eq!(None, host_bits_warning(parse_ipv6("2001:db8::"), &parse_ipv6net("2001:db8::", 64)));
+        assert!(host_bits_warning(parse_ipv6("2001:db8::1"), &parse_ipv6net("2001:db8::", 64)).is_some());
+    }
+
+    #[test]
+    fn test_explain_bits() {
+        let (options, _specs) = parse_show_net_args(&["--explain-bits", "192.0.2.0/26"], None).unwrap();
+        assert!(options.explain_bits);
+
+        let mut out = BufferOutput::new();
+        output_ipv4_network(&mut out, parse_ipv4net("192.0.2.0", 26), None, &options).unwrap();
+        let text = out.text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with("Network:   192.0.2.0/26 "));
+        assert_eq!("CCCNNNNN.NNNNNNNN.NNNNNNNN.NNHHHHHH", lines[1].trim_start());
+
+        // the legend is aligned with the binary representation
+        let offsets = binary_column_offsets(&lines[0..2].join("\n"));
+        assert_eq!(offsets[0], offsets[1]);
+
+        // class bits are only marked if they are all network bits
+        assert_eq!("HHHHHHHH.HHHHHHHH.HHHHHHHH.HHHHHHHH", bit_legend(&[0, 0, 0, 0], ipv4_class_bit_count(&parse_ipv4net("0.0.0.0", 0))));
+        assert_eq!(
+            "NNNNNNNNNNNNNNNN:NNNNNNNNNNNNNNNN:NNNNNNNNNNNNNNNN:NNNNNNNNNNNNNNNN:HHHHHHHHHHHHHHHH:HHHHHHHHHHHHHHHH:HHHHHHHHHHHHHHHH:HHHHHHHHHHHHHHHH",
+            bit_legend(&parse_ipv6net("2001:db8::", 64).subnet_mask().to_bytes(), 0),
+        );
+    }
+
+    fn strip_escape_sequences(text: &str) -> String {
+        let mut ret = String::with_capacity(text.len());
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                // skip up to and including the final letter of the sequence
+                for esc_c in chars.by_ref() {
+                    if esc_c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            } else {
+                ret.push(c);
+            }
+        }
+        ret
+    }
+
+    #[test]
+    fn test_plain_matches_colored() {
+        let (options, specs) = parse_show_net_args(&["2002:c000:204::1/48"], None).unwrap();
+        let (addr, net) = match specs[0].0 {
+            NetworkSpec::Ipv6(a, n) => (a, n),
+            _ => panic!("expected an IPv6 network"),
+        };
+
+        let mut plain = BufferOutput::new();
+        output_ipv6_network(&mut plain, net, Some(addr), &options).unwrap();
+
+        let mut colored = BufferOutput::new();
+        colored.colors_enabled = true;
+        output_ipv6_network(&mut colored, net, Some(addr), &options).unwrap();
+
+        assert!(!plain.text().contains('\x1b'));
+        if cfg!(feature = "console") {
+            assert_ne!(plain.text(), colored.text());
+        }
+        assert_eq!(plain.text(), strip_escape_sequences(&colored.text()));
+    }
+
+    fn binary_column_offsets(text: &str) -> Vec<usize> {
+        // the binary representation is the last column of each line with an address
+        text.lines()
+            .filter(|line| *line != "=>" && !line.starts_with("Hosts/Net:") && !line.starts_with("Scope:"))
+            .map(|line| line.rfind(' ').unwrap() + 1)
+            .collect()
+    }
+
+    // the expected output includes the host count
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn test_expanded_ipv6_alignment() {
+        let (options, _specs) = parse_show_net_args(&["--expand", "2001:db8::1/64"], None).unwrap();
+        assert!(options.expand_ipv6);
+
+        let mut out = BufferOutput::new();
+        output_ipv6_network(&mut out, parse_ipv6net("2001:db8::", 64), Some(parse_ipv6("2001:db8::1")), &options).unwrap();
+        let text = out.text();
+        assert!(text.starts_with("Address:   2001:0db8:0000:0000:0000:0000:0000:0001 "));
+
+        // the binary column starts at the same offset on all lines
+        let offsets = binary_column_offsets(&text);
+        assert_eq!(7, offsets.len());
+        assert!(offsets.iter().all(|o| *o == offsets[0]));
+        // the widest wildcard ("ffff:...:ffff (inverse of /128)") determines the column width
+        assert_eq!(11 + 58, offsets[0]);
+
+        // grouped digits of large host counts do not overflow the column
+        let (options, _specs) = parse_show_net_args(&["--group-digits", "::/0"], None).unwrap();
+        let mut out = BufferOutput::new();
+        output_ipv6_network(&mut out, parse_ipv6net("::", 0), None, &options).unwrap();
+        let text = out.text();
+        let hosts_line = text.lines().find(|line| line.starts_with("Hosts/Net:")).unwrap();
+        assert_eq!("Hosts/Net: 340,282,366,920,938,463,463,374,607,431,768,211,454", hosts_line.trim_end());
+        let offsets = binary_column_offsets(&text);
+        assert_eq!(4, offsets.len());
+        assert!(offsets.iter().all(|o| *o == hosts_line.len()));
+        assert_eq!(Some("Scope:     unspecified"), text.lines().last());
+    }
+
+    #[test]
+    fn test_show_net_errors_json() {
+        let json = CommandContext {
+            errors_json: true,
+            ..Default::default()
+        };
+
+        let mut stdout = BufferOutput::new();
+        let mut stderr = BufferOutput::new();
+        let result = show_net(&["ripcalc", "192.0.2.0/33"], &json, &mut stdout, &mut stderr);
+        assert_eq!(CommandResult::Error(1), result);
+        assert_eq!("", stdout.text());
+        assert_eq!("{\"error\":\"CIDR prefix 33 is greater than the maximum (32)\",\"kind\":\"prefix\"}\n", stderr.text());
+
+        // the context only affects errors
+        let mut stdout = BufferOutput::new();
+        let mut stderr = BufferOutput::new();
+        let result = show_net(&["ripcalc", "192.0.2.0/24"], &json, &mut stdout, &mut stderr);
+        assert_eq!(CommandResult::Ok, result);
+        assert!(stdout.text().starts_with("Address:   192.0.2.0 "));
+        assert_eq!("", stderr.text());
+    }
 }
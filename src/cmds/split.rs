@@ -1,95 +1,221 @@
 use std::collections::HashMap;
+use std::io;
 
+#[cfg(feature = "num-bigint")]
 use num_bigint::BigInt;
 
-use crate::usage;
+use crate::run::usage;
 use crate::addr::IpAddress;
-use crate::cmds::{NetworkSpec, parse_netspec};
+use crate::cmds::{CommandContext, CommandResult, ErrorKind, NetworkSpec, parse_netspec_as};
 use crate::cmds::derange::range_to_subnets;
-use crate::cmds::show_net::{output_ipv4_network, output_ipv6_network};
+#[cfg(feature = "num-bigint")]
+use crate::cmds::show_net::output_ipv6_network;
+use crate::cmds::show_net::{ShowNetOptions, output_ipv4_network};
 use crate::net::IpNetwork;
+use crate::output::Output;
 
 
-pub fn split(args: &[String]) -> i32 {
+/// The type of a requested number of hosts.
+#[cfg(feature = "num-bigint")]
+pub type HostCount = BigInt;
+
+/// The type of a requested number of hosts. Without arbitrary-precision arithmetic, only IPv4
+/// networks can be split, so 64 bits are plenty.
+#[cfg(not(feature = "num-bigint"))]
+pub type HostCount = u64;
+
+/// The type of a number of addresses; negative if more addresses are required than available.
+#[cfg(feature = "num-bigint")]
+pub type AddressCount = BigInt;
+
+/// The type of a number of addresses; negative if more addresses are required than available.
+#[cfg(not(feature = "num-bigint"))]
+pub type AddressCount = i128;
+
+
+pub fn split(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
     // ripcalc --split IPADDRESS/CIDRPREFIX HOSTCOUNT...
     if args.len() < 4 {
-        usage();
-        return 1;
+        return usage(ctx, stderr);
     }
 
-    let zero = BigInt::from(0);
+    let zero = HostCount::from(0u8);
 
-    let mut host_counts: Vec<BigInt> = Vec::with_capacity(args.len() - 3);
+    let mut host_counts: Vec<HostCount> = Vec::with_capacity(args.len() - 3);
     for count_str in &args[3..] {
-        let host_count: BigInt = match count_str.parse() {
+        let host_count: HostCount = match count_str.parse() {
             Ok(bu) => bu,
             Err(e) => {
-                eprintln!("failed to parse host count {:?}: {}", count_str, e);
-                return 1;
+                return ctx.fail(stderr, ErrorKind::Parse, format_args!("failed to parse host count {:?}: {}", count_str, e), 1);
             },
         };
         if host_count < zero {
-            eprintln!("host counts must be zero or greater");
-            return 1;
+            return ctx.fail(stderr, ErrorKind::Input, format_args!("host counts must be zero or greater"), 1);
         }
         host_counts.push(host_count);
     }
 
-    match parse_netspec(&args[2]) {
+    let result = match parse_netspec_as(&args[2], ctx.family) {
         Err(e) => {
-            eprintln!("failed to parse network specification {:?}: {}", args[2], e);
-            1
+            return ctx.fail(stderr, ErrorKind::from(&e), format_args!("failed to parse network specification {:?}: {}", args[2], e), 1);
         },
         Ok(NetworkSpec::Ipv4(_addr, net)) => {
-            output_split(net, host_counts, output_ipv4_network)
+            output_split(stdout, net, host_counts, output_ipv4_network)
         },
+        #[cfg(feature = "num-bigint")]
         Ok(NetworkSpec::Ipv6(_addr, net)) => {
-            output_split(net, host_counts, output_ipv6_network)
+            output_split(stdout, net, host_counts, output_ipv6_network)
+        },
+        #[cfg(not(feature = "num-bigint"))]
+        Ok(NetworkSpec::Ipv6(_addr, _net)) => {
+            return ctx.fail(stderr, ErrorKind::Input, format_args!("splitting IPv6 networks requires ripcalc to be built with the num-bigint feature"), 1);
         },
+    };
+    match result {
+        Ok(code) => CommandResult::from_exit_code(code),
+        Err(e) => ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1),
     }
 }
 
-fn output_split<A: IpAddress, ON: Fn(IpNetwork<A>, Option<A>)>(subnet: IpNetwork<A>, host_counts: Vec<BigInt>, output_network: ON) -> i32 {
-    println!("Subnet to split:");
-    output_network(subnet, None);
-    println!();
+fn output_split<A: IpAddress, ON: Fn(&mut dyn Output, IpNetwork<A>, Option<A>, &ShowNetOptions) -> io::Result<()>>(stdout: &mut dyn Output, subnet: IpNetwork<A>, host_counts: Vec<HostCount>, output_network: ON) -> io::Result<i32> {
+    let options = ShowNetOptions::default();
+
+    writeln!(stdout, "Subnet to split:")?;
+    output_network(stdout, subnet, None, &options)?;
+    writeln!(stdout)?;
 
-    let split_subnets = match split_subnet(subnet, host_counts.clone()) {
+    let split_subnets = match split_subnet_for_host_counts(subnet, host_counts.clone()) {
         Some(s) => s,
         None => {
-            println!("Not enough addresses available for this split.");
-            return 1;
+            writeln!(stdout, "Not enough addresses available for this split.")?;
+            return Ok(1);
         },
     };
     for (host_count, splitnet) in host_counts.iter().zip(&split_subnets) {
-        println!("Subnet for {} hosts:", host_count);
-        output_network(*splitnet, None);
-        println!();
+        writeln!(stdout, "Subnet for {} hosts:", host_count)?;
+        output_network(stdout, *splitnet, None, &options)?;
+        writeln!(stdout)?;
     }
 
+    let utilization = SplitUtilization::calculate(subnet, &host_counts, &split_subnets);
+    writeln!(stdout, "Utilization:")?;
+    writeln!(stdout, "Requested: {} ({}%)", utilization.requested, utilization.percentage(&utilization.requested))?;
+    writeln!(stdout, "Allocated: {} ({}%)", utilization.allocated, utilization.percentage(&utilization.allocated))?;
+    writeln!(stdout, "Wasted:    {} ({}%)", utilization.wasted(), utilization.percentage(&utilization.wasted()))?;
+    writeln!(stdout, "Free:      {} ({}%)", utilization.free(), utilization.percentage(&utilization.free()))?;
+    writeln!(stdout)?;
+
     let max_used_address = split_subnets.iter()
         .map(|sn| sn.last_addr_of_subnet())
         .max()
         .expect("no subnets returned");
     if !subnet.contains(&max_used_address) {
-        println!("Network is too small");
+        writeln!(stdout, "Network is too small")?;
     } else if let Some(next_unused_address) = max_used_address.add_offset(1) {
-        println!("Unused networks:");
+        writeln!(stdout, "Unused networks:")?;
         let last_address = subnet.last_addr_of_subnet();
         let unused_subnets = range_to_subnets(next_unused_address, last_address);
 
         for unused_subnet in unused_subnets {
-            println!("{}", unused_subnet);
+            writeln!(stdout, "{}", unused_subnet)?;
+        }
+    }
+
+    Ok(0)
+}
+
+#[cfg(feature = "num-bigint")]
+fn split_subnet_for_host_counts<A: IpAddress>(subnet: IpNetwork<A>, host_counts: Vec<HostCount>) -> Option<Vec<IpNetwork<A>>> {
+    split_subnet(subnet, host_counts)
+}
+
+#[cfg(not(feature = "num-bigint"))]
+fn split_subnet_for_host_counts<A: IpAddress>(subnet: IpNetwork<A>, host_counts: Vec<HostCount>) -> Option<Vec<IpNetwork<A>>> {
+    split_subnet_u64(subnet, host_counts)
+}
+
+#[cfg(feature = "num-bigint")]
+fn address_count<A: IpAddress>(net: &IpNetwork<A>) -> AddressCount {
+    BigInt::from(net.address_count())
+}
+
+#[cfg(not(feature = "num-bigint"))]
+fn address_count<A: IpAddress>(net: &IpNetwork<A>) -> AddressCount {
+    1u128.checked_shl(net.subnet_mask().count_zeros())
+        .and_then(|count| AddressCount::try_from(count).ok())
+        .expect("address count does not fit into i128")
+}
+
+/// How well the addresses of a network are used after it has been split.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SplitUtilization {
+    /// The number of addresses in the network that has been split.
+    pub total: AddressCount,
+
+    /// The number of addresses in the subnets created by the split.
+    pub allocated: AddressCount,
+
+    /// The number of hosts requested for the subnets.
+    pub requested: AddressCount,
+}
+impl SplitUtilization {
+    /// Calculates the utilization of the network `subnet` that has been split into `split_subnets`
+    /// for the given host counts.
+    pub fn calculate<A: IpAddress>(subnet: IpNetwork<A>, host_counts: &[HostCount], split_subnets: &[IpNetwork<A>]) -> SplitUtilization {
+        SplitUtilization {
+            total: address_count(&subnet),
+            allocated: split_subnets.iter()
+                .map(address_count)
+                .sum(),
+            requested: host_counts.iter()
+                .map(|hc| AddressCount::from(hc.clone()))
+                .sum(),
         }
     }
 
-    0
+    /// The number of allocated addresses that have not been requested, e.g. network and broadcast
+    /// addresses and addresses left over when rounding up to the next subnet size.
+    pub fn wasted(&self) -> AddressCount {
+        &self.allocated - &self.requested
+    }
+
+    /// The number of addresses of the network that have not been allocated. This is negative if the
+    /// subnets do not fit into the network.
+    pub fn free(&self) -> AddressCount {
+        &self.total - &self.allocated
+    }
+
+    /// Formats the given number of addresses as a percentage of the total number of addresses with
+    /// one decimal place, rounded towards zero.
+    pub fn percentage(&self, count: &AddressCount) -> String {
+        let per_mille: AddressCount = count * 1000 / &self.total;
+        let (sign, abs_per_mille) = if per_mille < AddressCount::from(0) {
+            ("-", -per_mille)
+        } else {
+            ("", per_mille)
+        };
+        format!("{}{}.{}", sign, &abs_per_mille / 10, &abs_per_mille % 10)
+    }
 }
 
 /// Splits a larger network into smaller networks, each housing at least a specific number of hosts.
+#[cfg(feature = "num-bigint")]
 pub fn split_subnet<A: IpAddress>(subnet: IpNetwork<A>, host_counts: Vec<BigInt>) -> Option<Vec<IpNetwork<A>>> {
+    split_subnet_by(subnet, host_counts, |net, host_count| net.host_count() >= *host_count)
+}
+
+/// Splits a larger network into smaller networks, each housing at least a specific number of hosts.
+/// Unlike `split_subnet`, this does not require arbitrary-precision arithmetic.
+pub fn split_subnet_u64<A: IpAddress>(subnet: IpNetwork<A>, host_counts: Vec<u64>) -> Option<Vec<IpNetwork<A>>> {
+    // the network and broadcast addresses are not available to hosts
+    split_subnet_by(subnet, host_counts, |net, host_count| net.address_count_exceeds(u128::from(*host_count) + 1))
+}
+
+/// Splits a larger network into smaller networks, using `fits` to decide whether a network can
+/// house the given number of hosts.
+fn split_subnet_by<A: IpAddress, C: Clone + Ord, F: Fn(&IpNetwork<A>, &C) -> bool>(subnet: IpNetwork<A>, host_counts: Vec<C>, fits: F) -> Option<Vec<IpNetwork<A>>> {
     // sort descending by size
-    let mut indexes_and_host_counts: Vec<(usize, BigInt)> = host_counts.iter()
+    let mut indexes_and_host_counts: Vec<(usize, C)> = host_counts.iter()
         .enumerate()
         .map(|(i, num)| (i, num.clone()))
         .collect();
@@ -100,14 +226,14 @@ pub fn split_subnet<A: IpAddress>(subnet: IpNetwork<A>, host_counts: Vec<BigInt>
 
     let mut index_to_subnet: HashMap<usize, IpNetwork<A>> = HashMap::new();
 
-    let mut current_net = IpNetwork::new_with_prefix(subnet.base_addr(), subnet.subnet_mask().byte_count()*8);
+    let mut current_net = IpNetwork::try_new_with_prefix(subnet.base_addr(), subnet.subnet_mask().byte_count()*8).ok()?;
     for (i, host_count) in indexes_and_host_counts {
-        while current_net.host_count() < host_count {
+        while !fits(&current_net, &host_count) {
             let cidr_prefix = current_net.cidr_prefix().unwrap();
             if cidr_prefix == 0 {
                 break;
             }
-            current_net = IpNetwork::new_with_prefix(current_net.base_addr(), cidr_prefix - 1);
+            current_net = IpNetwork::try_new_with_prefix(current_net.base_addr(), cidr_prefix - 1).ok()?;
         }
 
         if current_net.cidr_prefix().unwrap() == 0 {
@@ -121,7 +247,7 @@ pub fn split_subnet<A: IpAddress>(subnet: IpNetwork<A>, host_counts: Vec<BigInt>
             Some(nsba) => nsba,
             None => return None,
         };
-        current_net = IpNetwork::new_with_prefix(next_subnet_base_addr, current_net.subnet_mask().byte_count()*8);
+        current_net = IpNetwork::try_new_with_prefix(next_subnet_base_addr, current_net.subnet_mask().byte_count()*8).ok()?;
     }
 
     let mut ordered_subnets: Vec<(usize, IpNetwork<A>)> = index_to_subnet.iter()
@@ -138,10 +264,11 @@ pub fn split_subnet<A: IpAddress>(subnet: IpNetwork<A>, host_counts: Vec<BigInt>
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::net::test::{
-        parse_ipv4net, parse_ipv6net, parse_bigint,
-    };
+    #[cfg(feature = "num-bigint")]
+    use crate::net::test::{parse_ipv6net, parse_bigint};
+    use crate::net::test::parse_ipv4net;
 
+    #[cfg(feature = "num-bigint")]
     #[test]
     fn test_split_ipv4() {
         // single smaller net
@@ -208,6 +335,71 @@ mod test {
         assert!(none_subnet.is_none());
     }
 
+    #[test]
+    fn test_split_ipv4_u64() {
+        // single smaller net
+        let nets = split_subnet_u64(
+            parse_ipv4net("192.0.2.0", 24),
+            vec![10],
+        )
+            .unwrap();
+        assert_eq!(1, nets.len());
+        assert_eq!(parse_ipv4net("192.0.2.0", 28), nets[0]);
+
+        // multiple smaller nets of the same size, fitting
+        let nets = split_subnet_u64(
+            parse_ipv4net("192.0.2.0", 24),
+            vec![60, 60, 60, 60],
+        )
+            .unwrap();
+        assert_eq!(4, nets.len());
+        assert_eq!(parse_ipv4net("192.0.2.0", 26), nets[0]);
+        assert_eq!(parse_ipv4net("192.0.2.64", 26), nets[1]);
+        assert_eq!(parse_ipv4net("192.0.2.128", 26), nets[2]);
+        assert_eq!(parse_ipv4net("192.0.2.192", 26), nets[3]);
+
+        // multiple smaller nets of the same size, not fitting
+        let nets = split_subnet_u64(
+            parse_ipv4net("192.0.2.0", 24),
+            vec![60, 60, 60, 60, 60],
+        )
+            .unwrap();
+        assert_eq!(5, nets.len());
+        assert_eq!(parse_ipv4net("192.0.2.0", 26), nets[0]);
+        assert_eq!(parse_ipv4net("192.0.2.64", 26), nets[1]);
+        assert_eq!(parse_ipv4net("192.0.2.128", 26), nets[2]);
+        assert_eq!(parse_ipv4net("192.0.2.192", 26), nets[3]);
+        assert_eq!(parse_ipv4net("192.0.3.0", 26), nets[4]);
+
+        // multiple smaller nets of different sizes
+        let nets = split_subnet_u64(
+            parse_ipv4net("192.0.2.0", 24),
+            vec![60, 100, 60],
+        )
+            .unwrap();
+        assert_eq!(3, nets.len());
+        assert_eq!(parse_ipv4net("192.0.2.128", 26), nets[0]);
+        assert_eq!(parse_ipv4net("192.0.2.0", 25), nets[1]);
+        assert_eq!(parse_ipv4net("192.0.2.192", 26), nets[2]);
+
+        // even zero hosts need a /31 to leave room for the network and broadcast addresses
+        let nets = split_subnet_u64(
+            parse_ipv4net("192.0.2.0", 24),
+            vec![0, 1],
+        )
+            .unwrap();
+        assert_eq!(parse_ipv4net("192.0.2.4", 31), nets[0]);
+        assert_eq!(parse_ipv4net("192.0.2.0", 30), nets[1]);
+
+        // too many hosts for address space
+        let none_subnet = split_subnet_u64(
+            parse_ipv4net("192.0.2.0", 24),
+            vec![8589934592],
+        );
+        assert!(none_subnet.is_none());
+    }
+
+    #[cfg(feature = "num-bigint")]
     #[test]
     fn test_resize_ipv6() {
         // single smaller net
@@ -273,4 +465,35 @@ mod test {
         );
         assert!(none_subnet.is_none());
     }
+
+    #[test]
+    fn test_split_utilization() {
+        let subnet = parse_ipv4net("192.0.2.0", 24);
+        let host_counts: Vec<HostCount> = [60u8, 100, 60].map(HostCount::from).to_vec();
+        let nets = split_subnet_for_host_counts(subnet, host_counts.clone()).unwrap();
+        let utilization = SplitUtilization::calculate(subnet, &host_counts, &nets);
+        assert_eq!(AddressCount::from(256), utilization.total);
+        assert_eq!(AddressCount::from(256), utilization.allocated);
+        assert_eq!(AddressCount::from(220), utilization.requested);
+        assert_eq!(AddressCount::from(36), utilization.wasted());
+        assert_eq!(AddressCount::from(0), utilization.free());
+        assert_eq!("100.0", utilization.percentage(&utilization.allocated));
+        assert_eq!("85.9", utilization.percentage(&utilization.requested));
+        assert_eq!("14.0", utilization.percentage(&utilization.wasted()));
+        assert_eq!("0.0", utilization.percentage(&utilization.free()));
+
+        let host_counts: Vec<HostCount> = [10u8].map(HostCount::from).to_vec();
+        let nets = split_subnet_for_host_counts(subnet, host_counts.clone()).unwrap();
+        let utilization = SplitUtilization::calculate(subnet, &host_counts, &nets);
+        assert_eq!("6.2", utilization.percentage(&utilization.allocated));
+        assert_eq!("3.9", utilization.percentage(&utilization.requested));
+        assert_eq!("93.7", utilization.percentage(&utilization.free()));
+
+        // the subnets do not fit
+        let host_counts: Vec<HostCount> = [200u8, 60].map(HostCount::from).to_vec();
+        let nets = split_subnet_for_host_counts(subnet, host_counts.clone()).unwrap();
+        let utilization = SplitUtilization::calculate(subnet, &host_counts, &nets);
+        assert_eq!(AddressCount::from(-64), utilization.free());
+        assert_eq!("-25.0", utilization.percentage(&utilization.free()));
+    }
 }
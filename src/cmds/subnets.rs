@@ -0,0 +1,259 @@
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::num::ParseIntError;
+use std::ops::RangeInclusive;
+
+use crate::run::usage;
+use crate::addr::IpAddress;
+use crate::cmds::{CommandContext, CommandResult, ErrorKind, NetworkSpec, parse_netspec_as};
+use crate::net::{IpNetwork, SubnetIter};
+use crate::output::Output;
+
+
+/// The maximum difference between the number of network bits of the original network and its
+/// subnets, limiting the output to 2^16 = 65536 subnets.
+const MAX_SUBNET_BITS: usize = 16;
+
+
+/// An error that occurs when the subnets of a network cannot be listed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SubnetsError {
+    /// The CIDR prefix is out of range. The first value is the CIDR prefix that was requested and
+    /// the second value is the maximum CIDR prefix for the given IP address type.
+    PrefixRange(usize, usize),
+
+    /// The CIDR prefix describes larger networks than the original network. The first value is the
+    /// CIDR prefix that was requested and the second value is the number of network bits of the
+    /// original network.
+    PrefixTooShort(usize, usize),
+
+    /// The network would be split into too many subnets. The first value is the number of
+    /// additional network bits and the second is the maximum number of additional network bits.
+    TooManySubnets(usize, usize),
+
+    /// The start of a range of CIDR prefixes is greater than its end. The first value is the start
+    /// and the second value is the end of the range.
+    InvertedPrefixRange(usize, usize),
+}
+impl fmt::Display for SubnetsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubnetsError::PrefixRange(got, max)
+                => write!(f, "CIDR prefix {} is greater than the maximum ({})", got, max),
+            SubnetsError::PrefixTooShort(got, net_bits)
+                => write!(f, "CIDR prefix {} is shorter than the network's prefix ({})", got, net_bits),
+            SubnetsError::TooManySubnets(bits, max_bits)
+                => write!(f, "refusing to output 2^{} subnets (maximum is 2^{})", bits, max_bits),
+            SubnetsError::InvertedPrefixRange(start, end)
+                => write!(f, "start of CIDR prefix range ({}) is greater than its end ({})", start, end),
+        }
+    }
+}
+impl Error for SubnetsError {
+}
+impl From<&SubnetsError> for ErrorKind {
+    fn from(e: &SubnetsError) -> Self {
+        match e {
+            SubnetsError::TooManySubnets(_, _) => ErrorKind::Input,
+            _ => ErrorKind::Prefix,
+        }
+    }
+}
+
+
+pub fn subnets(args: &[String], ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
+    // ripcalc --subnets IPADDRESS/SUBNET CIDRPREFIX[-CIDRPREFIX]
+    if args.len() != 4 {
+        return usage(ctx, stderr);
+    }
+
+    let new_prefixes = match parse_prefix_range(&args[3]) {
+        Ok(p) => p,
+        Err(e) => {
+            return ctx.fail(stderr, ErrorKind::Prefix, format_args!("failed to parse CIDR prefix {:?}: {}", args[3], e), 1);
+        },
+    };
+
+    match parse_netspec_as(&args[2], ctx.family) {
+        Err(e) => {
+            ctx.fail(stderr, ErrorKind::from(&e), format_args!("failed to parse network spec {:?}: {}", args[2], e), 1)
+        },
+        Ok(NetworkSpec::Ipv4(_addr, net)) => output_subnets(ctx, stdout, stderr, net, new_prefixes),
+        Ok(NetworkSpec::Ipv6(_addr, net)) => output_subnets(ctx, stdout, stderr, net, new_prefixes),
+    }
+}
+
+fn output_subnets<A: IpAddress>(
+    ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output, net: IpNetwork<A>,
+    new_prefixes: RangeInclusive<usize>,
+) -> CommandResult {
+    let subnets = match checked_subnets_in_range(net, new_prefixes) {
+        Ok(s) => s,
+        Err(e) => {
+            return ctx.fail(stderr, ErrorKind::from(&e), format_args!("{}", e), 1);
+        },
+    };
+    for subnet in subnets {
+        if let Err(e) = writeln!(stdout, "{}", subnet) {
+            return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+        }
+    }
+    CommandResult::Ok
+}
+
+/// Parses a CIDR prefix (e.g. `26`) or an inclusive range of CIDR prefixes (e.g. `24-28`).
+pub fn parse_prefix_range(s: &str) -> Result<RangeInclusive<usize>, ParseIntError> {
+    if let Some((start_str, end_str)) = s.split_once('-') {
+        let start = start_str.parse()?;
+        let end = end_str.parse()?;
+        Ok(start..=end)
+    } else {
+        let prefix = s.parse()?;
+        Ok(prefix..=prefix)
+    }
+}
+
+/// Returns an iterator over the subnets of the given network that have `new_prefix` network bits,
+/// refusing to split a network into an excessive number of subnets.
+pub fn checked_subnets<A: IpAddress>(net: IpNetwork<A>, new_prefix: usize) -> Result<SubnetIter<A>, SubnetsError> {
+    let max_prefix = net.subnet_mask().byte_count() * 8;
+    if new_prefix > max_prefix {
+        return Err(SubnetsError::PrefixRange(new_prefix, max_prefix));
+    }
+
+    let net_bits = usize::try_from(net.subnet_mask().count_ones()).unwrap();
+    if new_prefix < net_bits {
+        return Err(SubnetsError::PrefixTooShort(new_prefix, net_bits));
+    }
+    if new_prefix - net_bits > MAX_SUBNET_BITS {
+        return Err(SubnetsError::TooManySubnets(new_prefix - net_bits, MAX_SUBNET_BITS));
+    }
+
+    let new_subnet_mask = net.extended_subnet_mask(new_prefix)
+        .expect("prefix has been validated");
+    Ok(net.subnets(new_subnet_mask))
+}
+
+/// Returns an iterator over the subnets of the given network for each CIDR prefix in the given
+/// range, ordered by prefix length. Each prefix is validated as in `checked_subnets` before any
+/// subnet is returned.
+pub fn checked_subnets_in_range<A: IpAddress>(
+    net: IpNetwork<A>,
+    new_prefixes: RangeInclusive<usize>,
+) -> Result<impl Iterator<Item = IpNetwork<A>>, SubnetsError> {
+    if new_prefixes.start() > new_prefixes.end() {
+        return Err(SubnetsError::InvertedPrefixRange(*new_prefixes.start(), *new_prefixes.end()));
+    }
+
+    let subnet_iters = new_prefixes
+        .map(|new_prefix| checked_subnets(net, new_prefix))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(subnet_iters.into_iter().flatten())
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::net::test::{parse_ipv4net, parse_ipv4netm, parse_ipv6net};
+
+    #[test]
+    fn test_subnets_ipv4() {
+        let lines: Vec<String> = checked_subnets(parse_ipv4net("192.0.2.0", 24), 26).unwrap()
+            .map(|net| net.to_string())
+            .collect();
+        assert_eq!(4, lines.len());
+        assert_eq!("192.0.2.0/26", lines[0]);
+        assert_eq!("192.0.2.64/26", lines[1]);
+        assert_eq!("192.0.2.128/26", lines[2]);
+        assert_eq!("192.0.2.192/26", lines[3]);
+
+        let lines: Vec<String> = checked_subnets(parse_ipv4net("192.0.2.0", 24), 24).unwrap()
+            .map(|net| net.to_string())
+            .collect();
+        assert_eq!(1, lines.len());
+        assert_eq!("192.0.2.0/24", lines[0]);
+
+        assert_eq!(256, checked_subnets(parse_ipv4net("10.0.0.0", 8), 16).unwrap().count());
+    }
+
+    #[test]
+    fn test_subnets_mixed() {
+        // the most significant host bit becomes a network bit
+        let subnets: Vec<IpNetwork<_>> = checked_subnets(parse_ipv4netm("192.0.0.2", "255.0.255.255"), 25).unwrap()
+            .collect();
+        assert_eq!(2, subnets.len());
+        assert_eq!(parse_ipv4netm("192.0.0.2", "255.128.255.255"), subnets[0]);
+        assert_eq!(parse_ipv4netm("192.128.0.2", "255.128.255.255"), subnets[1]);
+    }
+
+    #[test]
+    fn test_subnets_ipv6() {
+        let lines: Vec<String> = checked_subnets(parse_ipv6net("2001:db8::", 32), 34).unwrap()
+            .map(|net| net.to_string())
+            .collect();
+        assert_eq!(4, lines.len());
+        assert_eq!("2001:db8::/34", lines[0]);
+        assert_eq!("2001:db8:4000::/34", lines[1]);
+        assert_eq!("2001:db8:8000::/34", lines[2]);
+        assert_eq!("2001:db8:c000::/34", lines[3]);
+    }
+
+    #[test]
+    fn test_subnets_errors() {
+        assert_eq!(
+            Some(SubnetsError::PrefixRange(33, 32)),
+            checked_subnets(parse_ipv4net("192.0.2.0", 24), 33).err(),
+        );
+        assert_eq!(
+            Some(SubnetsError::PrefixTooShort(16, 24)),
+            checked_subnets(parse_ipv4net("192.0.2.0", 24), 16).err(),
+        );
+        assert_eq!(
+            Some(SubnetsError::TooManySubnets(17, 16)),
+            checked_subnets(parse_ipv4net("10.0.0.0", 8), 25).err(),
+        );
+        assert_eq!(
+            Some(SubnetsError::TooManySubnets(32, 16)),
+            checked_subnets(parse_ipv6net("2001:db8::", 32), 64).err(),
+        );
+    }
+
+    #[test]
+    fn test_parse_prefix_range() {
+        assert_eq!(Ok(26..=26), parse_prefix_range("26"));
+        assert_eq!(Ok(24..=28), parse_prefix_range("24-28"));
+        assert_eq!(Ok(RangeInclusive::new(28, 24)), parse_prefix_range("28-24"));
+        assert!(parse_prefix_range("24-").is_err());
+        assert!(parse_prefix_range("-28").is_err());
+        assert!(parse_prefix_range("24-28-30").is_err());
+    }
+
+    #[test]
+    fn test_subnets_in_range() {
+        let lines: Vec<String> = checked_subnets_in_range(parse_ipv4net("192.0.2.0", 24), 25..=26).unwrap()
+            .map(|net| net.to_string())
+            .collect();
+        assert_eq!(6, lines.len());
+        assert_eq!("192.0.2.0/25", lines[0]);
+        assert_eq!("192.0.2.128/25", lines[1]);
+        assert_eq!("192.0.2.0/26", lines[2]);
+        assert_eq!("192.0.2.64/26", lines[3]);
+        assert_eq!("192.0.2.128/26", lines[4]);
+        assert_eq!("192.0.2.192/26", lines[5]);
+
+        assert_eq!(
+            Some(SubnetsError::InvertedPrefixRange(26, 25)),
+            checked_subnets_in_range(parse_ipv4net("192.0.2.0", 24), RangeInclusive::new(26, 25)).err(),
+        );
+        assert_eq!(
+            Some(SubnetsError::PrefixRange(33, 32)),
+            checked_subnets_in_range(parse_ipv4net("192.0.2.0", 24), 30..=33).err(),
+        );
+        assert_eq!(
+            Some(SubnetsError::PrefixTooShort(23, 24)),
+            checked_subnets_in_range(parse_ipv4net("192.0.2.0", 24), 23..=25).err(),
+        );
+    }
+}
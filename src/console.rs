@@ -1,8 +1,11 @@
 use std::convert::TryInto;
+use std::io::{self, Write};
 
 #[cfg(feature = "console")]
 use console;
 
+use crate::output::Output;
+
 /// An ANSI color code for color terminals.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Color {
@@ -24,9 +27,64 @@ pub enum Color {
     White,
 }
 
+/// Returns whether colored output to the terminal is enabled.
+#[cfg(feature = "console")]
+pub fn colors_enabled() -> bool {
+    console::colors_enabled()
+}
+
+/// Returns whether colored output to the terminal is enabled.
+#[cfg(not(feature = "console"))]
+pub fn colors_enabled() -> bool {
+    false
+}
+
+/// Returns whether colored output to the terminal's error stream is enabled.
+#[cfg(feature = "console")]
+pub fn colors_enabled_stderr() -> bool {
+    console::colors_enabled_stderr()
+}
+
+/// Returns whether colored output to the terminal's error stream is enabled.
+#[cfg(not(feature = "console"))]
+pub fn colors_enabled_stderr() -> bool {
+    false
+}
+
+/// Writes text wrapped in the escape sequences for the given color.
+#[cfg(feature = "console")]
+pub fn write_colored<W: io::Write + ?Sized>(writer: &mut W, text: &str, color: Color) -> io::Result<()> {
+    let style = console::Style::new().force_styling(true);
+    let style = match color {
+        Color::Black => style.black(),
+        Color::DarkBlue => style.blue(),
+        Color::DarkGreen => style.green(),
+        Color::DarkCyan => style.cyan(),
+        Color::DarkRed => style.red(),
+        Color::DarkMagenta => style.magenta(),
+        Color::DarkYellow => style.yellow(),
+        Color::Gray => style.white(),
+        Color::DarkGray => style.bright().black(),
+        Color::Blue => style.bright().blue(),
+        Color::Green => style.bright().green(),
+        Color::Cyan => style.bright().cyan(),
+        Color::Red => style.bright().red(),
+        Color::Magenta => style.bright().magenta(),
+        Color::Yellow => style.bright().yellow(),
+        Color::White => style.bright().white(),
+    };
+    write!(writer, "{}", style.apply_to(text))
+}
+
+/// Writes text wrapped in the escape sequences for the given color.
+#[cfg(not(feature = "console"))]
+pub fn write_colored<W: io::Write + ?Sized>(writer: &mut W, text: &str, _color: Color) -> io::Result<()> {
+    write!(writer, "{}", text)
+}
+
 /// Outputs text, optionally in a given color, padded to a specific length. Positive padding values
 /// pad at the end, negative at the beginning.
-pub fn write_in_color<S: AsRef<str>>(text: S, color: Option<Color>, pad_to: isize) {
+pub fn write_in_color<S: AsRef<str>>(out: &mut dyn Output, text: S, color: Option<Color>, pad_to: isize) -> io::Result<()> {
     // pad the string
     let mut padded = String::from(text.as_ref());
     let padded_len_isize: isize = padded.len().try_into().unwrap();
@@ -50,33 +108,9 @@ pub fn write_in_color<S: AsRef<str>>(text: S, color: Option<Color>, pad_to: isiz
         }
     }
 
-    if cfg!(feature = "console") {
-        if console::colors_enabled() {
-            if let Some(clr) = color {
-                let styled = console::style(padded);
-                let colored = match clr {
-                    Color::Black => styled.black(),
-                    Color::DarkBlue => styled.blue(),
-                    Color::DarkGreen => styled.green(),
-                    Color::DarkCyan => styled.cyan(),
-                    Color::DarkRed => styled.red(),
-                    Color::DarkMagenta => styled.magenta(),
-                    Color::DarkYellow => styled.yellow(),
-                    Color::Gray => styled.white(),
-                    Color::DarkGray => styled.bright().black(),
-                    Color::Blue => styled.bright().blue(),
-                    Color::Green => styled.bright().green(),
-                    Color::Cyan => styled.bright().cyan(),
-                    Color::Red => styled.bright().red(),
-                    Color::Magenta => styled.bright().magenta(),
-                    Color::Yellow => styled.bright().yellow(),
-                    Color::White => styled.bright().white(),
-                };
-                print!("{}", colored);
-                return;
-            }
-        }
+    if let Some(clr) = color {
+        out.in_color(clr).write_all(padded.as_bytes())
+    } else {
+        out.write_all(padded.as_bytes())
     }
-
-    println!("{}", padded);
 }
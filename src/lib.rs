@@ -0,0 +1,10 @@
+pub mod addr;
+pub mod bit_manip;
+pub mod cidr;
+pub mod cmds;
+pub mod console;
+pub mod net;
+pub mod output;
+pub mod range;
+pub mod run;
+pub mod tagged;
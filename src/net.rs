@@ -1,10 +1,78 @@
+use std::cmp::Ordering;
 use std::convert::TryFrom;
+use std::error::Error;
 use std::fmt;
 
-use crate::addr::IpAddress;
+use crate::addr::{IpAddress, Ipv4Address, Ipv6Address};
 use crate::{bit_manip, cidr};
 
 
+/// An error that occurs when attempting to construct an IP network.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NetworkError {
+    /// The CIDR prefix is longer than the number of bits in an address. The first value is the
+    /// prefix and the second value is the maximum prefix for the address type.
+    PrefixOutOfRange(usize, usize),
+}
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkError::PrefixOutOfRange(prefix, max_prefix)
+                => write!(f, "CIDR prefix {} is out of range (maximum is {})", prefix, max_prefix),
+        }
+    }
+}
+impl Error for NetworkError {
+}
+
+
+/// The form in which the subnet mask of a network is output.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum MaskFormat {
+    /// `network/prefix` if the subnet mask is contiguous, otherwise `network/netmask`.
+    #[default]
+    Cidr,
+
+    /// Always `network/netmask`.
+    Slash,
+
+    /// Always `network netmask`.
+    Space,
+}
+impl MaskFormat {
+    /// Returns the mask format with the given name (`cidr`, `slash` or `space`).
+    pub fn from_name(name: &str) -> Option<MaskFormat> {
+        match name {
+            "cidr" => Some(MaskFormat::Cidr),
+            "slash" => Some(MaskFormat::Slash),
+            "space" => Some(MaskFormat::Space),
+            _ => None,
+        }
+    }
+}
+
+
+/// The relationship between two IP networks in terms of the addresses they contain.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Relationship {
+    /// Both networks contain the same addresses.
+    Equal,
+
+    /// This network contains all addresses of the other network, and more.
+    Superset,
+
+    /// The other network contains all addresses of this network, and more.
+    Subset,
+
+    /// The networks have some addresses in common, but each also contains addresses that the other
+    /// does not. This is only possible with non-contiguous subnet masks.
+    Overlapping,
+
+    /// The networks have no addresses in common.
+    Disjoint,
+}
+
+
 /// An IP network, consisting of a base address and subnet mask.
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct IpNetwork<A: IpAddress> {
@@ -30,19 +98,59 @@ impl<A: IpAddress> IpNetwork<A> {
     }
 
     /// Creates a new IpNetwork from the given IP address and CIDR prefix.
+    ///
+    /// Panics if the CIDR prefix is longer than the number of bits in the address; use
+    /// `try_new_with_prefix` if the prefix has not been validated.
     pub fn new_with_prefix(
         addr: A,
         cidr_prefix: usize,
     ) -> IpNetwork<A> {
+        match Self::try_new_with_prefix(addr, cidr_prefix) {
+            Ok(net) => net,
+            Err(e) => panic!("failed to create network from {}/{}: {}", addr, cidr_prefix, e),
+        }
+    }
+
+    /// Creates a new IpNetwork from the given IP address and CIDR prefix. Returns an error if the
+    /// CIDR prefix is longer than the number of bits in the address.
+    pub fn try_new_with_prefix(
+        addr: A,
+        cidr_prefix: usize,
+    ) -> Result<IpNetwork<A>, NetworkError> {
+        let max_prefix = addr.byte_count() * 8;
+        if cidr_prefix > max_prefix {
+            return Err(NetworkError::PrefixOutOfRange(cidr_prefix, max_prefix));
+        }
+
         let mask_bytes = cidr::subnet_mask_bytes_from_prefix(cidr_prefix, addr.byte_count());
-        let subnet_mask: A = A::from_bytes(&mask_bytes).unwrap();
+        let subnet_mask: A = A::from_bytes(&mask_bytes)
+            .expect("subnet mask from prefix");
         // calculate base address by ANDing address with subnet mask
         let base_addr = addr & subnet_mask;
 
-        IpNetwork {
+        Ok(IpNetwork {
             base_addr,
             subnet_mask,
             cidr_prefix: Some(cidr_prefix),
+        })
+    }
+
+    /// Creates a new IpNetwork spanning exactly the addresses from `start` to `end` (inclusive).
+    /// Returns `None` if the range does not correspond to a single CIDR network.
+    pub fn from_range(
+        start: A,
+        end: A,
+    ) -> Option<IpNetwork<A>> {
+        // the bits in which start and end differ must be exactly the host bits of the network
+        let host_bits = start ^ end;
+        let host_bit_count = usize::try_from(host_bits.count_ones()).unwrap();
+        let cidr_prefix = start.byte_count() * 8 - host_bit_count;
+        let net = IpNetwork::new_with_prefix(start, cidr_prefix);
+
+        if net.base_addr == start && net.subnet_mask.bitwise_negate() == host_bits {
+            Some(net)
+        } else {
+            None
         }
     }
 
@@ -61,12 +169,13 @@ impl<A: IpAddress> IpNetwork<A> {
     }
 
     /// Creates a new IpNetwork from the given IP address and CIDR prefix. Returns `None` if `addr`
-    /// is not the base address of the specified subnet.
+    /// is not the base address of the specified subnet or if the CIDR prefix is longer than the
+    /// number of bits in the address.
     pub fn new_with_prefix_strict(
         addr: A,
         cidr_prefix: usize,
     ) -> Option<IpNetwork<A>> {
-        let net = Self::new_with_prefix(addr, cidr_prefix);
+        let net = Self::try_new_with_prefix(addr, cidr_prefix).ok()?;
         if net.base_addr() == addr {
             Some(net)
         } else {
@@ -84,6 +193,12 @@ impl<A: IpAddress> IpNetwork<A> {
     /// subnet mask with network and host bits interspersed).
     pub fn cidr_prefix(&self) -> Option<usize> { self.cidr_prefix }
 
+    /// The CIDR prefix of this IP network, or the reason why the subnet mask cannot be expressed as
+    /// one.
+    pub fn prefix_or_reason(&self) -> Result<usize, &'static str> {
+        self.cidr_prefix.ok_or("non-contiguous subnet mask")
+    }
+
     /// The Cisco wildcard of this IP network, i.e. the bitwise complement of the subnet mask.
     pub fn cisco_wildcard(&self) -> A {
         self.subnet_mask.bitwise_negate()
@@ -109,6 +224,32 @@ impl<A: IpAddress> IpNetwork<A> {
         addr_count - 2
     }
 
+    /// The number of subnets with the given CIDR prefix that this network can be divided into, e.g.
+    /// 65536 /64 networks in a /48 network. Returns `None` if this network has a mixed subnet mask,
+    /// if the prefix is shorter than this network's prefix or longer than an address, or if the
+    /// count does not fit into 128 bits.
+    pub fn subnet_count_at(&self, prefix: usize) -> Option<u128> {
+        let cidr_prefix = self.cidr_prefix?;
+        if prefix < cidr_prefix || prefix > self.subnet_mask.byte_count() * 8 {
+            return None;
+        }
+        let additional_bits = u32::try_from(prefix - cidr_prefix).unwrap();
+        1u128.checked_shl(additional_bits)
+    }
+
+    /// Whether this network contains more than `n` addresses. Unlike `address_count`, this does not
+    /// require arbitrary-precision arithmetic.
+    pub fn address_count_exceeds(&self, n: u128) -> bool {
+        // the network contains 2**host_bits addresses
+        let host_bits = self.subnet_mask.count_zeros();
+        if n == 0 {
+            // every network contains at least one address
+            return true;
+        }
+        // 2**host_bits > n if and only if host_bits > floor(log2(n))
+        host_bits > n.ilog2()
+    }
+
     /// The address of the first host in this network, or `None` if the network has too few
     /// addresses to have even a single host address.
     pub fn first_host_addr(&self) -> Option<A> {
@@ -143,14 +284,7 @@ impl<A: IpAddress> IpNetwork<A> {
         }
 
         let unraveled_base = bit_manip::unravel_address(self.base_addr, self.subnet_mask);
-        let hca_bytes = cidr::subnet_mask_bytes_from_prefix(
-            self.base_addr.to_bytes().len()*8 - host_bits_available,
-            self.base_addr.byte_count(),
-        );
-        let host_count_address = A::from_bytes(&hca_bytes)
-            .expect("subnet mask from prefix")
-            .bitwise_negate();
-        let unraveled_broadcast = unraveled_base.add_addr(&host_count_address)?;
+        let unraveled_broadcast = unraveled_base.add_addr(&self.host_all_ones())?;
         Some(bit_manip::weave_address(unraveled_broadcast, self.subnet_mask))
     }
 
@@ -169,14 +303,7 @@ impl<A: IpAddress> IpNetwork<A> {
         }
 
         let unraveled_base = bit_manip::unravel_address(self.base_addr, self.subnet_mask);
-        let hca_bytes = cidr::subnet_mask_bytes_from_prefix(
-            self.base_addr.to_bytes().len()*8 - host_bits_available,
-            self.base_addr.byte_count(),
-        );
-        let host_count_address = A::from_bytes(&hca_bytes)
-            .expect("subnet mask from prefix")
-            .bitwise_negate();
-        let unraveled_broadcast = unraveled_base.add_addr(&host_count_address)?;
+        let unraveled_broadcast = unraveled_base.add_addr(&self.host_all_ones())?;
         let unraveled_last_host = unraveled_broadcast.subtract_offset(1)?;
         Some(bit_manip::weave_address(unraveled_last_host, self.subnet_mask))
     }
@@ -184,21 +311,43 @@ impl<A: IpAddress> IpNetwork<A> {
     /// The base address of the network immediately following this one, or `None` if this network
     /// borders the end of the address space.
     pub fn next_subnet_base_addr(&self) -> Option<A> {
+        let unraveled_base = bit_manip::unravel_address(self.base_addr, self.subnet_mask);
+        let unraveled_broadcast = unraveled_base.add_addr(&self.host_all_ones())?;
+        let unraveled_next_base = unraveled_broadcast.add_offset(1)?;
+        Some(bit_manip::weave_address(unraveled_next_base, self.subnet_mask))
+    }
+
+    /// The base address of the network immediately preceding this one, or `None` if this network
+    /// borders the start of the address space.
+    pub fn previous_subnet_base_addr(&self) -> Option<A> {
+        let unraveled_base = bit_manip::unravel_address(self.base_addr, self.subnet_mask);
+        let unraveled_previous_broadcast = unraveled_base.subtract_offset(1)?;
+        let unraveled_previous_base = unraveled_previous_broadcast.subtract_addr(&self.host_all_ones())?;
+        Some(bit_manip::weave_address(unraveled_previous_base, self.subnet_mask))
+    }
+
+    /// The address whose lowest bits, as many as this network has host bits, are ones and whose other
+    /// bits are zeroes. This is the offset between the base address and the broadcast address of
+    /// this network once the host bits have been unraveled to the end of the address.
+    pub fn host_all_ones(&self) -> A {
         let host_bits_available: usize = self.cisco_wildcard().to_bytes()
             .iter()
             .map(|b| usize::try_from(b.count_ones()).unwrap())
             .sum();
-        let unraveled_base = bit_manip::unravel_address(self.base_addr, self.subnet_mask);
         let hca_bytes = cidr::subnet_mask_bytes_from_prefix(
             self.base_addr.to_bytes().len()*8 - host_bits_available,
             self.base_addr.byte_count(),
         );
-        let host_count_address = A::from_bytes(&hca_bytes)
+        A::from_bytes(&hca_bytes)
             .expect("subnet mask from prefix")
-            .bitwise_negate();
-        let unraveled_broadcast = unraveled_base.add_addr(&host_count_address)?;
-        let unraveled_next_base = unraveled_broadcast.add_offset(1)?;
-        Some(bit_manip::weave_address(unraveled_next_base, self.subnet_mask))
+            .bitwise_negate()
+    }
+
+    /// Whether this network consists of exactly two addresses, e.g. a /31 IPv4 or /127 IPv6 network.
+    /// Such networks are commonly used for point-to-point links (RFC 3021, RFC 6164), where both
+    /// addresses are assigned to hosts.
+    pub fn is_point_to_point(&self) -> bool {
+        self.cisco_wildcard().count_ones() == 1
     }
 
     /// The last address of the network, which is the broadcast address or, if there is no broadcast
@@ -207,6 +356,49 @@ impl<A: IpAddress> IpNetwork<A> {
         self.broadcast_addr().unwrap_or(self.base_addr)
     }
 
+    /// Returns an iterator over the boundary addresses of the network: the base address and, if the
+    /// network has one, the broadcast address.
+    pub fn boundary_addresses(&self) -> impl Iterator<Item = A> {
+        std::iter::once(self.base_addr)
+            .chain(self.broadcast_addr())
+    }
+
+    /// Returns the addresses of the network that cannot be assigned to hosts: the base (network)
+    /// address and the broadcast address. Networks with fewer than four addresses (e.g. /31 and /32
+    /// in IPv4) have no host addresses in the usual sense, so they reserve none of their addresses.
+    pub fn reserved_addresses(&self) -> Vec<A> {
+        if self.first_host_addr().is_none() {
+            return Vec::new();
+        }
+        self.boundary_addresses().collect()
+    }
+
+    /// The address halfway between the base address and the last address of the network, rounded
+    /// down. If the subnet mask is not contiguous, the midpoint is calculated by counting only the
+    /// host bits.
+    pub fn midpoint(&self) -> A {
+        let unraveled_base = bit_manip::unravel_address(self.base_addr, self.subnet_mask);
+        let unraveled_last = bit_manip::unravel_address(self.last_addr_of_subnet(), self.subnet_mask);
+        let unraveled_midpoint = average_addresses(unraveled_base, unraveled_last);
+        bit_manip::weave_address(unraveled_midpoint, self.subnet_mask)
+    }
+
+    /// Returns the network in the form `base/prefix`, or `None` if the subnet mask is not
+    /// contiguous and therefore cannot be represented as a CIDR prefix.
+    pub fn to_cidr_string(&self) -> Option<String> {
+        self.cidr_prefix
+            .map(|prefix| format!("{}/{}", self.base_addr, prefix))
+    }
+
+    /// Returns the network as a string with the subnet mask in the given format.
+    pub fn to_string_with_mask(self, format: MaskFormat) -> String {
+        match format {
+            MaskFormat::Cidr => self.to_string(),
+            MaskFormat::Slash => format!("{}/{}", self.base_addr, self.subnet_mask),
+            MaskFormat::Space => format!("{} {}", self.base_addr, self.subnet_mask),
+        }
+    }
+
     /// Returns whether this network contains the given address.
     pub fn contains(&self, addr: &A) -> bool {
         (*addr & self.subnet_mask) == self.base_addr
@@ -230,6 +422,73 @@ impl<A: IpAddress> IpNetwork<A> {
         other.is_superset_of(self)
     }
 
+    /// Returns the relationship of this network to another network, e.g. `Relationship::Superset` if
+    /// this network contains the other network.
+    pub fn relationship(&self, other: &IpNetwork<A>) -> Relationship {
+        match (self.is_superset_of(other), self.is_subset_of(other)) {
+            (true, true) => Relationship::Equal,
+            (true, false) => Relationship::Superset,
+            (false, true) => Relationship::Subset,
+            (false, false) => if self.intersects(other) {
+                Relationship::Overlapping
+            } else {
+                Relationship::Disjoint
+            },
+        }
+    }
+
+    /// Compares this network to another network in routing table order: networks with fewer network
+    /// bits (i.e. more general routes) come first, and networks with the same number of network
+    /// bits are ordered by their base address and then by their subnet mask. Unlike the `Ord`
+    /// implementation, which sorts by base address first, this places e.g. `0.0.0.0/0` before
+    /// `0.0.0.0/24`.
+    pub fn route_order(&self, other: &IpNetwork<A>) -> Ordering {
+        self.subnet_mask.count_ones().cmp(&other.subnet_mask.count_ones())
+            .then_with(|| self.base_addr.cmp(&other.base_addr))
+            .then_with(|| other.subnet_mask.cmp(&self.subnet_mask))
+    }
+
+    /// Compares this network to another network in numeric order: networks are ordered by the
+    /// numeric value of their base address (see `IpAddress::numeric_cmp`) and then by the numeric
+    /// value of their subnet mask. This is a total order, so sorting by it is deterministic; in
+    /// particular, a covering aggregate comes before the host routes and other subnets sharing its
+    /// base address.
+    pub fn numeric_order(&self, other: &IpNetwork<A>) -> Ordering {
+        self.base_addr.numeric_cmp(&other.base_addr)
+            .then_with(|| self.subnet_mask.numeric_cmp(&other.subnet_mask))
+    }
+
+    /// Returns whether this network and another network are adjacent, i.e. one of them begins
+    /// immediately after the other one ends. A network bordering the end of the address space is
+    /// not adjacent to any network following it; in particular, adjacency does not wrap around to
+    /// the beginning of the address space.
+    pub fn is_adjacent_to(&self, other: &IpNetwork<A>) -> bool {
+        let self_then_other = self.next_subnet_base_addr()
+            .map(|next_base| next_base == other.base_addr)
+            .unwrap_or(false);
+        let other_then_self = other.next_subnet_base_addr()
+            .map(|next_base| next_base == self.base_addr)
+            .unwrap_or(false);
+        self_then_other || other_then_self
+    }
+
+    /// Returns the smallest network with a CIDR prefix that contains both this network and another
+    /// network.
+    pub fn common_supernet(&self, other: &IpNetwork<A>) -> IpNetwork<A> {
+        // the network bits of the supernet must be network bits in both networks and have the same
+        // value in both base addresses
+        let common_bits = self.subnet_mask & other.subnet_mask & (self.base_addr ^ other.base_addr).bitwise_negate();
+        let mut prefix = 0;
+        for b in common_bits.to_bytes() {
+            let leading_ones = usize::try_from(b.leading_ones()).unwrap();
+            prefix += leading_ones;
+            if leading_ones < 8 {
+                break;
+            }
+        }
+        IpNetwork::new_with_prefix(self.base_addr, prefix)
+    }
+
     /// Returns whether this network and another network intersect, i.e. there is at least one
     /// address that is contained in both networks.
     pub fn intersects(&self, other: &IpNetwork<A>) -> bool {
@@ -241,7 +500,217 @@ impl<A: IpAddress> IpNetwork<A> {
         // thisFirst <= otherLast && otherFirst <= thisLast
         self_first <= other_last && other_first <= self_last
     }
+
+    /// Returns the subnet mask obtained by turning the most significant host bits of this network's
+    /// subnet mask into network bits until the mask has `net_bits` network bits. Returns `None` if
+    /// this network already has more network bits or if `net_bits` exceeds the size of the address.
+    pub fn extended_subnet_mask(&self, net_bits: usize) -> Option<A> {
+        let current_net_bits = usize::try_from(self.subnet_mask.count_ones()).unwrap();
+        let max_net_bits = self.subnet_mask.byte_count() * 8;
+        if net_bits < current_net_bits || net_bits > max_net_bits {
+            return None;
+        }
+
+        // the network bits come first in the unraveled subnet mask; weaving it puts them back
+        let unraveled_mask_bytes = cidr::subnet_mask_bytes_from_prefix(net_bits, self.subnet_mask.byte_count());
+        let unraveled_mask = A::from_bytes(&unraveled_mask_bytes)
+            .expect("subnet mask from prefix");
+        Some(bit_manip::weave_address(unraveled_mask, self.subnet_mask))
+    }
+
+    /// Returns an iterator over the subnets of this network that have the given (potentially mixed)
+    /// subnet mask. The iterator is empty unless all network bits of this network are also network
+    /// bits in the new subnet mask.
+    pub fn subnets(&self, new_subnet_mask: A) -> SubnetIter<A> {
+        SubnetIter::new(*self, new_subnet_mask)
+    }
+
+    /// Splits this network into its two halves by turning its most significant host bit into a
+    /// network bit. Returns `None` if this network has no host bits.
+    pub fn split_in_half(&self) -> Option<(IpNetwork<A>, IpNetwork<A>)> {
+        let net_bits = usize::try_from(self.subnet_mask.count_ones()).unwrap();
+        let new_subnet_mask = self.extended_subnet_mask(net_bits + 1)?;
+        let mut halves = self.subnets(new_subnet_mask);
+        let lower_half = halves.next().expect("lower half");
+        let upper_half = halves.next().expect("upper half");
+        Some((lower_half, upper_half))
+    }
+
+    /// Returns an iterator over the subnets of this network with the given number of network bits
+    /// that contain at least one address between `start` and `end` (inclusive). Iteration stops as
+    /// soon as the subnets have moved past `end`.
+    pub fn iter_subnets_between(&self, new_prefix: usize, start: A, end: A) -> impl Iterator<Item = IpNetwork<A>> {
+        self.extended_subnet_mask(new_prefix)
+            .map(|new_subnet_mask| self.subnets(new_subnet_mask))
+            .into_iter()
+            .flatten()
+            .take_while(move |net| net.base_addr() <= end)
+            // the base address is known to be <= end; check the other side of the intersection
+            .filter(move |net| net.last_addr_of_subnet() >= start)
+    }
+}
+
+impl IpNetwork<Ipv4Address> {
+    /// The number of addresses in this network. Unlike `address_count`, this does not require
+    /// arbitrary-precision arithmetic, as the number of IPv4 addresses always fits into 64 bits.
+    pub fn address_count_u64(&self) -> u64 {
+        1u64 << self.cisco_wildcard().count_ones()
+    }
+
+    /// The number of host addresses, i.e. non-network and non-broadcast addresses, in this network.
+    /// Unlike `host_count`, this does not require arbitrary-precision arithmetic.
+    pub fn host_count_i64(&self) -> i64 {
+        i64::try_from(self.address_count_u64()).unwrap() - 2
+    }
+
+    /// Returns whether this network has exactly the size given by the former address class of its
+    /// base address (see `Ipv4Address::classful_prefix`). Networks of class D and E addresses are
+    /// never classful.
+    pub fn is_classful(&self) -> bool {
+        self.cidr_prefix.is_some() && self.cidr_prefix == self.base_addr.classful_prefix()
+    }
+
+    /// Returns whether this network contains the given address, which is given as a standard
+    /// library type.
+    #[cfg(feature = "std-net")]
+    pub fn contains_std(&self, addr: std::net::Ipv4Addr) -> bool {
+        self.contains(&addr.into())
+    }
+}
+
+impl IpNetwork<Ipv6Address> {
+    /// The number of host addresses, i.e. non-network and non-broadcast addresses, in this network,
+    /// if the network has at most 64 host bits. Networks with fewer than two addresses have zero
+    /// host addresses. Unlike `host_count`, this does not require arbitrary-precision arithmetic.
+    pub fn host_count_u64(&self) -> Option<u64> {
+        let host_bits = self.cisco_wildcard().count_ones();
+        if host_bits > 64 {
+            return None;
+        }
+        let address_count = 1u128 << host_bits;
+        Some(u64::try_from(address_count.saturating_sub(2)).unwrap())
+    }
+
+    /// Returns whether this network contains the given address, which is given as a standard
+    /// library type.
+    #[cfg(feature = "std-net")]
+    pub fn contains_std(&self, addr: std::net::Ipv6Addr) -> bool {
+        self.contains(&addr.into())
+    }
+}
+
+/// An iterator over the equally-sized subnets of a network, in ascending order of their base
+/// addresses.
+pub struct SubnetIter<A: IpAddress> {
+    base_addr: A,
+    new_subnet_mask: A,
+    new_net_bits: A,
+    step: Option<A>,
+    counter: Option<A>,
+}
+impl<A: IpAddress> SubnetIter<A> {
+    fn new(network: IpNetwork<A>, new_subnet_mask: A) -> Self {
+        // the bits that are host bits in the network but network bits in the subnets
+        let new_net_bits = new_subnet_mask & network.subnet_mask().bitwise_negate();
+        let byte_count = new_subnet_mask.byte_count();
+
+        // the counter enumerates the values of the new network bits in its topmost bits
+        let new_net_bit_count = usize::try_from(new_net_bits.count_ones()).unwrap();
+        let step = if new_net_bit_count == 0 {
+            None
+        } else {
+            let above_bytes = cidr::subnet_mask_bytes_from_prefix(new_net_bit_count - 1, byte_count);
+            let including_bytes = cidr::subnet_mask_bytes_from_prefix(new_net_bit_count, byte_count);
+            let above = A::from_bytes(&above_bytes).expect("subnet mask from prefix");
+            let including = A::from_bytes(&including_bytes).expect("subnet mask from prefix");
+            Some(above ^ including)
+        };
+
+        let counter = if new_subnet_mask & network.subnet_mask() != network.subnet_mask() {
+            // that's not a subnet
+            None
+        } else {
+            Some(A::from_bytes(&vec![0u8; byte_count]).expect("zero address from bytes"))
+        };
+
+        Self {
+            base_addr: network.base_addr(),
+            new_subnet_mask,
+            new_net_bits,
+            step,
+            counter,
+        }
+    }
+}
+impl<A: IpAddress> Iterator for SubnetIter<A> {
+    type Item = IpNetwork<A>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let counter = self.counter?;
+
+        // distribute the counter's bits to the new network bits
+        let new_net_bits_value = bit_manip::weave_address(counter, self.new_net_bits);
+        let new_net = IpNetwork::new_with_mask(self.base_addr | new_net_bits_value, self.new_subnet_mask);
+
+        // once the counter overflows, we're done
+        self.counter = self.step
+            .and_then(|step| counter.add_addr(&step));
+
+        Some(new_net)
+    }
+}
+
+/// Returns the smallest network with a CIDR prefix that contains all the given networks, or `None`
+/// if no networks are given. A single network is returned unchanged.
+pub fn covering_aggregate<A: IpAddress>(nets: &[IpNetwork<A>]) -> Option<IpNetwork<A>> {
+    nets.iter()
+        .copied()
+        .reduce(|aggregate, net| aggregate.common_supernet(&net))
+}
+
+/// Returns the first of the `existing` networks that intersects with the `candidate` network, or
+/// `None` if the candidate does not intersect with any of them.
+pub fn overlaps_any<A: IpAddress>(candidate: IpNetwork<A>, existing: &[IpNetwork<A>]) -> Option<IpNetwork<A>> {
+    existing.iter()
+        .copied()
+        .find(|net| net.intersects(&candidate))
+}
+
+/// Whether the given address is contained in the network with the given base address and CIDR
+/// prefix. Host bits in the base address are ignored. If the CIDR prefix is longer than the number
+/// of bits in the address, there is no such network and the address is not contained in it.
+pub fn addr_in_prefix<A: IpAddress>(addr: A, base: A, prefix: usize) -> bool {
+    IpNetwork::try_new_with_prefix(base, prefix)
+        .map(|net| net.contains(&addr))
+        .unwrap_or(false)
+}
+
+/// Calculates the average of two addresses, rounded down. The sum is calculated with an additional
+/// carry bit, so this cannot overflow even at the end of the address space.
+fn average_addresses<A: IpAddress>(one: A, other: A) -> A {
+    let one_bytes = one.to_bytes();
+    let other_bytes = other.to_bytes();
+    let mut sum_bytes = vec![0u8; one_bytes.len()];
+
+    // add, starting with the least significant byte
+    let mut carry = false;
+    for i in (0..sum_bytes.len()).rev() {
+        let sum = u16::from(one_bytes[i]) + u16::from(other_bytes[i]) + u16::from(carry);
+        sum_bytes[i] = (sum & 0xFF) as u8;
+        carry = sum > 0xFF;
+    }
+
+    // shift right by one, starting with the most significant byte, shifting the carry bit in
+    for b in &mut sum_bytes {
+        let shifted_out = (*b & 0x01) != 0;
+        *b = (*b >> 1) | if carry { 0x80 } else { 0x00 };
+        carry = shifted_out;
+    }
+
+    A::from_bytes(&sum_bytes)
+        .expect("same number of bytes")
 }
+
 impl<A: IpAddress> fmt::Display for IpNetwork<A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let Some(prefix) = self.cidr_prefix {
@@ -257,13 +726,14 @@ pub mod test {
     use super::*;
     #[cfg(feature = "num-bigint")]
     use num_bigint::{BigInt, BigUint};
-    use crate::addr::{Ipv4Address, Ipv6Address};
 
     // utility functions for network-related unit tests
     // (unit tests for this module follow)
     pub fn parse_ipv4(s: &str) -> Ipv4Address { s.parse().unwrap() }
     pub fn parse_ipv6(s: &str) -> Ipv6Address { s.parse().unwrap() }
+    #[cfg(feature = "num-bigint")]
     pub fn parse_bigint(s: &str) -> BigInt { s.parse().unwrap() }
+    #[cfg(feature = "num-bigint")]
     pub fn parse_biguint(s: &str) -> BigUint { s.parse().unwrap() }
     pub fn parse_ipv4net(addr_str: &str, cidr: usize) -> IpNetwork<Ipv4Address> {
         let base_addr = addr_str.parse().expect("IP address is invalid");
@@ -309,7 +779,8 @@ pub mod test {
         assert!(net.contains(&net.last_host_addr().unwrap()));
         assert!(net.contains(&net.broadcast_addr().unwrap()));
         assert!(!net.contains(&net.next_subnet_base_addr().unwrap()));
-        if cfg!(feature = "num-bigint") {
+        #[cfg(feature = "num-bigint")]
+        {
             assert_eq!(BigUint::from(16777216u32), net.address_count());
             assert_eq!(BigInt::from(16777214), net.host_count());
         }
@@ -333,7 +804,8 @@ pub mod test {
         assert!(net.contains(&net.last_host_addr().unwrap()));
         assert!(net.contains(&net.broadcast_addr().unwrap()));
         assert!(!net.contains(&net.next_subnet_base_addr().unwrap()));
-        if cfg!(feature = "num-bigint") {
+        #[cfg(feature = "num-bigint")]
+        {
             assert_eq!(BigUint::from(65536u32), net.address_count());
             assert_eq!(BigInt::from(65534), net.host_count());
         }
@@ -355,7 +827,8 @@ pub mod test {
         assert!(!net.contains(&parse_ipv4("127.0.0.0")));
         assert!(!net.contains(&parse_ipv4("127.0.0.2")));
         assert!(!net.contains(&net.next_subnet_base_addr().unwrap()));
-        if cfg!(feature = "num-bigint") {
+        #[cfg(feature = "num-bigint")]
+        {
             assert_eq!(BigUint::from(1u32), net.address_count());
             assert_eq!(BigInt::from(-1), net.host_count());
         }
@@ -376,7 +849,8 @@ pub mod test {
         assert!(net.contains(&net.base_addr()));
         assert!(net.contains(&net.broadcast_addr().unwrap()));
         assert!(!net.contains(&net.next_subnet_base_addr().unwrap()));
-        if cfg!(feature = "num-bigint") {
+        #[cfg(feature = "num-bigint")]
+        {
             assert_eq!(BigUint::from(2u32), net.address_count());
             assert_eq!(BigInt::from(0), net.host_count());
         }
@@ -399,7 +873,8 @@ pub mod test {
         assert!(net.contains(&parse_ipv4("31.41.59.26")));
         assert!(net.contains(&net.last_host_addr().unwrap()));
         assert!(net.contains(&net.broadcast_addr().unwrap()));
-        if cfg!(feature = "num-bigint") {
+        #[cfg(feature = "num-bigint")]
+        {
             assert_eq!(BigUint::from(4294967296u64), net.address_count());
             assert_eq!(BigInt::from(4294967294u32), net.host_count());
         }
@@ -426,7 +901,8 @@ pub mod test {
         assert!(net.contains(&net.last_host_addr().unwrap()));
         assert!(net.contains(&net.broadcast_addr().unwrap()));
         assert!(!net.contains(&net.next_subnet_base_addr().unwrap()));
-        if cfg!(feature = "num-bigint") {
+        #[cfg(feature = "num-bigint")]
+        {
             assert_eq!(parse_biguint("332306998946228968225951765070086144"), net.address_count());
             assert_eq!(parse_bigint("332306998946228968225951765070086142"), net.host_count());
         }
@@ -450,7 +926,8 @@ pub mod test {
         assert!(net.contains(&net.last_host_addr().unwrap()));
         assert!(net.contains(&net.broadcast_addr().unwrap()));
         assert!(!net.contains(&net.next_subnet_base_addr().unwrap()));
-        if cfg!(feature = "num-bigint") {
+        #[cfg(feature = "num-bigint")]
+        {
             assert_eq!(parse_biguint("18446744073709551616"), net.address_count());
             assert_eq!(parse_bigint("18446744073709551614"), net.host_count());
         }
@@ -470,7 +947,8 @@ pub mod test {
         assert_eq!(Some(parse_ipv6("::2")), net.next_subnet_base_addr());
         assert!(net.contains(&net.base_addr()));
         assert!(!net.contains(&net.next_subnet_base_addr().unwrap()));
-        if cfg!(feature = "num-bigint") {
+        #[cfg(feature = "num-bigint")]
+        {
             assert_eq!(BigUint::from(1u32), net.address_count());
             assert_eq!(BigInt::from(-1), net.host_count());
         }
@@ -491,7 +969,8 @@ pub mod test {
         assert!(net.contains(&net.base_addr()));
         assert!(net.contains(&net.broadcast_addr().unwrap()));
         assert!(!net.contains(&net.next_subnet_base_addr().unwrap()));
-        if cfg!(feature = "num-bigint") {
+        #[cfg(feature = "num-bigint")]
+        {
             assert_eq!(BigUint::from(2u32), net.address_count());
             assert_eq!(BigInt::from(0), net.host_count());
         }
@@ -514,7 +993,8 @@ pub mod test {
         assert!(net.contains(&parse_ipv6("1234:3141:1234:5926:5358:1234:9793:1234")));
         assert!(net.contains(&net.last_host_addr().unwrap()));
         assert!(net.contains(&net.broadcast_addr().unwrap()));
-        if cfg!(feature = "num-bigint") {
+        #[cfg(feature = "num-bigint")]
+        {
             assert_eq!(parse_biguint("340282366920938463463374607431768211456"), net.address_count());
             assert_eq!(parse_bigint("340282366920938463463374607431768211454"), net.host_count());
         }
@@ -550,6 +1030,32 @@ pub mod test {
         assert_eq!(Some(10), net.cidr_prefix);
     }
 
+    #[test]
+    fn test_try_new_with_prefix() {
+        assert_eq!(Ok(parse_ipv4net("192.0.2.1", 32)), IpNetwork::try_new_with_prefix(parse_ipv4("192.0.2.1"), 32));
+        assert_eq!(
+            Err(NetworkError::PrefixOutOfRange(33, 32)),
+            IpNetwork::try_new_with_prefix(parse_ipv4("192.0.2.1"), 33),
+        );
+        assert_eq!(
+            Err(NetworkError::PrefixOutOfRange(1000, 32)),
+            IpNetwork::try_new_with_prefix(parse_ipv4("192.0.2.1"), 1000),
+        );
+        assert_eq!(Ok(parse_ipv6net("2001:db8::1", 128)), IpNetwork::try_new_with_prefix(parse_ipv6("2001:db8::1"), 128));
+        assert_eq!(
+            Err(NetworkError::PrefixOutOfRange(129, 128)),
+            IpNetwork::try_new_with_prefix(parse_ipv6("2001:db8::1"), 129),
+        );
+
+        assert_eq!(None, IpNetwork::new_with_prefix_strict(parse_ipv4("192.0.2.1"), 33));
+    }
+
+    #[test]
+    #[should_panic(expected = "CIDR prefix 33 is out of range")]
+    fn test_new_with_prefix_out_of_range() {
+        IpNetwork::new_with_prefix(parse_ipv4("192.0.2.1"), 33);
+    }
+
     #[test]
     fn test_ipv4_new_with_mask_strict() {
         // CIDR mask
@@ -731,4 +1237,519 @@ pub mod test {
         assert_eq!(parse_ipv6("ffc0::"), net.subnet_mask());
         assert_eq!(Some(10), net.cidr_prefix);
     }
+
+    #[test]
+    fn test_iter_subnets_between() {
+        let net = parse_ipv4net("10.1.0.0", 16);
+
+        let subnets: Vec<IpNetwork<Ipv4Address>> = net.iter_subnets_between(
+            24,
+            parse_ipv4("10.1.5.17"),
+            parse_ipv4("10.1.8.0"),
+        )
+            .collect();
+        assert_eq!(4, subnets.len());
+        assert_eq!(parse_ipv4net("10.1.5.0", 24), subnets[0]);
+        assert_eq!(parse_ipv4net("10.1.6.0", 24), subnets[1]);
+        assert_eq!(parse_ipv4net("10.1.7.0", 24), subnets[2]);
+        assert_eq!(parse_ipv4net("10.1.8.0", 24), subnets[3]);
+
+        // window reaching beyond the network
+        let subnets: Vec<IpNetwork<Ipv4Address>> = net.iter_subnets_between(
+            24,
+            parse_ipv4("10.1.254.0"),
+            parse_ipv4("10.2.3.0"),
+        )
+            .collect();
+        assert_eq!(2, subnets.len());
+        assert_eq!(parse_ipv4net("10.1.254.0", 24), subnets[0]);
+        assert_eq!(parse_ipv4net("10.1.255.0", 24), subnets[1]);
+
+        // window outside the network
+        let subnets: Vec<IpNetwork<Ipv4Address>> = net.iter_subnets_between(
+            24,
+            parse_ipv4("10.2.0.0"),
+            parse_ipv4("10.3.0.0"),
+        )
+            .collect();
+        assert_eq!(0, subnets.len());
+
+        let net = parse_ipv6net("2001:db8::", 32);
+        let subnets: Vec<IpNetwork<Ipv6Address>> = net.iter_subnets_between(
+            48,
+            parse_ipv6("2001:db8:ffff::"),
+            parse_ipv6("2001:db9::"),
+        )
+            .collect();
+        assert_eq!(1, subnets.len());
+        assert_eq!(parse_ipv6net("2001:db8:ffff::", 48), subnets[0]);
+    }
+
+    #[test]
+    fn test_common_supernet() {
+        assert_eq!(
+            parse_ipv4net("192.0.2.0", 24),
+            parse_ipv4net("192.0.2.0", 25).common_supernet(&parse_ipv4net("192.0.2.128", 25)),
+        );
+        assert_eq!(
+            parse_ipv4net("192.0.0.0", 22),
+            parse_ipv4net("192.0.3.0", 24).common_supernet(&parse_ipv4net("192.0.0.0", 24)),
+        );
+        assert_eq!(
+            parse_ipv4net("10.0.0.0", 8),
+            parse_ipv4net("10.0.0.0", 8).common_supernet(&parse_ipv4net("10.1.2.3", 32)),
+        );
+        assert_eq!(
+            parse_ipv4net("0.0.0.0", 0),
+            parse_ipv4net("10.0.0.0", 8).common_supernet(&parse_ipv4net("192.0.2.0", 24)),
+        );
+        assert_eq!(
+            parse_ipv6net("2001:db8::", 32),
+            parse_ipv6net("2001:db8:1::", 48).common_supernet(&parse_ipv6net("2001:db8:8000::", 33)),
+        );
+
+        // the result always has a CIDR prefix
+        assert_eq!(
+            parse_ipv4net("192.0.0.0", 16),
+            parse_ipv4netm("192.0.0.2", "255.255.0.255").common_supernet(&parse_ipv4net("192.0.2.0", 24)),
+        );
+    }
+
+    #[test]
+    fn test_covering_aggregate() {
+        assert_eq!(None, covering_aggregate::<Ipv4Address>(&[]));
+        assert_eq!(Some(parse_ipv4net("192.0.2.0", 24)), covering_aggregate(&[parse_ipv4net("192.0.2.0", 24)]));
+        assert_eq!(
+            Some(parse_ipv4net("198.51.96.0", 20)),
+            covering_aggregate(&[
+                parse_ipv4net("198.51.100.0", 24),
+                parse_ipv4net("198.51.96.0", 24),
+                parse_ipv4net("198.51.111.0", 24),
+            ]),
+        );
+    }
+
+    #[test]
+    fn test_overlaps_any() {
+        let existing = [
+            parse_ipv4net("192.0.2.0", 25),
+            parse_ipv4net("198.51.100.0", 24),
+            parse_ipv4net("203.0.113.0", 24),
+        ];
+        assert_eq!(Some(existing[1]), overlaps_any(parse_ipv4net("198.51.100.128", 26), &existing));
+        assert_eq!(Some(existing[1]), overlaps_any(parse_ipv4net("198.51.0.0", 16), &existing));
+        assert_eq!(Some(existing[0]), overlaps_any(parse_ipv4net("0.0.0.0", 0), &existing));
+        assert_eq!(None, overlaps_any(parse_ipv4net("192.0.2.128", 25), &existing));
+        assert_eq!(None, overlaps_any(parse_ipv4net("192.0.2.1", 32), &[]));
+    }
+
+    #[test]
+    fn test_addr_in_prefix() {
+        let base = parse_ipv4("192.0.2.0");
+        assert!(addr_in_prefix(parse_ipv4("192.0.2.0"), base, 24));
+        assert!(addr_in_prefix(parse_ipv4("192.0.2.77"), base, 24));
+        assert!(addr_in_prefix(parse_ipv4("192.0.2.255"), base, 24));
+        assert!(!addr_in_prefix(parse_ipv4("192.0.3.0"), base, 24));
+        assert!(!addr_in_prefix(parse_ipv4("192.0.1.255"), base, 24));
+
+        // host bits in the base address are ignored
+        assert!(addr_in_prefix(parse_ipv4("192.0.2.77"), parse_ipv4("192.0.2.1"), 24));
+        assert!(addr_in_prefix(parse_ipv4("198.51.100.1"), base, 0));
+        assert!(addr_in_prefix(parse_ipv6("2001:db8::1"), parse_ipv6("2001:db8::"), 32));
+        assert!(!addr_in_prefix(parse_ipv6("2001:db9::1"), parse_ipv6("2001:db8::"), 32));
+
+        // prefixes longer than the address
+        assert!(addr_in_prefix(base, base, 32));
+        assert!(!addr_in_prefix(base, base, 33));
+        assert!(!addr_in_prefix(parse_ipv6("2001:db8::"), parse_ipv6("2001:db8::"), 129));
+    }
+
+    #[test]
+    fn test_is_adjacent_to() {
+        let lower = parse_ipv4net("192.0.2.0", 25);
+        let upper = parse_ipv4net("192.0.2.128", 25);
+        assert!(lower.is_adjacent_to(&upper));
+        assert!(upper.is_adjacent_to(&lower));
+        assert!(!lower.is_adjacent_to(&lower));
+        assert!(!lower.is_adjacent_to(&parse_ipv4net("192.0.3.0", 25)));
+        assert!(upper.is_adjacent_to(&parse_ipv4net("192.0.3.0", 24)));
+
+        // no wrapping around at the end of the address space
+        let top = parse_ipv4net("255.255.255.128", 25);
+        let bottom = parse_ipv4net("0.0.0.0", 25);
+        assert!(!top.is_adjacent_to(&bottom));
+        assert!(!bottom.is_adjacent_to(&top));
+        assert!(top.is_adjacent_to(&parse_ipv4net("255.255.255.0", 25)));
+        assert!(!parse_ipv4net("0.0.0.0", 0).is_adjacent_to(&parse_ipv4net("0.0.0.0", 0)));
+
+        let top = parse_ipv6net("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff", 128);
+        assert!(!top.is_adjacent_to(&parse_ipv6net("::", 128)));
+        assert!(top.is_adjacent_to(&parse_ipv6net("ffff:ffff:ffff:ffff:ffff:ffff:ffff:fffe", 128)));
+    }
+
+    #[test]
+    fn test_boundary_addresses() {
+        assert_eq!(
+            vec![parse_ipv4("192.0.2.4"), parse_ipv4("192.0.2.7")],
+            parse_ipv4net("192.0.2.4", 30).boundary_addresses().collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            vec![parse_ipv4("192.0.2.1")],
+            parse_ipv4net("192.0.2.1", 32).boundary_addresses().collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            vec![parse_ipv6("2001:db8::"), parse_ipv6("2001:db8:0:ffff:ffff:ffff:ffff:ffff")],
+            parse_ipv6net("2001:db8::", 48).boundary_addresses().collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn test_midpoint() {
+        assert_eq!(parse_ipv4("192.0.2.127"), parse_ipv4net("192.0.2.0", 24).midpoint());
+        assert_eq!(parse_ipv4("192.0.2.129"), parse_ipv4net("192.0.2.128", 30).midpoint());
+        assert_eq!(parse_ipv4("192.0.2.1"), parse_ipv4net("192.0.2.1", 32).midpoint());
+        assert_eq!(parse_ipv4("127.255.255.255"), parse_ipv4net("0.0.0.0", 0).midpoint());
+        assert_eq!(parse_ipv4("255.255.255.127"), parse_ipv4net("255.255.255.0", 24).midpoint());
+
+        // only the host bits are counted
+        assert_eq!(parse_ipv4("192.127.0.2"), parse_ipv4netm("192.0.0.2", "255.0.255.255").midpoint());
+
+        assert_eq!(parse_ipv6("2001:db8:7fff:ffff:ffff:ffff:ffff:ffff"), parse_ipv6net("2001:db8::", 32).midpoint());
+        assert_eq!(parse_ipv6("7fff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"), parse_ipv6net("::", 0).midpoint());
+        assert_eq!(
+            parse_ipv6("ffff:ffff:ffff:ffff:7fff:ffff:ffff:ffff"),
+            parse_ipv6net("ffff:ffff:ffff:ffff::", 64).midpoint(),
+        );
+    }
+
+    #[test]
+    fn test_split_in_half() {
+        assert_eq!(
+            Some((parse_ipv4net("192.0.2.0", 25), parse_ipv4net("192.0.2.128", 25))),
+            parse_ipv4net("192.0.2.0", 24).split_in_half(),
+        );
+        assert_eq!(
+            Some((parse_ipv4net("0.0.0.0", 1), parse_ipv4net("128.0.0.0", 1))),
+            parse_ipv4net("0.0.0.0", 0).split_in_half(),
+        );
+        assert_eq!(
+            Some((parse_ipv4net("192.0.2.254", 32), parse_ipv4net("192.0.2.255", 32))),
+            parse_ipv4net("192.0.2.254", 31).split_in_half(),
+        );
+        assert_eq!(None, parse_ipv4net("192.0.2.1", 32).split_in_half());
+
+        // the most significant host bit becomes a network bit
+        assert_eq!(
+            Some((parse_ipv4netm("192.0.0.2", "255.128.255.255"), parse_ipv4netm("192.128.0.2", "255.128.255.255"))),
+            parse_ipv4netm("192.0.0.2", "255.0.255.255").split_in_half(),
+        );
+
+        assert_eq!(
+            Some((parse_ipv6net("2001:db8::", 33), parse_ipv6net("2001:db8:8000::", 33))),
+            parse_ipv6net("2001:db8::", 32).split_in_half(),
+        );
+        assert_eq!(None, parse_ipv6net("2001:db8::1", 128).split_in_half());
+    }
+
+    #[test]
+    fn test_route_order() {
+        let default_route = parse_ipv4net("0.0.0.0", 0);
+        let high_net = parse_ipv4net("192.0.2.0", 24);
+        let low_net = parse_ipv4net("10.0.0.0", 24);
+        let host = parse_ipv4net("10.0.0.1", 32);
+        assert_eq!(Ordering::Less, default_route.route_order(&high_net));
+        assert_eq!(Ordering::Greater, high_net.route_order(&default_route));
+        assert_eq!(Ordering::Less, low_net.route_order(&high_net));
+        assert_eq!(Ordering::Equal, high_net.route_order(&high_net));
+
+        let mut nets = vec![host, high_net, default_route, low_net, parse_ipv4net("128.0.0.0", 1)];
+        nets.sort_by(|a, b| a.route_order(b));
+        assert_eq!(
+            vec![default_route, parse_ipv4net("128.0.0.0", 1), low_net, high_net, host],
+            nets,
+        );
+
+        // contrast with Ord
+        assert_eq!(Ordering::Less, parse_ipv4net("10.0.0.0", 24).cmp(&parse_ipv4net("192.0.0.0", 2)));
+        assert_eq!(Ordering::Greater, parse_ipv4net("10.0.0.0", 24).route_order(&parse_ipv4net("192.0.0.0", 2)));
+
+        assert_eq!(Ordering::Less, parse_ipv6net("::", 0).route_order(&parse_ipv6net("2001:db8::", 32)));
+    }
+
+    #[test]
+    fn test_numeric_order() {
+        let ascending = vec![
+            parse_ipv6net("::", 0),
+            parse_ipv6net("2001:db8::", 32),
+            parse_ipv6net("2001:db8::", 64),
+            parse_ipv6net("2001:db8::", 128),
+            parse_ipv6net("2001:db8::1", 128),
+            parse_ipv6net("2001:db8::ffff", 128),
+            parse_ipv6net("2001:db8::1:0", 112),
+            parse_ipv6net("2001:db8::1:0", 128),
+            parse_ipv6net("2001:db8:0:1::", 64),
+            parse_ipv6net("2001:db8:1::", 48),
+            parse_ipv6net("fe80::", 10),
+        ];
+
+        let mut scrambled = vec![
+            ascending[7], ascending[2], ascending[10], ascending[5], ascending[0], ascending[9],
+            ascending[3], ascending[8], ascending[1], ascending[6], ascending[4],
+        ];
+        scrambled.sort_by(|a, b| a.numeric_order(b));
+        assert_eq!(ascending, scrambled);
+
+        // the order is total, so the starting order does not matter
+        let mut reversed: Vec<_> = ascending.iter().rev().copied().collect();
+        reversed.sort_unstable_by(|a, b| a.numeric_order(b));
+        assert_eq!(ascending, reversed);
+
+        // host routes and their covering aggregate
+        let aggregate = parse_ipv4net("192.0.2.0", 24);
+        let first_host = parse_ipv4net("192.0.2.0", 32);
+        let other_host = parse_ipv4net("192.0.2.1", 32);
+        assert_eq!(Ordering::Less, aggregate.numeric_order(&first_host));
+        assert_eq!(Ordering::Less, first_host.numeric_order(&other_host));
+        assert_eq!(Ordering::Equal, aggregate.numeric_order(&aggregate));
+        assert_eq!(aggregate.cmp(&first_host), aggregate.numeric_order(&first_host));
+    }
+
+    #[cfg(feature = "std-net")]
+    #[test]
+    fn test_contains_std() {
+        let net = parse_ipv4net("192.0.2.0", 24);
+        assert!(net.contains_std("192.0.2.1".parse().unwrap()));
+        assert!(net.contains_std("192.0.2.255".parse().unwrap()));
+        assert!(!net.contains_std("192.0.3.0".parse().unwrap()));
+
+        let net = parse_ipv6net("2001:db8::", 32);
+        assert!(net.contains_std("2001:db8::1".parse().unwrap()));
+        assert!(!net.contains_std("2001:db9::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_from_range() {
+        assert_eq!(
+            Some(parse_ipv4net("192.0.2.0", 24)),
+            IpNetwork::from_range(parse_ipv4("192.0.2.0"), parse_ipv4("192.0.2.255")),
+        );
+        assert_eq!(
+            Some(parse_ipv4net("192.0.2.7", 32)),
+            IpNetwork::from_range(parse_ipv4("192.0.2.7"), parse_ipv4("192.0.2.7")),
+        );
+        assert_eq!(
+            Some(parse_ipv4net("0.0.0.0", 0)),
+            IpNetwork::from_range(parse_ipv4("0.0.0.0"), parse_ipv4("255.255.255.255")),
+        );
+        assert_eq!(
+            Some(parse_ipv6net("2001:db8::", 32)),
+            IpNetwork::from_range(parse_ipv6("2001:db8::"), parse_ipv6("2001:db8:ffff:ffff:ffff:ffff:ffff:ffff")),
+        );
+
+        // misaligned
+        assert_eq!(None, IpNetwork::from_range(parse_ipv4("192.0.2.128"), parse_ipv4("192.0.3.127")));
+        // not a power of two
+        assert_eq!(None, IpNetwork::from_range(parse_ipv4("192.0.2.0"), parse_ipv4("192.0.2.254")));
+        // reversed
+        assert_eq!(None, IpNetwork::from_range(parse_ipv4("192.0.2.255"), parse_ipv4("192.0.2.0")));
+        assert_eq!(None, IpNetwork::from_range(parse_ipv6("2001:db8::1"), parse_ipv6("2001:db8::2")));
+    }
+
+    #[test]
+    fn test_to_cidr_string() {
+        assert_eq!(Some(String::from("192.0.2.0/24")), parse_ipv4net("192.0.2.0", 24).to_cidr_string());
+        assert_eq!(Some(String::from("0.0.0.0/0")), parse_ipv4net("0.0.0.0", 0).to_cidr_string());
+        assert_eq!(Some(String::from("2001:db8::/32")), parse_ipv6net("2001:db8::", 32).to_cidr_string());
+        assert_eq!(None, parse_ipv4netm("192.0.0.2", "255.0.255.255").to_cidr_string());
+        assert_eq!(None, parse_ipv6netm("2001:db8::1", "ffff:ffff::ffff").to_cidr_string());
+    }
+
+    #[test]
+    fn test_to_string_with_mask() {
+        let net = parse_ipv4net("192.0.2.0", 24);
+        assert_eq!("192.0.2.0/24", net.to_string_with_mask(MaskFormat::Cidr));
+        assert_eq!("192.0.2.0/255.255.255.0", net.to_string_with_mask(MaskFormat::Slash));
+        assert_eq!("192.0.2.0 255.255.255.0", net.to_string_with_mask(MaskFormat::Space));
+
+        let noncontiguous = parse_ipv4netm("192.0.0.2", "255.0.255.255");
+        assert_eq!("192.0.0.2/255.0.255.255", noncontiguous.to_string_with_mask(MaskFormat::Cidr));
+        assert_eq!("192.0.0.2 255.0.255.255", noncontiguous.to_string_with_mask(MaskFormat::Space));
+    }
+
+    #[test]
+    fn test_reserved_addresses() {
+        assert_eq!(
+            vec![parse_ipv4("192.0.2.0"), parse_ipv4("192.0.2.255")],
+            parse_ipv4net("192.0.2.0", 24).reserved_addresses(),
+        );
+        assert_eq!(
+            vec![parse_ipv4("192.0.2.4"), parse_ipv4("192.0.2.7")],
+            parse_ipv4net("192.0.2.4", 30).reserved_addresses(),
+        );
+        assert_eq!(Vec::<Ipv4Address>::new(), parse_ipv4net("192.0.2.4", 31).reserved_addresses());
+        assert_eq!(Vec::<Ipv4Address>::new(), parse_ipv4net("192.0.2.4", 32).reserved_addresses());
+        assert_eq!(
+            vec![parse_ipv6("2001:db8::"), parse_ipv6("2001:db8::ffff:ffff:ffff:ffff")],
+            parse_ipv6net("2001:db8::", 64).reserved_addresses(),
+        );
+    }
+
+    #[test]
+    fn test_previous_subnet_base_addr() {
+        assert_eq!(Some(parse_ipv4("192.0.2.0")), parse_ipv4net("192.0.2.64", 26).previous_subnet_base_addr());
+        assert_eq!(None, parse_ipv4net("0.0.0.0", 26).previous_subnet_base_addr());
+        assert_eq!(Some(parse_ipv4("192.0.1.192")), parse_ipv4net("192.0.2.0", 26).previous_subnet_base_addr());
+        assert_eq!(Some(parse_ipv6("2001:db7:ffff::")), parse_ipv6net("2001:db8::", 48).previous_subnet_base_addr());
+
+        // mixed mask
+        let net: IpNetwork<Ipv4Address> = IpNetwork::new_with_mask(
+            parse_ipv4("127.0.1.0"),
+            parse_ipv4("255.0.255.0"),
+        );
+        assert_eq!(Some(parse_ipv4("127.0.0.0")), net.previous_subnet_base_addr());
+    }
+
+    #[test]
+    fn test_host_all_ones() {
+        let net = parse_ipv4net("192.0.2.0", 24);
+        assert_eq!(parse_ipv4("0.0.0.255"), net.host_all_ones());
+        assert_eq!(Some(parse_ipv4("192.0.2.255")), net.broadcast_addr());
+        assert_eq!(Some(parse_ipv4("192.0.2.254")), net.last_host_addr());
+        assert_eq!(Some(parse_ipv4("192.0.3.0")), net.next_subnet_base_addr());
+
+        let net = parse_ipv4net("192.0.2.1", 32);
+        assert_eq!(parse_ipv4("0.0.0.0"), net.host_all_ones());
+        assert_eq!(None, net.broadcast_addr());
+        assert_eq!(None, net.last_host_addr());
+        assert_eq!(Some(parse_ipv4("192.0.2.2")), net.next_subnet_base_addr());
+
+        let net = parse_ipv4net("0.0.0.0", 0);
+        assert_eq!(parse_ipv4("255.255.255.255"), net.host_all_ones());
+        assert_eq!(Some(parse_ipv4("255.255.255.255")), net.broadcast_addr());
+        assert_eq!(Some(parse_ipv4("255.255.255.254")), net.last_host_addr());
+        assert_eq!(None, net.next_subnet_base_addr());
+
+        assert_eq!(parse_ipv6("::ffff:ffff:ffff:ffff"), parse_ipv6net("2001:db8::", 64).host_all_ones());
+
+        // mixed mask: the host bits are counted, wherever they are
+        let net = parse_ipv4netm("10.0.0.0", "255.0.255.0");
+        assert_eq!(parse_ipv4("0.0.255.255"), net.host_all_ones());
+        assert_eq!(Some(parse_ipv4("10.255.0.255")), net.broadcast_addr());
+        assert_eq!(Some(parse_ipv4("10.255.0.254")), net.last_host_addr());
+        assert_eq!(Some(parse_ipv4("10.0.1.0")), net.next_subnet_base_addr());
+
+        let net = parse_ipv6netm("2001:db8::", "ffff:ffff:0:ffff::");
+        assert_eq!(parse_ipv6("::ffff:ffff:ffff:ffff:ffff"), net.host_all_ones());
+        assert_eq!(Some(parse_ipv6("2001:db8:ffff:0:ffff:ffff:ffff:ffff")), net.broadcast_addr());
+        assert_eq!(Some(parse_ipv6("2001:db8:ffff:0:ffff:ffff:ffff:fffe")), net.last_host_addr());
+        assert_eq!(Some(parse_ipv6("2001:db8:0:1::")), net.next_subnet_base_addr());
+    }
+
+    #[test]
+    fn test_relationship() {
+        let net = parse_ipv4net("192.0.2.0", 24);
+        assert_eq!(Relationship::Equal, net.relationship(&parse_ipv4net("192.0.2.0", 24)));
+        assert_eq!(Relationship::Superset, net.relationship(&parse_ipv4net("192.0.2.128", 25)));
+        assert_eq!(Relationship::Subset, net.relationship(&parse_ipv4net("192.0.0.0", 16)));
+        assert_eq!(Relationship::Disjoint, net.relationship(&parse_ipv4net("192.0.3.0", 24)));
+
+        // 10.0.5.0 is in both, but neither contains the other
+        let every_256th = parse_ipv4netm("10.0.0.0", "255.0.0.255");
+        assert_eq!(Relationship::Overlapping, every_256th.relationship(&parse_ipv4net("10.0.0.0", 16)));
+
+        let net6 = parse_ipv6net("2001:db8::", 32);
+        assert_eq!(Relationship::Superset, net6.relationship(&parse_ipv6net("2001:db8:1::", 48)));
+        assert_eq!(Relationship::Disjoint, net6.relationship(&parse_ipv6net("2001:db9::", 32)));
+    }
+
+    #[test]
+    fn test_is_classful() {
+        assert!(parse_ipv4net("10.0.0.0", 8).is_classful());
+        assert!(!parse_ipv4net("10.0.0.0", 16).is_classful());
+        assert!(parse_ipv4net("172.16.0.0", 16).is_classful());
+        assert!(parse_ipv4net("192.0.2.0", 24).is_classful());
+        assert!(!parse_ipv4net("192.0.0.0", 16).is_classful());
+        assert!(!parse_ipv4net("224.0.0.0", 4).is_classful());
+        assert!(!parse_ipv4netm("10.0.0.0", "255.0.0.255").is_classful());
+    }
+
+    #[test]
+    fn test_is_point_to_point() {
+        assert!(parse_ipv4net("192.0.2.0", 31).is_point_to_point());
+        assert!(parse_ipv6net("2001:db8::", 127).is_point_to_point());
+        assert!(!parse_ipv4net("192.0.2.0", 30).is_point_to_point());
+        assert!(!parse_ipv4net("192.0.2.0", 32).is_point_to_point());
+    }
+
+    #[test]
+    fn test_prefix_or_reason() {
+        assert_eq!(Ok(24), parse_ipv4net("192.0.2.0", 24).prefix_or_reason());
+        assert_eq!(Ok(128), parse_ipv6net("2001:db8::1", 128).prefix_or_reason());
+        assert_eq!(Err("non-contiguous subnet mask"), parse_ipv4netm("192.0.0.2", "255.0.255.255").prefix_or_reason());
+    }
+
+    #[test]
+    fn test_ipv4_counts_u64() {
+        let net = parse_ipv4net("192.0.2.0", 24);
+        assert_eq!(256, net.address_count_u64());
+        assert_eq!(254, net.host_count_i64());
+
+        let net = parse_ipv4net("0.0.0.0", 0);
+        assert_eq!(4294967296, net.address_count_u64());
+        assert_eq!(4294967294, net.host_count_i64());
+
+        let net = parse_ipv4net("192.0.2.1", 32);
+        assert_eq!(1, net.address_count_u64());
+        assert_eq!(-1, net.host_count_i64());
+
+        let net = parse_ipv4netm("192.0.0.2", "255.0.255.255");
+        assert_eq!(256, net.address_count_u64());
+        assert_eq!(254, net.host_count_i64());
+    }
+
+    #[test]
+    fn test_ipv6_host_count_u64() {
+        assert_eq!(Some(18446744073709551614), parse_ipv6net("2001:db8::", 64).host_count_u64());
+        assert_eq!(Some(254), parse_ipv6net("2001:db8::", 120).host_count_u64());
+        assert_eq!(Some(0), parse_ipv6net("2001:db8::", 127).host_count_u64());
+        assert_eq!(Some(0), parse_ipv6net("2001:db8::1", 128).host_count_u64());
+        assert_eq!(None, parse_ipv6net("2001:db8::", 63).host_count_u64());
+        assert_eq!(None, parse_ipv6net("2001:db8::", 32).host_count_u64());
+    }
+
+    #[test]
+    fn test_subnet_count_at() {
+        assert_eq!(Some(65536), parse_ipv6net("2001:db8::", 48).subnet_count_at(64));
+        assert_eq!(Some(1), parse_ipv6net("2001:db8::", 48).subnet_count_at(48));
+        assert_eq!(Some(4), parse_ipv4net("192.0.2.0", 24).subnet_count_at(26));
+        assert_eq!(Some(1 << 127), parse_ipv6net("::", 0).subnet_count_at(127));
+
+        // shorter prefix
+        assert_eq!(None, parse_ipv6net("2001:db8::", 48).subnet_count_at(32));
+        // longer than an address
+        assert_eq!(None, parse_ipv4net("192.0.2.0", 24).subnet_count_at(33));
+        // mixed subnet mask
+        assert_eq!(None, parse_ipv4netm("192.0.0.2", "255.0.255.255").subnet_count_at(32));
+        // too many subnets
+        assert_eq!(None, parse_ipv6net("::", 0).subnet_count_at(128));
+    }
+
+    #[test]
+    fn test_address_count_exceeds() {
+        let net = parse_ipv4net("192.0.2.0", 24);
+        assert!(net.address_count_exceeds(0));
+        assert!(net.address_count_exceeds(255));
+        assert!(!net.address_count_exceeds(256));
+        assert!(!net.address_count_exceeds(257));
+
+        let net = parse_ipv4netm("192.64.2.0", "255.240.255.255");
+        assert!(net.address_count_exceeds(15));
+        assert!(!net.address_count_exceeds(16));
+
+        assert!(!parse_ipv6net("2001:db8::1", 128).address_count_exceeds(1));
+        assert!(parse_ipv6net("::", 0).address_count_exceeds(u128::MAX));
+        assert!(!parse_ipv6net("::", 1).address_count_exceeds(u128::MAX));
+        assert!(parse_ipv6net("::", 1).address_count_exceeds(u128::MAX >> 1));
+        assert!(!parse_ipv6net("::", 1).address_count_exceeds(1 << 127));
+    }
 }
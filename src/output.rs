@@ -0,0 +1,228 @@
+use std::io;
+
+use crate::console::{Color, colors_enabled, colors_enabled_stderr, write_colored};
+
+
+/// A target to which the output of a command is written, optionally in color.
+pub trait Output: io::Write {
+    /// Returns a writer, borrowing this output, that outputs text in the given color. If this output
+    /// does not support colors, the text is written without any color information.
+    fn in_color(&mut self, color: Color) -> ColoredWriter<'_>;
+}
+
+
+/// Output to the standard output stream, colored if the terminal supports it.
+pub struct StdoutOutput {
+    stdout: io::Stdout,
+    colors_enabled: bool,
+}
+impl StdoutOutput {
+    /// Creates a new output writing to the standard output stream, using colors if the terminal
+    /// supports them.
+    pub fn new() -> StdoutOutput {
+        StdoutOutput {
+            stdout: io::stdout(),
+            colors_enabled: colors_enabled(),
+        }
+    }
+
+    /// Creates a new output writing to the standard output stream which never uses colors,
+    /// regardless of terminal support and environment variables.
+    pub fn plain() -> StdoutOutput {
+        StdoutOutput {
+            stdout: io::stdout(),
+            colors_enabled: false,
+        }
+    }
+
+    /// Whether this output writes colors to the standard output stream.
+    pub fn colors_enabled(&self) -> bool {
+        self.colors_enabled
+    }
+}
+impl Default for StdoutOutput {
+    fn default() -> StdoutOutput {
+        StdoutOutput::new()
+    }
+}
+impl io::Write for StdoutOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stdout.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdout.flush()
+    }
+}
+impl Output for StdoutOutput {
+    fn in_color(&mut self, color: Color) -> ColoredWriter<'_> {
+        ColoredWriter::new(&mut self.stdout, self.colors_enabled.then_some(color))
+    }
+}
+
+
+/// Output to the standard error stream, colored if the terminal supports it.
+pub struct StderrOutput {
+    stderr: io::Stderr,
+    colors_enabled: bool,
+}
+impl StderrOutput {
+    /// Creates a new output writing to the standard error stream, using colors if the terminal
+    /// supports them.
+    pub fn new() -> StderrOutput {
+        StderrOutput {
+            stderr: io::stderr(),
+            colors_enabled: colors_enabled_stderr(),
+        }
+    }
+
+    /// Creates a new output writing to the standard error stream which never uses colors,
+    /// regardless of terminal support and environment variables.
+    pub fn plain() -> StderrOutput {
+        StderrOutput {
+            stderr: io::stderr(),
+            colors_enabled: false,
+        }
+    }
+
+    /// Whether this output writes colors to the standard error stream.
+    pub fn colors_enabled(&self) -> bool {
+        self.colors_enabled
+    }
+}
+impl Default for StderrOutput {
+    fn default() -> StderrOutput {
+        StderrOutput::new()
+    }
+}
+impl io::Write for StderrOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stderr.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stderr.flush()
+    }
+}
+impl Output for StderrOutput {
+    fn in_color(&mut self, color: Color) -> ColoredWriter<'_> {
+        ColoredWriter::new(&mut self.stderr, self.colors_enabled.then_some(color))
+    }
+}
+
+
+/// Output that discards everything written to it.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct NullOutput;
+impl io::Write for NullOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+impl Output for NullOutput {
+    fn in_color(&mut self, _color: Color) -> ColoredWriter<'_> {
+        ColoredWriter::new(self, None)
+    }
+}
+
+
+/// Output collected in a byte buffer, e.g. for inspection in tests or when running a command
+/// in memory.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct BufferOutput {
+    /// The bytes that have been output.
+    pub buffer: Vec<u8>,
+
+    /// Whether colored output is written with escape sequences.
+    pub colors_enabled: bool,
+}
+impl BufferOutput {
+    /// Creates a new, empty buffer output which ignores colors.
+    pub fn new() -> BufferOutput {
+        BufferOutput {
+            buffer: Vec::new(),
+            colors_enabled: false,
+        }
+    }
+
+    /// Returns the output collected so far as a string.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.buffer).into_owned()
+    }
+}
+impl io::Write for BufferOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+impl Output for BufferOutput {
+    fn in_color(&mut self, color: Color) -> ColoredWriter<'_> {
+        ColoredWriter::new(&mut self.buffer, self.colors_enabled.then_some(color))
+    }
+}
+
+
+/// A writer that outputs everything written to it in a specific color, or without color
+/// information if no color is set.
+pub struct ColoredWriter<'a> {
+    inner: &'a mut dyn io::Write,
+    color: Option<Color>,
+}
+impl<'a> ColoredWriter<'a> {
+    /// Creates a new writer that outputs text to `inner` in the given color, or without color
+    /// information if `color` is `None`.
+    pub fn new(inner: &'a mut dyn io::Write, color: Option<Color>) -> ColoredWriter<'a> {
+        ColoredWriter {
+            inner,
+            color,
+        }
+    }
+}
+impl<'a> io::Write for ColoredWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(color) = self.color {
+            let text = String::from_utf8_lossy(buf);
+            write_colored(self.inner, &text, color)?;
+            Ok(buf.len())
+        } else {
+            self.inner.write(buf)
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_in_color() {
+        let mut output = BufferOutput::new();
+        output.in_color(Color::Red).write_all(b"plain").unwrap();
+        assert_eq!("plain", output.text());
+
+        let mut output = BufferOutput::new();
+        output.colors_enabled = true;
+        output.in_color(Color::Red).write_all(b"red").unwrap();
+        write!(output, " ").unwrap();
+        output.in_color(Color::DarkGreen).write_all(b"green").unwrap();
+        if cfg!(feature = "console") {
+            assert_eq!("\x1b[38;5;9mred\x1b[0m \x1b[32mgreen\x1b[0m", output.text());
+        } else {
+            assert_eq!("red green", output.text());
+        }
+    }
+}
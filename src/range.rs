@@ -0,0 +1,256 @@
+use std::cmp::{max, min};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::addr::{IpAddress, IpAddressParseError};
+use crate::cmds::derange::range_to_subnets;
+use crate::net::IpNetwork;
+
+
+/// The character separating the start from the end of a range, as in `192.0.2.7-192.0.2.42`.
+pub const RANGE_SEPARATOR: char = '-';
+
+
+/// A contiguous range of IP addresses. Both ends are inclusive.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct AddrRange<A: IpAddress> {
+    start: A,
+    end: A,
+}
+impl<A: IpAddress> AddrRange<A> {
+    /// Creates a new range spanning the addresses between the given ends (inclusive). The ends may
+    /// be passed in either order.
+    pub fn new(end_one: A, end_two: A) -> AddrRange<A> {
+        AddrRange {
+            start: min(end_one, end_two),
+            end: max(end_one, end_two),
+        }
+    }
+
+    /// The first address of the range.
+    pub fn start(&self) -> A {
+        self.start
+    }
+
+    /// The last address of the range.
+    pub fn end(&self) -> A {
+        self.end
+    }
+
+    /// Whether the given address is contained in this range.
+    pub fn contains(&self, addr: &A) -> bool {
+        self.start <= *addr && *addr <= self.end
+    }
+
+    /// Whether this range and the given range have at least one address in common.
+    pub fn intersects(&self, other: &AddrRange<A>) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    /// The number of addresses in this range. Returns `None` if the number does not fit into a
+    /// `u128`, which is only the case for the whole IPv6 address space.
+    pub fn length(&self) -> Option<u128> {
+        let difference = self.end.subtract_addr(&self.start)
+            .expect("end is not less than start");
+        let difference_value = difference.to_bytes()
+            .iter()
+            .fold(0u128, |value, b| (value << 8) | u128::from(*b));
+        difference_value.checked_add(1)
+    }
+
+    /// Converts this range into the equivalent list of networks (see `range_to_subnets`).
+    pub fn to_networks(self) -> Vec<IpNetwork<A>> {
+        range_to_subnets(self.start, self.end)
+    }
+}
+impl<A: IpAddress> fmt::Display for AddrRange<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.start, RANGE_SEPARATOR, self.end)
+    }
+}
+impl<A: IpAddress + FromStr<Err = IpAddressParseError>> FromStr for AddrRange<A> {
+    type Err = ParseAddrRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start_str, end_str) = s.split_once(RANGE_SEPARATOR)
+            .ok_or_else(|| ParseAddrRangeError::NoSeparator(String::from(s)))?;
+        let start: A = start_str.trim().parse()
+            .map_err(ParseAddrRangeError::Start)?;
+        let end: A = end_str.trim().parse()
+            .map_err(ParseAddrRangeError::End)?;
+        Ok(AddrRange::new(start, end))
+    }
+}
+
+
+/// Merges overlapping and adjacent ranges (where the end of one range directly precedes the start
+/// of the next) in the given list. Returns the merged ranges sorted by their start address.
+pub fn merge_ranges<A: IpAddress>(mut ranges: Vec<AddrRange<A>>) -> Vec<AddrRange<A>> {
+    ranges.sort_unstable();
+
+    let mut merged: Vec<AddrRange<A>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        if let Some(last) = merged.last_mut() {
+            // if the last range extends to the end of the address space, everything overlaps it
+            let touches_last = last.end.add_offset(1)
+                .map(|after_last| range.start <= after_last)
+                .unwrap_or(true);
+            if touches_last {
+                last.end = max(last.end, range.end);
+                continue;
+            }
+        }
+        merged.push(range);
+    }
+    merged
+}
+
+
+/// An error that occurs when attempting to parse an address range.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseAddrRangeError {
+    /// The string does not contain the separator between the start and the end of the range. The
+    /// contained value is the string.
+    NoSeparator(String),
+
+    /// The start of the range could not be parsed.
+    Start(IpAddressParseError),
+
+    /// The end of the range could not be parsed.
+    End(IpAddressParseError),
+}
+impl fmt::Display for ParseAddrRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseAddrRangeError::NoSeparator(s)
+                => write!(f, "address range {:?} does not have the form START{}END", s, RANGE_SEPARATOR),
+            ParseAddrRangeError::Start(e)
+                => write!(f, "failed to parse start of address range: {}", e),
+            ParseAddrRangeError::End(e)
+                => write!(f, "failed to parse end of address range: {}", e),
+        }
+    }
+}
+impl Error for ParseAddrRangeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseAddrRangeError::NoSeparator(_) => None,
+            ParseAddrRangeError::Start(e) => Some(e),
+            ParseAddrRangeError::End(e) => Some(e),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::addr::{Ipv4Address, Ipv6Address};
+    use crate::net::test::{parse_ipv4, parse_ipv4net, parse_ipv6, parse_ipv6net};
+
+    #[test]
+    fn test_parse_range() {
+        let range: AddrRange<Ipv4Address> = "192.0.2.7-192.0.2.42".parse().unwrap();
+        assert_eq!(parse_ipv4("192.0.2.7"), range.start());
+        assert_eq!(parse_ipv4("192.0.2.42"), range.end());
+        assert_eq!("192.0.2.7-192.0.2.42", range.to_string());
+
+        // whitespace is ignored and the ends may be swapped
+        assert_eq!(Ok(range), "192.0.2.42 - 192.0.2.7".parse());
+
+        let range: AddrRange<Ipv6Address> = "2001:db8::1-2001:db8::ff".parse().unwrap();
+        assert_eq!(parse_ipv6("2001:db8::1"), range.start());
+        assert_eq!(parse_ipv6("2001:db8::ff"), range.end());
+
+        assert_eq!(
+            Err(ParseAddrRangeError::NoSeparator(String::from("192.0.2.7"))),
+            "192.0.2.7".parse::<AddrRange<Ipv4Address>>(),
+        );
+        assert!(matches!("192.0.2-192.0.2.42".parse::<AddrRange<Ipv4Address>>(), Err(ParseAddrRangeError::Start(_))));
+        assert!(matches!("192.0.2.7-2001:db8::1".parse::<AddrRange<Ipv4Address>>(), Err(ParseAddrRangeError::End(_))));
+    }
+
+    #[test]
+    fn test_range_contains() {
+        let range = AddrRange::new(parse_ipv4("192.0.2.7"), parse_ipv4("192.0.2.42"));
+        assert!(range.contains(&parse_ipv4("192.0.2.7")));
+        assert!(range.contains(&parse_ipv4("192.0.2.20")));
+        assert!(range.contains(&parse_ipv4("192.0.2.42")));
+        assert!(!range.contains(&parse_ipv4("192.0.2.6")));
+        assert!(!range.contains(&parse_ipv4("192.0.2.43")));
+
+        assert!(range.intersects(&AddrRange::new(parse_ipv4("192.0.2.42"), parse_ipv4("192.0.2.50"))));
+        assert!(range.intersects(&AddrRange::new(parse_ipv4("192.0.2.0"), parse_ipv4("192.0.2.255"))));
+        assert!(range.intersects(&AddrRange::new(parse_ipv4("192.0.2.10"), parse_ipv4("192.0.2.11"))));
+        assert!(!range.intersects(&AddrRange::new(parse_ipv4("192.0.2.43"), parse_ipv4("192.0.2.50"))));
+        assert!(!range.intersects(&AddrRange::new(parse_ipv4("192.0.2.0"), parse_ipv4("192.0.2.6"))));
+    }
+
+    #[test]
+    fn test_range_length() {
+        assert_eq!(Some(36), AddrRange::new(parse_ipv4("192.0.2.7"), parse_ipv4("192.0.2.42")).length());
+        assert_eq!(Some(1), AddrRange::new(parse_ipv4("192.0.2.7"), parse_ipv4("192.0.2.7")).length());
+        assert_eq!(Some(1 << 32), AddrRange::new(parse_ipv4("0.0.0.0"), parse_ipv4("255.255.255.255")).length());
+        assert_eq!(Some(1 << 64), AddrRange::new(parse_ipv6("2001:db8::"), parse_ipv6("2001:db8::ffff:ffff:ffff:ffff")).length());
+        assert_eq!(None, AddrRange::new(parse_ipv6("::"), parse_ipv6("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff")).length());
+    }
+
+    #[test]
+    fn test_merge_ranges() {
+        fn v4_range(start: &str, end: &str) -> AddrRange<Ipv4Address> {
+            AddrRange::new(parse_ipv4(start), parse_ipv4(end))
+        }
+
+        // adjacent
+        assert_eq!(
+            vec![v4_range("0.0.0.0", "0.0.0.20")],
+            merge_ranges(vec![v4_range("0.0.0.0", "0.0.0.10"), v4_range("0.0.0.11", "0.0.0.20")]),
+        );
+
+        // overlapping, contained and unsorted
+        assert_eq!(
+            vec![v4_range("192.0.2.0", "192.0.2.50")],
+            merge_ranges(vec![
+                v4_range("192.0.2.30", "192.0.2.50"),
+                v4_range("192.0.2.0", "192.0.2.40"),
+                v4_range("192.0.2.5", "192.0.2.6"),
+            ]),
+        );
+
+        // disjoint
+        let disjoint = vec![v4_range("0.0.0.0", "0.0.0.10"), v4_range("0.0.0.12", "0.0.0.20")];
+        assert_eq!(disjoint, merge_ranges(disjoint.clone()));
+        assert_eq!(disjoint, merge_ranges(vec![disjoint[1], disjoint[0]]));
+
+        // end of the address space
+        assert_eq!(
+            vec![v4_range("255.255.255.0", "255.255.255.255")],
+            merge_ranges(vec![v4_range("255.255.255.0", "255.255.255.255"), v4_range("255.255.255.255", "255.255.255.255")]),
+        );
+
+        assert_eq!(Vec::<AddrRange<Ipv4Address>>::new(), merge_ranges(Vec::new()));
+    }
+
+    #[test]
+    fn test_range_to_networks() {
+        let range = AddrRange::new(parse_ipv4("192.0.2.0"), parse_ipv4("192.0.2.255"));
+        assert_eq!(vec![parse_ipv4net("192.0.2.0", 24)], range.to_networks());
+
+        let range = AddrRange::new(parse_ipv4("192.0.2.7"), parse_ipv4("192.0.2.42"));
+        assert_eq!(
+            vec![
+                parse_ipv4net("192.0.2.7", 32),
+                parse_ipv4net("192.0.2.8", 29),
+                parse_ipv4net("192.0.2.16", 28),
+                parse_ipv4net("192.0.2.32", 29),
+                parse_ipv4net("192.0.2.40", 31),
+                parse_ipv4net("192.0.2.42", 32),
+            ],
+            range.to_networks(),
+        );
+
+        let range = AddrRange::new(parse_ipv6("2001:db8::"), parse_ipv6("2001:db8::ffff"));
+        assert_eq!(vec![parse_ipv6net("2001:db8::", 112)], range.to_networks());
+    }
+}
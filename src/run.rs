@@ -0,0 +1,387 @@
+use std::io::{self, BufRead, Write};
+
+use crate::cmds::{AddressFamily, CommandContext, CommandResult, ErrorKind};
+use crate::console::{Color, write_in_color};
+use crate::output::{BufferOutput, NullOutput, Output};
+
+
+/// Runs ripcalc with the given arguments, the first of which is the program name, and returns the
+/// collected standard output, the collected standard error output and the exit code.
+///
+/// Commands that read networks from standard input (`-`) receive no input; use `run_with_input` to
+/// supply some. `--no-color` is recognized but has no effect, as the collected output is never
+/// colored.
+pub fn run(args: &[&str]) -> (String, String, i32) {
+    run_with_input(args, "")
+}
+
+/// Runs ripcalc like `run`, passing `input` to commands that read networks from standard input.
+pub fn run_with_input(args: &[&str], input: &str) -> (String, String, i32) {
+    let args: Vec<String> = args.iter()
+        .map(|arg| String::from(*arg))
+        .collect();
+    let mut input = input.as_bytes();
+    let mut stdout = BufferOutput::new();
+    let mut stderr = BufferOutput::new();
+    let (ctx, args) = extract_command_context(&args);
+    let result = dispatch_with_context(&args, &ctx, &mut input, &mut stdout, &mut stderr);
+    (stdout.text(), stderr.text(), result.exit_code())
+}
+
+/// Runs the command selected by the given arguments, the first of which is the program name.
+///
+/// The options that apply to all modes of operation are removed from the arguments and passed to
+/// the command as a `CommandContext`; commands that read networks from standard input receive no
+/// input. `--no-color` is only recorded in the context, as it has to be applied when the outputs are
+/// created; use `extract_command_context` and `dispatch_with_context` to apply it beforehand or to
+/// supply input.
+pub fn dispatch<O: Output, E: Output>(args: &[String], stdout: &mut O, stderr: &mut E) -> CommandResult {
+    let (ctx, args) = extract_command_context(args);
+    dispatch_with_context(&args, &ctx, &mut io::empty(), stdout, stderr)
+}
+
+/// Runs the command selected by the given arguments, which no longer contain the options that apply
+/// to all modes of operation (see `extract_command_context`). Commands that read networks from
+/// standard input read them from `input` instead.
+pub fn dispatch_with_context<O: Output, E: Output>(
+    args: &[String], ctx: &CommandContext, input: &mut dyn BufRead, stdout: &mut O, stderr: &mut E,
+) -> CommandResult {
+    let mut null_output = NullOutput;
+    let stdout: &mut dyn Output = if ctx.quiet { &mut null_output } else { stdout };
+    let stderr: &mut dyn Output = stderr;
+
+    if args.len() < 2 {
+        return usage(ctx, stderr);
+    }
+
+    if args[1] == "-m" || args[1] == "--minimize" {
+        crate::cmds::minimize::minimize(args, ctx, input, stdout, stderr)
+    } else if args[1] == "-d" || args[1] == "--derange" {
+        crate::cmds::derange::derange(args, ctx, stdout, stderr)
+    } else if args[1] == "--cover" {
+        crate::cmds::cover::cover(args, ctx, input, stdout, stderr)
+    } else if args[1] == "--cover-file" {
+        crate::cmds::cover::cover_file(args, ctx, stdout, stderr)
+    } else if args[1] == "--summarize" {
+        crate::cmds::derange::summarize(args, ctx, stdout, stderr)
+    } else if args[1] == "-s" || args[1] == "--split" {
+        crate::cmds::split::split(args, ctx, stdout, stderr)
+    } else if args[1] == "-r" || args[1] == "--resize" {
+        crate::cmds::resize::resize(args, ctx, stdout, stderr)
+    } else if args[1] == "-e" || args[1] == "--enumerate" {
+        crate::cmds::enumerate::enumerate(args, ctx, input, stdout, stderr)
+    } else if args[1] == "--cisco" {
+        crate::cmds::cisco::cisco(args, ctx, stdout, stderr)
+    } else if args[1] == "--subnets" {
+        crate::cmds::subnets::subnets(args, ctx, stdout, stderr)
+    } else if args[1] == "--map" {
+        crate::cmds::map::map(args, ctx, stdout, stderr)
+    } else if args[1] == "--diff" {
+        crate::cmds::diff::diff(args, ctx, stdout, stderr)
+    } else if args[1] == "--hex" {
+        crate::cmds::hex::hex(args, ctx, stdout, stderr)
+    } else if args[1] == "--hex-out" {
+        crate::cmds::hex::hex_out(args, ctx, stdout, stderr)
+    } else if args[1] == "--compare" {
+        crate::cmds::compare::compare(args, ctx, stdout, stderr)
+    } else if args[1] == "--canonical" {
+        crate::cmds::canonical::canonical(args, ctx, stdout, stderr)
+    } else if args[1] == "--next" {
+        crate::cmds::adjacent::next(args, ctx, stdout, stderr)
+    } else if args[1] == "--prev" {
+        crate::cmds::adjacent::prev(args, ctx, stdout, stderr)
+    } else if args[1] == "--histogram" {
+        crate::cmds::histogram::histogram(args, ctx, input, stdout, stderr)
+    } else if args[1] == "--color-test" {
+        color_test(ctx, stdout, stderr)
+    } else if args[1] == "--help" {
+        if let Err(e) = print_usage(stderr) {
+            return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+        }
+        CommandResult::Ok
+    } else {
+        crate::cmds::show_net::show_net(args, ctx, stdout, stderr)
+    }
+}
+
+/// Removes the options that apply to all modes of operation from the arguments and returns them
+/// along with the remaining arguments.
+pub fn extract_command_context(args: &[String]) -> (CommandContext, Vec<String>) {
+    let mut ctx = CommandContext::default();
+    let mut remaining_args = Vec::with_capacity(args.len());
+    for (i, arg) in args.iter().enumerate() {
+        if i == 0 {
+            // program name
+            remaining_args.push(arg.clone());
+        } else if arg == "--no-color" {
+            ctx.no_color = true;
+        } else if arg == "--errors-json" {
+            ctx.errors_json = true;
+        } else if arg == "--ipv4" {
+            ctx.family = Some(AddressFamily::Ipv4);
+        } else if arg == "--ipv6" {
+            ctx.family = Some(AddressFamily::Ipv6);
+        } else if arg == "--quiet" {
+            ctx.quiet = true;
+        } else {
+            remaining_args.push(arg.clone());
+        }
+    }
+    (ctx, remaining_args)
+}
+
+fn color_test(ctx: &CommandContext, stdout: &mut dyn Output, stderr: &mut dyn Output) -> CommandResult {
+    let colors = [
+        ("Black", Color::Black),
+        ("DarkBlue", Color::DarkBlue),
+        ("DarkGreen", Color::DarkGreen),
+        ("DarkCyan", Color::DarkCyan),
+        ("DarkRed", Color::DarkRed),
+        ("DarkMagenta", Color::DarkMagenta),
+        ("DarkYellow", Color::DarkYellow),
+        ("Gray", Color::Gray),
+        ("DarkGray", Color::DarkGray),
+        ("Blue", Color::Blue),
+        ("Green", Color::Green),
+        ("Cyan", Color::Cyan),
+        ("Red", Color::Red),
+        ("Magenta", Color::Magenta),
+        ("Yellow", Color::Yellow),
+        ("White", Color::White),
+    ];
+
+    for (name, color) in colors {
+        if let Err(e) = write_in_color(stdout, name, Some(color), 20) {
+            return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+        }
+    }
+    if let Err(e) = writeln!(stdout) {
+        return ctx.fail(stderr, ErrorKind::Io, format_args!("failed to write output: {}", e), 1);
+    }
+    CommandResult::Ok
+}
+
+/// Reports that the arguments are invalid, outputting the usage information unless errors are
+/// reported as JSON, and returns the corresponding failed command result.
+pub fn usage<E: Output + ?Sized>(ctx: &CommandContext, stderr: &mut E) -> CommandResult {
+    if ctx.errors_json {
+        ctx.fail(stderr, ErrorKind::Usage, format_args!("invalid arguments; see ripcalc --help for usage"), 1)
+    } else {
+        // there is no other place to report a failure to write the usage information
+        let _ = print_usage(stderr);
+        CommandResult::Error(1)
+    }
+}
+
+/// Writes the usage information to the given output.
+pub fn print_usage<W: Write + ?Sized>(out: &mut W) -> io::Result<()> {
+    writeln!(out, "Usage: ripcalc [--group-digits] [--no-arrow] [--expand] [--describe-mask] [--warn-host-bits] [--explain-bits] [--p2p] [--table] [--no-hosts] [--mask-hex] IPADDRESS/SUBNET...")?;
+    writeln!(out, "       ripcalc -m|--minimize [--merge-only] [--explain|--json] [--sort-routes] [--mask-format=FORMAT] IPADDRESS/SUBNET|-...")?;
+    writeln!(out, "       ripcalc -d|--derange [--json] [--mask-format=FORMAT] IPADDRESS|IPADDRESS/SUBNET IPADDRESS|IPADDRESS/SUBNET")?;
+    writeln!(out, "       ripcalc -s|--split IPADDRESS/CIDRPREFIX HOSTCOUNT...")?;
+    writeln!(out, "       ripcalc --cover IPADDRESS/SUBNET|-...")?;
+    writeln!(out, "       ripcalc --cover-file FILE")?;
+    writeln!(out, "       ripcalc --summarize [--json] [--mask-format=FORMAT] IPADDRESS|IPADDRESS/SUBNET IPADDRESS|IPADDRESS/SUBNET")?;
+    writeln!(out, "       ripcalc -r|--resize IPADDRESS/SUBNET SUBNET")?;
+    writeln!(out, "       ripcalc -e|--enumerate [--reverse] [--boundaries] [--padded] [--separator SEPARATOR] IPADDRESS/SUBNET|-...")?;
+    writeln!(out, "       ripcalc --subnets IPADDRESS/SUBNET CIDRPREFIX[-CIDRPREFIX]")?;
+    writeln!(out, "       ripcalc --map IPADDRESS/SUBNET IPADDRESS/SUBNET...")?;
+    writeln!(out, "       ripcalc --cisco IPADDRESS/SUBNET...")?;
+    writeln!(out, "       ripcalc --diff FILE FILE")?;
+    writeln!(out, "       ripcalc --diff IPADDRESS/SUBNET... -- IPADDRESS/SUBNET...")?;
+    writeln!(out, "       ripcalc --hex HEXADDRESS...")?;
+    writeln!(out, "       ripcalc --hex-out IPADDRESS...")?;
+    writeln!(out, "       ripcalc --compare IPADDRESS IPADDRESS")?;
+    writeln!(out, "       ripcalc --canonical IPV6ADDRESS...")?;
+    writeln!(out, "       ripcalc --next|--prev IPADDRESS...")?;
+    writeln!(out, "       ripcalc --histogram IPADDRESS/SUBNET|-...")?;
+    writeln!(out)?;
+    writeln!(out, "SUBNET is one of: SUBNETMASK")?;
+    writeln!(out, "                  CIDRPREFIX")?;
+    writeln!(out, "                  -WILDCARD")?;
+    writeln!(out)?;
+    writeln!(out, "FORMAT is one of: cidr (default), slash (NETWORK/SUBNETMASK), space (NETWORK SUBNETMASK)")?;
+    writeln!(out)?;
+    writeln!(out, "IPv4 and IPv6 are supported, but cannot be mixed within an invocation.")?;
+    writeln!(out)?;
+    writeln!(out, "For --minimize, --cover, --enumerate and --histogram, \"-\" reads networks from standard")?;
+    writeln!(out, "input, one per line. Text following the network on the same line is ignored, as are lines")?;
+    writeln!(out, "starting with \"!\" or \"#\". For --minimize, --cover and --histogram, an argument may also")?;
+    writeln!(out, "contain multiple comma-separated networks.")?;
+    writeln!(out)?;
+    writeln!(out, "--no-color may be passed in any mode to disable colored output.")?;
+    writeln!(out, "--errors-json may be passed in any mode to report errors as JSON objects.")?;
+    writeln!(out, "--ipv4 or --ipv6 may be passed in any mode to only accept addresses of that IP version.")?;
+    writeln!(out, "--quiet may be passed in any mode to suppress the normal output.")?;
+    Ok(())
+}
+
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn to_args(args: &[&str]) -> Vec<String> {
+        args.iter()
+            .map(|a| String::from(*a))
+            .collect()
+    }
+
+    fn dispatch_strs(args: &[&str]) -> (String, String, CommandResult) {
+        let mut stdout = BufferOutput::new();
+        let mut stderr = BufferOutput::new();
+        let result = dispatch(&to_args(args), &mut stdout, &mut stderr);
+        (stdout.text(), stderr.text(), result)
+    }
+
+    #[test]
+    fn test_extract_command_context() {
+        let (ctx, args) = extract_command_context(&to_args(&["ripcalc", "--no-color", "192.0.2.0/24"]));
+        assert!(ctx.no_color);
+        assert_eq!(to_args(&["ripcalc", "192.0.2.0/24"]), args);
+
+        let (ctx, args) = extract_command_context(&to_args(&["ripcalc", "--resize", "192.0.2.0/24", "--no-color", "25"]));
+        assert!(ctx.no_color);
+        assert_eq!(to_args(&["ripcalc", "--resize", "192.0.2.0/24", "25"]), args);
+
+        let (ctx, args) = extract_command_context(&to_args(&["ripcalc", "--errors-json", "--minimize", "192.0.2.0/24"]));
+        assert!(ctx.errors_json);
+        assert!(!ctx.no_color);
+        assert_eq!(to_args(&["ripcalc", "--minimize", "192.0.2.0/24"]), args);
+
+        let (ctx, args) = extract_command_context(&to_args(&["ripcalc", "--ipv6", "--enumerate", "2001:db8::/126"]));
+        assert_eq!(Some(AddressFamily::Ipv6), ctx.family);
+        assert_eq!(to_args(&["ripcalc", "--enumerate", "2001:db8::/126"]), args);
+
+        let (ctx, args) = extract_command_context(&to_args(&["ripcalc", "--ipv6", "192.0.2.0/24", "--ipv4"]));
+        assert_eq!(Some(AddressFamily::Ipv4), ctx.family);
+        assert_eq!(to_args(&["ripcalc", "192.0.2.0/24"]), args);
+
+        let (ctx, args) = extract_command_context(&to_args(&["ripcalc", "--compare", "--quiet", "192.0.2.1", "192.0.2.2"]));
+        assert!(ctx.quiet);
+        assert_eq!(to_args(&["ripcalc", "--compare", "192.0.2.1", "192.0.2.2"]), args);
+
+        let (ctx, args) = extract_command_context(&to_args(&["ripcalc", "192.0.2.0/24"]));
+        assert_eq!(CommandContext::default(), ctx);
+        assert_eq!(to_args(&["ripcalc", "192.0.2.0/24"]), args);
+    }
+
+    #[test]
+    fn test_run() {
+        let (stdout, stderr, exit_code) = run(&["ripcalc", "192.0.2.0/24"]);
+        assert_eq!(0, exit_code);
+        assert!(stdout.contains("Network:"));
+        assert!(stdout.contains("192.0.2.0/24"));
+        assert_eq!("", stderr);
+
+        let (stdout, stderr, exit_code) = run(&["ripcalc", "192.0.2.0~24"]);
+        assert_eq!(1, exit_code);
+        assert_eq!("", stdout);
+        assert!(stderr.contains("unrecognized network specification"));
+
+        let (stdout, stderr, exit_code) = run(&["ripcalc", "192.0.2.1", "198.51.100.7"]);
+        assert_eq!(1, exit_code);
+        assert_eq!("", stdout);
+        assert!(stderr.contains("unrecognized network specification: \"192.0.2.1\""), "{}", stderr);
+
+        let (stdout, _stderr, exit_code) = run(&["ripcalc", "--resize", "192.0.2.0/24", "25"]);
+        assert_eq!(0, exit_code);
+        assert!(stdout.contains("192.0.2.128/25"));
+
+        let (stdout, stderr, exit_code) = run(&["ripcalc", "--errors-json", "--cisco", "2001:db8::/32"]);
+        assert_eq!(1, exit_code);
+        assert_eq!("", stdout);
+        assert!(stderr.starts_with("{\"error\":\"\\\"2001:db8::/32\\\" is not an IPv4 network"));
+        assert!(stderr.ends_with(",\"kind\":\"input\"}\n"));
+
+        let (stdout, stderr, exit_code) = run(&["ripcalc", "--ipv6", "192.0.2.0/24"]);
+        assert_eq!(1, exit_code);
+        assert_eq!("", stdout);
+        assert!(stderr.contains("is not an IPv6 network"), "{}", stderr);
+
+        let (stdout, _stderr, exit_code) = run(&["ripcalc", "--ipv4", "--cover", "192.0.2.0/24", "192.0.3.0/24"]);
+        assert_eq!(0, exit_code);
+        assert_eq!("192.0.2.0/23\n", stdout);
+
+        let (_stdout, stderr, exit_code) = run(&["ripcalc", "--errors-json", "--ipv4", "--next", "2001:db8::1"]);
+        assert_eq!(1, exit_code);
+        assert!(stderr.starts_with("{\"error\":"));
+        assert!(stderr.ends_with(",\"kind\":\"address\"}\n"));
+
+        let (_stdout, stderr, exit_code) = run(&["ripcalc", "--errors-json", "--hex"]);
+        assert_eq!(1, exit_code);
+        assert_eq!("{\"error\":\"invalid arguments; see ripcalc --help for usage\",\"kind\":\"usage\"}\n", stderr);
+    }
+
+    #[test]
+    fn test_run_with_input() {
+        let (stdout, stderr, exit_code) = run_with_input(&["ripcalc", "--minimize", "-"], "192.0.2.0/25\n192.0.2.128/25\n");
+        assert_eq!(0, exit_code);
+        assert_eq!("192.0.2.0/24\n", stdout);
+        assert_eq!("", stderr);
+
+        let (stdout, _stderr, exit_code) = run_with_input(&["ripcalc", "--cover", "-", "192.0.4.0/24"], "192.0.2.0/24\n");
+        assert_eq!(0, exit_code);
+        assert_eq!("192.0.0.0/21\n", stdout);
+
+        let (stdout, _stderr, exit_code) = run_with_input(&["ripcalc", "--enumerate", "-"], "192.0.2.0/31\n");
+        assert_eq!(0, exit_code);
+        assert_eq!("192.0.2.0\n192.0.2.1\n", stdout);
+
+        // run() passes no input instead of reading from the real standard input
+        let (stdout, _stderr, exit_code) = run(&["ripcalc", "--histogram", "-"]);
+        assert_eq!(0, exit_code);
+        assert_eq!("", stdout);
+    }
+
+    #[test]
+    fn test_dispatch() {
+        // without further arguments, each command reports a usage error itself instead of the
+        // flag being passed on to the network display
+        let command_flags = [
+            "-m", "--minimize", "-d", "--derange", "--cover", "--cover-file", "--summarize", "-s",
+            "--split", "-r", "--resize", "-e", "--enumerate", "--cisco", "--subnets", "--map",
+            "--diff", "--hex", "--hex-out", "--compare", "--canonical", "--next", "--prev",
+            "--histogram",
+        ];
+        for flag in command_flags {
+            let (stdout, stderr, result) = dispatch_strs(&["ripcalc", flag]);
+            assert_eq!(CommandResult::Error(1), result, "{}", flag);
+            assert_eq!("", stdout, "{}", flag);
+            assert!(stderr.starts_with("Usage: ripcalc "), "{}", flag);
+        }
+
+        let (stdout, stderr, result) = dispatch_strs(&["ripcalc", "--help"]);
+        assert_eq!(CommandResult::Ok, result);
+        assert_eq!("", stdout);
+        assert!(stderr.starts_with("Usage: ripcalc "));
+        for flag in command_flags.iter().filter(|f| f.starts_with("--")) {
+            assert!(stderr.contains(flag), "{}", flag);
+        }
+
+        let (stdout, _stderr, result) = dispatch_strs(&["ripcalc", "--color-test"]);
+        assert_eq!(CommandResult::Ok, result);
+        assert!(stdout.starts_with("Black"));
+        assert!(stdout.contains("White"));
+
+        let (stdout, _stderr, result) = dispatch_strs(&["ripcalc", "192.0.2.0/24"]);
+        assert_eq!(CommandResult::Ok, result);
+        assert!(stdout.contains("Network:"));
+
+        let (_stdout, _stderr, result) = dispatch_strs(&["ripcalc"]);
+        assert_eq!(CommandResult::Error(1), result);
+    }
+
+    #[test]
+    fn test_quiet() {
+        assert_eq!((String::new(), String::new(), 2), run(&["ripcalc", "--compare", "--quiet", "192.0.2.1", "192.0.2.2"]));
+        assert_eq!((String::new(), String::new(), 3), run(&["ripcalc", "--quiet", "--compare", "192.0.2.2", "192.0.2.1"]));
+        assert_eq!((String::new(), String::new(), 0), run(&["ripcalc", "--quiet", "192.0.2.0/24"]));
+
+        let (stdout, stderr, exit_code) = run(&["ripcalc", "--quiet", "192.0.2.0~24"]);
+        assert_eq!("", stdout);
+        assert!(stderr.contains("unrecognized network specification"));
+        assert_eq!(1, exit_code);
+    }
+}
@@ -0,0 +1,90 @@
+use std::fmt;
+
+use crate::addr::IpAddress;
+use crate::net::{IpNetwork, MaskFormat};
+
+
+/// The character separating a network from its tag, as in `192.0.2.0/24=lan`.
+pub const TAG_SEPARATOR: char = '=';
+
+
+/// An IP network with an optional tag attached, e.g. a name from an inventory.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct TaggedNetwork<A: IpAddress> {
+    /// The network.
+    pub net: IpNetwork<A>,
+
+    /// The tag attached to the network, if any.
+    pub tag: Option<String>,
+}
+impl<A: IpAddress> TaggedNetwork<A> {
+    /// Creates a new network with the given tag.
+    pub fn new(net: IpNetwork<A>, tag: Option<String>) -> TaggedNetwork<A> {
+        TaggedNetwork {
+            net,
+            tag,
+        }
+    }
+
+    /// Returns the tagged network as a string with the subnet mask in the given format.
+    pub fn to_string_with_mask(&self, format: MaskFormat) -> String {
+        match &self.tag {
+            Some(tag) => format!("{}{}{}", self.net.to_string_with_mask(format), TAG_SEPARATOR, tag),
+            None => self.net.to_string_with_mask(format),
+        }
+    }
+}
+impl<A: IpAddress> From<IpNetwork<A>> for TaggedNetwork<A> {
+    fn from(net: IpNetwork<A>) -> Self {
+        TaggedNetwork::new(net, None)
+    }
+}
+impl<A: IpAddress> fmt::Display for TaggedNetwork<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.net)?;
+        if let Some(tag) = &self.tag {
+            write!(f, "{}{}", TAG_SEPARATOR, tag)?;
+        }
+        Ok(())
+    }
+}
+
+
+/// Splits a specification of the form `NETWORK[=TAG]` into the network and the tag. An empty tag
+/// is treated like a missing tag.
+pub fn split_tag(spec: &str) -> (&str, Option<&str>) {
+    match spec.split_once(TAG_SEPARATOR) {
+        Some((net_str, tag)) if !tag.is_empty() => (net_str, Some(tag)),
+        Some((net_str, _empty_tag)) => (net_str, None),
+        None => (spec, None),
+    }
+}
+
+/// Returns the tag of the first of the tagged networks that equals the given network and has a tag.
+pub fn find_tag<'a, A: IpAddress>(tagged: &'a [TaggedNetwork<A>], net: &IpNetwork<A>) -> Option<&'a str> {
+    tagged.iter()
+        .filter(|t| &t.net == net)
+        .find_map(|t| t.tag.as_deref())
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::net::test::parse_ipv4net;
+
+    #[test]
+    fn test_split_tag() {
+        assert_eq!(("192.0.2.0/24", Some("lan")), split_tag("192.0.2.0/24=lan"));
+        assert_eq!(("192.0.2.0/24", Some("a=b")), split_tag("192.0.2.0/24=a=b"));
+        assert_eq!(("192.0.2.0/24", None), split_tag("192.0.2.0/24="));
+        assert_eq!(("192.0.2.0/24", None), split_tag("192.0.2.0/24"));
+    }
+
+    #[test]
+    fn test_display() {
+        let net = parse_ipv4net("192.0.2.0", 24);
+        assert_eq!("192.0.2.0/24=lan", TaggedNetwork::new(net, Some(String::from("lan"))).to_string());
+        assert_eq!("192.0.2.0/24", TaggedNetwork::from(net).to_string());
+    }
+}